@@ -1,9 +1,6 @@
-use std::collections::HashMap;
-use std::path::Display;
-use std::str::FromStr;
 use ucdf::{
-    parse, AccessMode, ConnectionParams, DataType, DataValue, Endpoint, Error, Field, Metadata,
-    Parser, Result, SourceType, StructureData, UCDF,
+    parse, AccessMode, ConnectionParams, Endpoint, Error, Metadata, Parser, Result, SourceType,
+    Structure, StructureData, UCDF,
 };
 
 fn main() -> Result<()> {
@@ -16,7 +13,7 @@ fn main() -> Result<()> {
     let parser = Parser::new();
 
     let ucdf = parser.parse(ucdf_str)?;
-    println!("Parsed UCDF: {}\n", ucdf.to_string());
+    println!("Parsed UCDF: {}\n", ucdf);
 
     // Example 2: Working with nested data
     println!("Example 2: Working with nested data");
@@ -58,7 +55,7 @@ fn main() -> Result<()> {
     }
 
     // Extract and handle fields
-    if let Some(StructureData::Fields(fields)) = nested_ucdf.structure.get("fields") {
+    if let Some(fields) = nested_ucdf.structure.fields() {
         for field in fields {
             println!("  Field: {} ({})", field.name, field.dtype);
 
@@ -81,14 +78,14 @@ fn main() -> Result<()> {
 
     // Create structure with endpoints
     let endpoints = vec![
-        Endpoint::new("/users".to_string(), "GET".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "GET".to_string()),
-        Endpoint::new("/users".to_string(), "POST".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "PUT".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "DELETE".to_string()),
+        Endpoint::new("/users", "GET"),
+        Endpoint::new("/users/{id}", "GET"),
+        Endpoint::new("/users", "POST"),
+        Endpoint::new("/users/{id}", "PUT"),
+        Endpoint::new("/users/{id}", "DELETE"),
     ];
 
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("endpoints".to_string(), StructureData::Endpoints(endpoints));
 
     // Add format information
@@ -100,7 +97,7 @@ fn main() -> Result<()> {
     // Add custom structure information
     structure.insert(
         "version".to_string(),
-        StructureData::Custom("version".to_string(), "2.0".to_string()),
+        StructureData::Custom("2.0".to_string()),
     );
 
     // Create metadata
@@ -112,14 +109,14 @@ fn main() -> Result<()> {
 
     // Create the complete UCDF
     let api_ucdf = UCDF::builder()
-        .source_type(SourceType::new("api".to_string(), Some("rest".to_string())))
+        .source_type(SourceType::new("api", Some("rest".into())))
         .connection(connection)
         .structure(structure)
-        .access_mode(AccessMode::ReadWrite)
+        .access_mode(AccessMode::READ_WRITE)
         .metadata(metadata)
         .build();
 
-    println!("API UCDF: {}\n", api_ucdf.to_string());
+    println!("API UCDF: {}\n", api_ucdf);
 
     // Example 4: Error handling and validation
     println!("Example 4: Error handling and validation");
@@ -142,7 +139,7 @@ fn main() -> Result<()> {
     fn parse_with_detailed_error(input: &str) -> std::result::Result<(), String> {
         match parse(input) {
             Ok(ucdf) => {
-                println!("Successfully parsed: {}", ucdf.to_string());
+                println!("Successfully parsed: {}", ucdf);
                 Ok(())
             }
             Err(Error::MissingTypeSection) => {
@@ -1,5 +1,4 @@
-use std::str::FromStr;
-use ucdf::{parse, AccessMode, DataValue, Endpoint, Field, SourceType, StructureData, UCDF};
+use ucdf::{parse, AccessMode, DataValue, Endpoint, Field, SourceType, UCDF};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 1: Parse a UCDF string
@@ -21,7 +20,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Access the fields
-    if let Some(StructureData::Fields(fields)) = ucdf.structure.get("fields") {
+    if let Some(fields) = ucdf.structure.fields() {
         println!("Fields:");
         for field in fields {
             println!("  - {} ({})", field.name, field.dtype);
@@ -32,7 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example 2: Create a UCDF structure programmatically
     println!("Example 2: Creating a UCDF structure programmatically");
-    let source_type = SourceType::new("api".to_string(), Some("rest".to_string()));
+    let source_type = SourceType::new("api", Some("rest".into()));
     let mut ucdf = UCDF::with_source_type(source_type);
 
     // Add connection parameters
@@ -42,13 +41,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Add endpoints
     let endpoints = vec![
-        Endpoint::new("/users".to_string(), "GET".to_string()),
-        Endpoint::new("/orders".to_string(), "POST".to_string()),
+        Endpoint::new("/users", "GET"),
+        Endpoint::new("/orders", "POST"),
     ];
     ucdf.add_endpoints(endpoints);
 
     // Set access mode
-    ucdf.set_access_mode(AccessMode::ReadWrite);
+    ucdf.set_access_mode(AccessMode::READ_WRITE);
 
     // Add metadata
     ucdf.add_metadata("owner", "admin")
@@ -62,31 +61,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example 3: Using the fluent API to create a Kafka UCDF
     println!("Example 3: Using the fluent API to create a Kafka UCDF");
-    let kafka_ucdf = UCDF::with_source_type(SourceType::new(
-        "stream".to_string(),
-        Some("kafka".to_string()),
-    ))
-    .with_connection("brokers", "broker1:9092,broker2:9092")
-    .with_connection("topic", "events")
-    .with_format("json")
-    .with_fields(vec![
-        Field::new("event_id".to_string(), "str".to_string(), None),
-        Field::new("payload".to_string(), "json".to_string(), None),
-    ])
-    .with_access_mode(AccessMode::Read)
-    .with_metadata("desc", "Kafka event stream");
-
-    println!("Kafka UCDF: {}", kafka_ucdf.to_string());
+    let kafka_ucdf = UCDF::with_source_type(SourceType::new("stream", Some("kafka".into())))
+        .with_connection("brokers", "broker1:9092,broker2:9092")
+        .with_connection("topic", "events")
+        .with_format("json")
+        .with_fields(vec![
+            Field::new("event_id", "str", None),
+            Field::new("payload", "json", None),
+        ])
+        .with_access_mode(AccessMode::READ)
+        .with_metadata("desc", "Kafka event stream");
+
+    println!("Kafka UCDF: {}", kafka_ucdf);
 
     println!("\n---\n");
 
     // Example 4: Working with values and data types
     println!("Example 4: Working with values and data types");
-    let field_with_value = Field::new(
-        "temperature".to_string(),
-        "float".to_string(),
-        Some(DataValue::Float(25.5)),
-    );
+    let field_with_value = Field::new("temperature", "float", Some(DataValue::Float(25.5)));
 
     println!(
         "Field: {} = {}",
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
 use ucdf::{
-    AccessMode, ConnectionParams, Endpoint, Field, Metadata, SourceType, StructureData, UCDF,
+    AccessMode, ConnectionParams, Endpoint, Field, Metadata, SourceType, Structure, StructureData,
+    UCDF,
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -9,27 +9,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example 1: Basic CSV file
     println!("Example 1: CSV File");
     let csv_ucdf = build_csv_example();
-    println!("CSV UCDF: {}\n", csv_ucdf.to_string());
+    println!("CSV UCDF: {}\n", csv_ucdf);
 
     // Example 2: Database connection
     println!("Example 2: Database Connection");
     let db_ucdf = build_database_example();
-    println!("Database UCDF: {}\n", db_ucdf.to_string());
+    println!("Database UCDF: {}\n", db_ucdf);
 
     // Example 3: REST API
     println!("Example 3: REST API");
     let api_ucdf = build_api_example();
-    println!("API UCDF: {}\n", api_ucdf.to_string());
+    println!("API UCDF: {}\n", api_ucdf);
 
     // Example 4: Kafka Stream
     println!("Example 4: Kafka Stream");
     let stream_ucdf = build_stream_example();
-    println!("Stream UCDF: {}\n", stream_ucdf.to_string());
+    println!("Stream UCDF: {}\n", stream_ucdf);
 
     // Example 5: Custom data source
     println!("Example 5: Custom Data Source");
     let custom_ucdf = build_custom_example();
-    println!("Custom UCDF: {}\n", custom_ucdf.to_string());
+    println!("Custom UCDF: {}\n", custom_ucdf);
 
     Ok(())
 }
@@ -37,7 +37,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 // Build a CSV file UCDF example
 fn build_csv_example() -> UCDF {
     // Create source type
-    let source_type = SourceType::new("file".to_string(), Some("csv".to_string()));
+    let source_type = SourceType::new("file", Some("csv".into()));
 
     // Create connection parameters
     let mut connection = ConnectionParams::new();
@@ -46,17 +46,17 @@ fn build_csv_example() -> UCDF {
 
     // Create fields
     let fields = vec![
-        Field::new("id".to_string(), "int".to_string(), None),
-        Field::new("date".to_string(), "date".to_string(), None),
-        Field::new("customer_id".to_string(), "int".to_string(), None),
-        Field::new("product_id".to_string(), "int".to_string(), None),
-        Field::new("quantity".to_string(), "int".to_string(), None),
-        Field::new("price".to_string(), "float".to_string(), None),
-        Field::new("total".to_string(), "float".to_string(), None),
+        Field::new("id", "int", None),
+        Field::new("date", "date", None),
+        Field::new("customer_id", "int", None),
+        Field::new("product_id", "int", None),
+        Field::new("quantity", "int", None),
+        Field::new("price", "float", None),
+        Field::new("total", "float", None),
     ];
 
     // Create structure
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("fields".to_string(), StructureData::Fields(fields));
 
     // Create metadata
@@ -70,7 +70,7 @@ fn build_csv_example() -> UCDF {
         .source_type(source_type)
         .connection(connection)
         .metadata(metadata)
-        .access_mode(AccessMode::Read)
+        .access_mode(AccessMode::READ)
         .build()
     // Create UCDF
 }
@@ -78,7 +78,7 @@ fn build_csv_example() -> UCDF {
 // Build a database UCDF example
 fn build_database_example() -> UCDF {
     // Create source type
-    let source_type = SourceType::new("db".to_string(), Some("postgresql".to_string()));
+    let source_type = SourceType::new("db", Some("postgresql".into()));
 
     // Create connection parameters
     let mut connection = ConnectionParams::new();
@@ -91,19 +91,19 @@ fn build_database_example() -> UCDF {
 
     // Create fields
     let fields = vec![
-        Field::new("id".to_string(), "int".to_string(), None),
-        Field::new("name".to_string(), "str".to_string(), None),
-        Field::new("email".to_string(), "str".to_string(), None),
-        Field::new("created_at".to_string(), "datetime".to_string(), None),
-        Field::new("status".to_string(), "str".to_string(), None),
+        Field::new("id", "int", None),
+        Field::new("name", "str", None),
+        Field::new("email", "str", None),
+        Field::new("created_at", "datetime", None),
+        Field::new("status", "str", None),
     ];
 
     // Create structure
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("fields".to_string(), StructureData::Fields(fields));
     structure.insert(
         "table".to_string(),
-        StructureData::Custom("table".to_string(), "customers".to_string()),
+        StructureData::Custom("customers".to_string()),
     );
 
     // Create metadata
@@ -117,14 +117,14 @@ fn build_database_example() -> UCDF {
         .source_type(source_type)
         .connection(connection)
         .metadata(metadata)
-        .access_mode(AccessMode::ReadWrite)
+        .access_mode(AccessMode::READ_WRITE)
         .build()
 }
 
 // Build a REST API UCDF example
 fn build_api_example() -> UCDF {
     // Create source type
-    let source_type = SourceType::new("api".to_string(), Some("rest".to_string()));
+    let source_type = SourceType::new("api", Some("rest".into()));
 
     // Create connection parameters
     let mut connection = ConnectionParams::new();
@@ -135,15 +135,15 @@ fn build_api_example() -> UCDF {
 
     // Create endpoints
     let endpoints = vec![
-        Endpoint::new("/users".to_string(), "GET".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "GET".to_string()),
-        Endpoint::new("/users".to_string(), "POST".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "PUT".to_string()),
-        Endpoint::new("/users/{id}".to_string(), "DELETE".to_string()),
+        Endpoint::new("/users", "GET"),
+        Endpoint::new("/users/{id}", "GET"),
+        Endpoint::new("/users", "POST"),
+        Endpoint::new("/users/{id}", "PUT"),
+        Endpoint::new("/users/{id}", "DELETE"),
     ];
 
     // Create structure
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("endpoints".to_string(), StructureData::Endpoints(endpoints));
     structure.insert(
         "format".to_string(),
@@ -151,7 +151,7 @@ fn build_api_example() -> UCDF {
     );
     structure.insert(
         "version".to_string(),
-        StructureData::Custom("version".to_string(), "2.0".to_string()),
+        StructureData::Custom("2.0".to_string()),
     );
 
     // Create metadata
@@ -165,14 +165,14 @@ fn build_api_example() -> UCDF {
         .source_type(source_type)
         .connection(connection)
         .metadata(metadata)
-        .access_mode(AccessMode::ReadWrite)
+        .access_mode(AccessMode::READ_WRITE)
         .build()
 }
 
 // Build a Kafka stream UCDF example
 fn build_stream_example() -> UCDF {
     // Create source type
-    let source_type = SourceType::new("stream".to_string(), Some("kafka".to_string()));
+    let source_type = SourceType::new("stream", Some("kafka".into()));
 
     // Create connection parameters
     let mut connection = ConnectionParams::new();
@@ -183,15 +183,15 @@ fn build_stream_example() -> UCDF {
 
     // Create fields
     let fields = vec![
-        Field::new("event_id".to_string(), "str".to_string(), None),
-        Field::new("user_id".to_string(), "int".to_string(), None),
-        Field::new("event_type".to_string(), "str".to_string(), None),
-        Field::new("timestamp".to_string(), "datetime".to_string(), None),
-        Field::new("data".to_string(), "json".to_string(), None),
+        Field::new("event_id", "str", None),
+        Field::new("user_id", "int", None),
+        Field::new("event_type", "str", None),
+        Field::new("timestamp", "datetime", None),
+        Field::new("data", "json", None),
     ];
 
     // Create structure
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("fields".to_string(), StructureData::Fields(fields));
     structure.insert(
         "format".to_string(),
@@ -210,14 +210,14 @@ fn build_stream_example() -> UCDF {
         .source_type(source_type)
         .connection(connection)
         .metadata(metadata)
-        .access_mode(AccessMode::ReadWrite)
+        .access_mode(AccessMode::READ_WRITE)
         .build()
 }
 
 // Build a custom data source UCDF example
 fn build_custom_example() -> UCDF {
     // Create source type
-    let source_type = SourceType::new("custom".to_string(), Some("iot".to_string()));
+    let source_type = SourceType::new("custom", Some("iot".into()));
 
     // Create connection parameters
     let mut connection = ConnectionParams::new();
@@ -230,17 +230,17 @@ fn build_custom_example() -> UCDF {
 
     // Create fields
     let fields = vec![
-        Field::new("device_id".to_string(), "str".to_string(), None),
-        Field::new("sensor_type".to_string(), "str".to_string(), None),
-        Field::new("value".to_string(), "float".to_string(), None),
-        Field::new("unit".to_string(), "str".to_string(), None),
-        Field::new("timestamp".to_string(), "datetime".to_string(), None),
-        Field::new("battery".to_string(), "float".to_string(), None),
-        Field::new("rssi".to_string(), "int".to_string(), None),
+        Field::new("device_id", "str", None),
+        Field::new("sensor_type", "str", None),
+        Field::new("value", "float", None),
+        Field::new("unit", "str", None),
+        Field::new("timestamp", "datetime", None),
+        Field::new("battery", "float", None),
+        Field::new("rssi", "int", None),
     ];
 
     // Create structure
-    let mut structure = HashMap::new();
+    let mut structure = Structure::new();
     structure.insert("fields".to_string(), StructureData::Fields(fields));
     structure.insert(
         "format".to_string(),
@@ -248,7 +248,7 @@ fn build_custom_example() -> UCDF {
     );
     structure.insert(
         "frequency".to_string(),
-        StructureData::Custom("frequency".to_string(), "60s".to_string()),
+        StructureData::Custom("60s".to_string()),
     );
 
     // Create metadata
@@ -263,6 +263,6 @@ fn build_custom_example() -> UCDF {
         .source_type(source_type)
         .connection(connection)
         .metadata(metadata)
-        .access_mode(AccessMode::ReadWrite)
+        .access_mode(AccessMode::READ_WRITE)
         .build()
 }
@@ -1,6 +1,4 @@
-use ucdf::{
-    parse, to_string, AccessMode, ConnectionParams, Endpoint, Field, Metadata, SourceType, UCDF,
-};
+use ucdf::{parse, AccessMode, Endpoint, Field, SourceType, UCDF};
 
 fn main() {
     // Пример 1: Парсинг UCDF-строки
@@ -19,13 +17,13 @@ fn main() {
         println!("{:?}", fields);
     }
 
-    println!("\nUCDF-строка: {}", ucdf.to_string());
+    println!("\nUCDF-строка: {}", ucdf);
 
     // Пример 2: Создание UCDF-структуры с помощью Builder
     println!("\n=== Пример 2: Создание с помощью Builder ===");
     let source_type = SourceType::builder()
-        .category("db".to_string())
-        .subtype("postgresql".to_string())
+        .category("db")
+        .subtype("postgresql")
         .build();
 
     let ucdf = UCDF::builder()
@@ -37,30 +35,24 @@ fn main() {
         .with_connection("password", "secret")
         .with_connection("db", "mydb")
         .with_fields(vec![
+            Field::builder().name("id").dtype("int").build(),
+            Field::builder().name("name").dtype("str").build(),
             Field::builder()
-                .name("id".to_string())
-                .dtype("int".to_string())
-                .build(),
-            Field::builder()
-                .name("name".to_string())
-                .dtype("str".to_string())
-                .build(),
-            Field::builder()
-                .name("created_at".to_string())
-                .dtype("datetime".to_string())
+                .name("created_at")
+                .dtype("datetime")
                 .build(),
         ])
-        .with_access_mode(AccessMode::ReadWrite)
+        .with_access_mode(AccessMode::READ_WRITE)
         .with_metadata("desc", "База данных пользователей")
         .with_metadata("owner", "admin");
 
-    println!("UCDF-строка: {}", ucdf.to_string());
+    println!("UCDF-строка: {}", ucdf);
 
     // Пример 3: Создание UCDF для REST API
     println!("\n=== Пример 3: Создание UCDF для REST API ===");
     let source_type = SourceType::builder()
-        .category("api".to_string())
-        .subtype("rest".to_string())
+        .category("api")
+        .subtype("rest")
         .build();
 
     let ucdf = UCDF::builder()
@@ -70,34 +62,28 @@ fn main() {
         .with_connection("auth.type", "bearer")
         .with_connection("auth.token", "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")
         .with_endpoints(vec![
+            Endpoint::builder().path("/users").method("GET").build(),
             Endpoint::builder()
-                .path("/users".to_string())
-                .method("GET".to_string())
+                .path("/users/{id}")
+                .method("GET")
                 .build(),
+            Endpoint::builder().path("/users").method("POST").build(),
             Endpoint::builder()
-                .path("/users/{id}".to_string())
-                .method("GET".to_string())
-                .build(),
-            Endpoint::builder()
-                .path("/users".to_string())
-                .method("POST".to_string())
-                .build(),
-            Endpoint::builder()
-                .path("/users/{id}".to_string())
-                .method("PUT".to_string())
+                .path("/users/{id}")
+                .method("PUT")
                 .build(),
         ])
-        .with_access_mode(AccessMode::ReadWrite)
+        .with_access_mode(AccessMode::READ_WRITE)
         .with_metadata("desc", "REST API для управления пользователями")
         .with_metadata("version", "1.0");
 
-    println!("UCDF-строка: {}", ucdf.to_string());
+    println!("UCDF-строка: {}", ucdf);
 
     // Пример 4: Создание UCDF для Kafka
     println!("\n=== Пример 4: Создание UCDF для Kafka ===");
     let source_type = SourceType::builder()
-        .category("stream".to_string())
-        .subtype("kafka".to_string())
+        .category("stream")
+        .subtype("kafka")
         .build();
 
     let ucdf = UCDF::builder()
@@ -108,32 +94,17 @@ fn main() {
         .with_connection("group_id", "user-service")
         .with_format("json")
         .with_fields(vec![
-            Field::builder()
-                .name("event_id".to_string())
-                .dtype("str".to_string())
-                .build(),
-            Field::builder()
-                .name("event_type".to_string())
-                .dtype("str".to_string())
-                .build(),
-            Field::builder()
-                .name("user_id".to_string())
-                .dtype("int".to_string())
-                .build(),
-            Field::builder()
-                .name("data".to_string())
-                .dtype("json".to_string())
-                .build(),
-            Field::builder()
-                .name("timestamp".to_string())
-                .dtype("datetime".to_string())
-                .build(),
+            Field::builder().name("event_id").dtype("str").build(),
+            Field::builder().name("event_type").dtype("str").build(),
+            Field::builder().name("user_id").dtype("int").build(),
+            Field::builder().name("data").dtype("json").build(),
+            Field::builder().name("timestamp").dtype("datetime").build(),
         ])
-        .with_access_mode(AccessMode::Read)
+        .with_access_mode(AccessMode::READ)
         .with_metadata("desc", "Поток событий пользовательской активности")
         .with_metadata("retention", "7d");
 
-    println!("UCDF-строка: {}", ucdf.to_string());
+    println!("UCDF-строка: {}", ucdf);
 
     // Пример 5: Модификация существующего UCDF
     println!("\n=== Пример 5: Модификация существующего UCDF ===");
@@ -142,17 +113,11 @@ fn main() {
     // Добавляем новые параметры
     ucdf.add_connection("encoding", "utf-8");
     ucdf.add_fields(vec![
-        Field::builder()
-            .name("id".to_string())
-            .dtype("int".to_string())
-            .build(),
-        Field::builder()
-            .name("name".to_string())
-            .dtype("str".to_string())
-            .build(),
+        Field::builder().name("id").dtype("int").build(),
+        Field::builder().name("name").dtype("str").build(),
     ]);
-    ucdf.set_access_mode(AccessMode::ReadWrite);
+    ucdf.set_access_mode(AccessMode::READ_WRITE);
     ucdf.add_metadata("owner", "data-team");
 
-    println!("Модифицированный UCDF: {}", ucdf.to_string());
+    println!("Модифицированный UCDF: {}", ucdf);
 }
@@ -0,0 +1,216 @@
+//! Arena-backed parsing for batch jobs
+//!
+//! [`parse_in`] copies a UCDF string into a [`Bump`] arena once and parses it
+//! into a [`UcdfIn`] whose strings and lists all live in that arena. A batch
+//! job can parse thousands of descriptors into one arena and free them all
+//! at once by dropping or resetting it, without running a destructor or
+//! calling the allocator per string.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::bumpalo::Bump;
+//! use ucdf::parse_in;
+//!
+//! let bump = Bump::new();
+//! let sources: Vec<_> = ["t=db.postgresql;c.host=db1", "t=file.csv;c.path=/data/users.csv"]
+//!     .iter()
+//!     .map(|s| parse_in(&bump, s).unwrap())
+//!     .collect();
+//! assert_eq!(sources[0].connection("host"), Some("db1"));
+//! assert_eq!(sources[1].category, "file");
+//! ```
+
+use std::borrow::Cow;
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+
+use crate::error::{Error, Result};
+use crate::parser::{next_section, structure_value, trailing_input, trim_input};
+use crate::sections::{AccessMode, KeyPath, SourceType, UCDF};
+
+/// Descriptor parsed into an arena by [`parse_in`]
+///
+/// Entries keep the order they were written in; a repeated key replaces
+/// the earlier value in place, as in [`UCDF`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UcdfIn<'bump> {
+    pub category: &'bump str,
+    pub subtype: Option<&'bump str>,
+    pub connection: Vec<'bump, (&'bump str, &'bump str)>,
+    /// Structure sections as written, checked by [`UcdfIn::to_ucdf`]
+    pub structure: Vec<'bump, (&'bump str, &'bump str)>,
+    pub access_mode: Option<AccessMode>,
+    pub section_access: Vec<'bump, (&'bump str, AccessMode)>,
+    pub metadata: Vec<'bump, (&'bump str, &'bump str)>,
+    pub encrypted_connection: Option<&'bump str>,
+    pub includes: Vec<'bump, &'bump str>,
+}
+
+/// Parse a UCDF string into `bump`
+///
+/// Accepts the same sections as [`parse`](crate::parse). Structure values
+/// are kept as written rather than parsed into fields and endpoints.
+pub fn parse_in<'bump>(bump: &'bump Bump, s: &str) -> Result<UcdfIn<'bump>> {
+    let (start, s) = trim_input(s);
+    let s: &'bump str = bump.alloc_str(s);
+    let mut source_type: Option<(&str, Option<&str>)> = None;
+    let mut ucdf = UcdfIn {
+        category: "",
+        subtype: None,
+        connection: Vec::new_in(bump),
+        structure: Vec::new_in(bump),
+        access_mode: None,
+        section_access: Vec::new_in(bump),
+        metadata: Vec::new_in(bump),
+        encrypted_connection: None,
+        includes: Vec::new_in(bump),
+    };
+
+    let mut input = s;
+    while !input.is_empty() {
+        // Skip empty sections
+        if let Some(after) = input.strip_prefix(';') {
+            input = after;
+            continue;
+        }
+        let Some((key, value, rest)) = next_section(input) else {
+            break;
+        };
+        // Quoted values with escapes are decoded into the arena
+        let value: &'bump str = match value {
+            Cow::Borrowed(value) => value,
+            Cow::Owned(value) => bump.alloc_str(&value),
+        };
+        match KeyPath::parse(key) {
+            Some(KeyPath::Type) => {
+                let mut parts = value.split('.');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(category), subtype, None) => {
+                        if let Some((first, first_subtype)) = source_type {
+                            let first = SourceType::new(first, first_subtype.map(Into::into));
+                            return Err(Error::DuplicateTypeSection(format!(
+                                "{} after {}",
+                                value, first
+                            )));
+                        }
+                        source_type = Some((category, subtype));
+                    }
+                    _ => return Err(Error::InvalidSourceType(value.to_string())),
+                }
+            }
+            Some(KeyPath::Include) => ucdf.includes.push(value),
+            Some(KeyPath::EncryptedConnection) => ucdf.encrypted_connection = Some(value),
+            Some(KeyPath::Connection(key)) => set(&mut ucdf.connection, key, value),
+            Some(KeyPath::Structure(key)) => set(&mut ucdf.structure, key, value),
+            Some(KeyPath::Access) => ucdf.access_mode = Some(value.parse()?),
+            Some(KeyPath::SectionAccess(key)) => set(&mut ucdf.section_access, key, value.parse()?),
+            Some(KeyPath::Meta(key)) => set(&mut ucdf.metadata, key, value),
+            Some(KeyPath::Custom(prefix, _)) => {
+                return Err(Error::UnknownSectionPrefix(prefix.to_string()))
+            }
+            None => return Err(Error::UnknownSectionPrefix(key.to_string())),
+        }
+        input = rest;
+        if !input.starts_with(';') {
+            break;
+        }
+    }
+    if !input.is_empty() {
+        return Err(trailing_input(start + s.len() - input.len(), input));
+    }
+
+    (ucdf.category, ucdf.subtype) = source_type.ok_or(Error::MissingTypeSection)?;
+    Ok(ucdf)
+}
+
+fn set<'bump, V>(entries: &mut Vec<'bump, (&'bump str, V)>, key: &'bump str, value: V) {
+    match entries.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((key, value)),
+    }
+}
+
+impl<'bump> UcdfIn<'bump> {
+    /// Get the source type
+    pub fn source_type(&self) -> SourceType {
+        SourceType::new(self.category, self.subtype.map(Into::into))
+    }
+
+    /// Get a connection parameter
+    pub fn connection(&self, key: &str) -> Option<&'bump str> {
+        get(&self.connection, key)
+    }
+
+    /// Get a metadata value
+    pub fn metadata(&self, key: &str) -> Option<&'bump str> {
+        get(&self.metadata, key)
+    }
+
+    /// Copy the descriptor out of the arena, parsing its structure values
+    pub fn to_ucdf(&self) -> Result<UCDF> {
+        let mut ucdf = UCDF::with_source_type(self.source_type());
+        for (key, value) in &self.connection {
+            ucdf.add_connection(*key, *value);
+        }
+        for (key, value) in &self.structure {
+            ucdf.structure
+                .insert(key.to_string(), structure_value(key, value)?);
+        }
+        ucdf.access_mode = self.access_mode;
+        for (key, mode) in &self.section_access {
+            ucdf.set_section_access(*key, *mode);
+        }
+        for (key, value) in &self.metadata {
+            ucdf.add_metadata(*key, *value);
+        }
+        ucdf.encrypted_connection = self.encrypted_connection.map(str::to_string);
+        ucdf.includes = self.includes.iter().map(|s| s.to_string()).collect();
+        Ok(ucdf)
+    }
+}
+
+fn get<'bump>(entries: &[(&'bump str, &'bump str)], key: &str) -> Option<&'bump str> {
+    entries
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_parse_in() {
+        let bump = Bump::new();
+        let s = "t=db.mysql;c.host=db1;c.dsn=\"a;b\";s.fields=id:int,name:str;a=rw;m.owner=data;c.host=db2";
+        let ucdf = parse_in(&bump, s).unwrap();
+        assert_eq!(ucdf.connection("host"), Some("db2"));
+        assert_eq!(ucdf.connection("dsn"), Some("a;b"));
+        assert_eq!(ucdf.structure[0], ("fields", "id:int,name:str"));
+        assert_eq!(ucdf.to_ucdf().unwrap(), parse(s).unwrap());
+
+        assert!(matches!(
+            parse_in(&bump, "c.host=db1"),
+            Err(Error::MissingTypeSection)
+        ));
+        assert!(parse_in(&bump, "t=db.mysql;a=q").is_err());
+        assert!(matches!(
+            parse_in(&bump, "t=db.mysql;q.x=1"),
+            Err(Error::UnknownSectionPrefix(_))
+        ));
+        assert!(matches!(
+            parse_in(&bump, "t=db.mysql;;c.x=\"a\"c.y=b"),
+            Err(Error::TrailingInput { offset: 19, .. })
+        ));
+        assert!(matches!(
+            parse_in(&bump, "t=db.mysql;t=db.mysql"),
+            Err(Error::DuplicateTypeSection(_))
+        ));
+        let ucdf = parse_in(&bump, "t=db.mysql;s.fields=:int").unwrap();
+        assert!(ucdf.to_ucdf().is_err());
+    }
+}
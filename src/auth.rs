@@ -0,0 +1,324 @@
+//! Authentication settings
+//!
+//! Authentication is kept in the `c.auth.*` connection keys, with
+//! `c.auth.type` naming the scheme. [`UCDF::auth`] reads them as an [`Auth`],
+//! [`UCDF::set_auth`] writes one back, and [`Auth::headers`] gives the HTTP
+//! headers a client sends.
+//!
+//! | `c.auth.type` | Keys                                                                            |
+//! |---------------|---------------------------------------------------------------------------------|
+//! | `none`        |                                                                                 |
+//! | `basic`       | `c.auth.user`, `c.auth.password`                                                |
+//! | `bearer`      | `c.auth.token`                                                                  |
+//! | `api_key`     | `c.auth.header` (default `X-API-Key`), `c.auth.api_key`                         |
+//! | `oauth2`      | `c.auth.token_url`, `c.auth.client_id`, `c.auth.client_secret`, `c.auth.scopes` |
+//!
+//! Other types are kept as [`Auth::Custom`] with their keys. Descriptors
+//! without `c.auth.*` keys have no authentication. Secrets should be
+//! references, resolved before building headers.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{parse, Auth};
+//!
+//! let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\";c.auth.type=bearer;c.auth.token=xyz").unwrap();
+//! let auth = ucdf.auth().unwrap();
+//! assert_eq!(auth, Auth::Bearer { token: "xyz".to_string() });
+//! assert_eq!(auth.headers(), [("Authorization".to_string(), "Bearer xyz".to_string())]);
+//!
+//! let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\"")
+//!     .unwrap()
+//!     .with_auth(Auth::api_key("X-Token", "${API_KEY}"));
+//! assert_eq!(
+//!     ucdf.to_string(),
+//!     "t=api.rest;c.url=\"https://api.example.com\";c.auth.type=api_key;c.auth.header=X-Token;c.auth.api_key=${API_KEY}"
+//! );
+//! ```
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use indexmap::IndexMap;
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// Header an API key is sent in when `c.auth.header` is not set
+pub const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+/// Authentication of a source, kept in its `c.auth.*` keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Auth {
+    None,
+    /// HTTP basic authentication
+    Basic {
+        user: String,
+        password: Option<String>,
+    },
+    /// Bearer token sent in the `Authorization` header
+    Bearer {
+        token: String,
+    },
+    /// API key sent in header `header`
+    ApiKey {
+        header: String,
+        key: String,
+    },
+    /// OAuth 2.0 client credentials, exchanged for a token at `token_url`
+    OAuth2 {
+        token_url: String,
+        client_id: Option<String>,
+        client_secret: Option<String>,
+        scopes: Vec<String>,
+    },
+    /// Other scheme, with its `c.auth.*` keys other than `c.auth.type`
+    Custom {
+        kind: String,
+        params: IndexMap<String, String>,
+    },
+}
+
+impl Auth {
+    /// Create basic authentication
+    pub fn basic(user: impl Into<String>, password: impl Into<String>) -> Self {
+        Auth::Basic {
+            user: user.into(),
+            password: Some(password.into()),
+        }
+    }
+
+    /// Create bearer token authentication
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Auth::Bearer {
+            token: token.into(),
+        }
+    }
+
+    /// Create API key authentication sending `key` in header `header`
+    pub fn api_key(header: impl Into<String>, key: impl Into<String>) -> Self {
+        Auth::ApiKey {
+            header: header.into(),
+            key: key.into(),
+        }
+    }
+
+    /// Get the `c.auth.type` of the scheme
+    pub fn kind(&self) -> &str {
+        match self {
+            Auth::None => "none",
+            Auth::Basic { .. } => "basic",
+            Auth::Bearer { .. } => "bearer",
+            Auth::ApiKey { .. } => "api_key",
+            Auth::OAuth2 { .. } => "oauth2",
+            Auth::Custom { kind, .. } => kind,
+        }
+    }
+
+    /// Get the HTTP headers authenticating a request
+    ///
+    /// OAuth 2.0 and custom schemes need a token exchange or knowledge of
+    /// the scheme, and give no headers.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        match self {
+            Auth::Basic { user, password } => {
+                let credentials = format!("{}:{}", user, password.as_deref().unwrap_or_default());
+                vec![(
+                    "Authorization".to_string(),
+                    format!("Basic {}", STANDARD.encode(credentials)),
+                )]
+            }
+            Auth::Bearer { token } => {
+                vec![("Authorization".to_string(), format!("Bearer {}", token))]
+            }
+            Auth::ApiKey { header, key } => vec![(header.clone(), key.clone())],
+            Auth::None | Auth::OAuth2 { .. } | Auth::Custom { .. } => Vec::new(),
+        }
+    }
+
+    // `c.auth.*` keys, without the `auth.` prefix, in writing order
+    fn params(&self) -> Vec<(String, String)> {
+        let mut params = vec![("type".to_string(), self.kind().to_string())];
+        let mut param = |key: &str, value: &str| params.push((key.to_string(), value.to_string()));
+        match self {
+            Auth::None => {}
+            Auth::Basic { user, password } => {
+                param("user", user);
+                if let Some(password) = password {
+                    param("password", password);
+                }
+            }
+            Auth::Bearer { token } => param("token", token),
+            Auth::ApiKey { header, key } => {
+                param("header", header);
+                param("api_key", key);
+            }
+            Auth::OAuth2 {
+                token_url,
+                client_id,
+                client_secret,
+                scopes,
+            } => {
+                param("token_url", token_url);
+                if let Some(client_id) = client_id {
+                    param("client_id", client_id);
+                }
+                if let Some(client_secret) = client_secret {
+                    param("client_secret", client_secret);
+                }
+                if !scopes.is_empty() {
+                    param("scopes", &scopes.join(","));
+                }
+            }
+            Auth::Custom { params: custom, .. } => {
+                for (key, value) in custom {
+                    param(key, value);
+                }
+            }
+        }
+        params
+    }
+}
+
+impl UCDF {
+    /// Read the authentication from the `c.auth.*` keys
+    pub fn auth(&self) -> Result<Auth> {
+        let mut params: IndexMap<String, String> = self
+            .connection
+            .iter()
+            .filter_map(|(key, value)| {
+                Some((key.strip_prefix("auth.")?.to_string(), value.clone()))
+            })
+            .collect();
+        if params.is_empty() {
+            return Ok(Auth::None);
+        }
+        let kind = params.shift_remove("type").ok_or_else(|| {
+            Error::InvalidFormat("c.auth.type is required with c.auth.* keys".to_string())
+        })?;
+        let mut take = |key: &str| params.shift_remove(key).filter(|value| !value.is_empty());
+        let required = |value: Option<String>, key: &str| {
+            value.ok_or_else(|| {
+                Error::InvalidFormat(format!("c.auth.{} is required for {} auth", key, kind))
+            })
+        };
+        let auth = match kind.to_ascii_lowercase().as_str() {
+            "none" => Auth::None,
+            "basic" => Auth::Basic {
+                user: required(take("user"), "user")?,
+                password: take("password"),
+            },
+            "bearer" => Auth::Bearer {
+                token: required(take("token"), "token")?,
+            },
+            "api_key" | "apikey" | "api-key" => Auth::ApiKey {
+                header: take("header").unwrap_or_else(|| DEFAULT_API_KEY_HEADER.to_string()),
+                key: required(take("api_key"), "api_key")?,
+            },
+            "oauth2" => Auth::OAuth2 {
+                token_url: required(take("token_url"), "token_url")?,
+                client_id: take("client_id"),
+                client_secret: take("client_secret"),
+                scopes: take("scopes")
+                    .map(|scopes| {
+                        scopes
+                            .split([',', ' '])
+                            .filter(|scope| !scope.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            },
+            _ => {
+                return Ok(Auth::Custom {
+                    kind: kind.clone(),
+                    params,
+                })
+            }
+        };
+        Ok(auth)
+    }
+
+    /// Replace the `c.auth.*` keys with those of `auth`
+    ///
+    /// [`Auth::None`] removes them.
+    pub fn set_auth(&mut self, auth: Auth) -> &mut Self {
+        let keys: Vec<String> = self
+            .connection
+            .iter()
+            .filter(|(key, _)| key.starts_with("auth."))
+            .map(|(key, _)| key.to_string())
+            .collect();
+        for key in keys {
+            self.connection.remove(&key);
+        }
+        if auth != Auth::None {
+            for (key, value) in auth.params() {
+                self.connection.insert(format!("auth.{}", key), value);
+            }
+        }
+        self
+    }
+
+    /// Fluent API for setting the authentication
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.set_auth(auth);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_auth_round_trip() {
+        let auths = [
+            Auth::basic("Aladdin", "open sesame"),
+            Auth::bearer("${TOKEN}"),
+            Auth::api_key(DEFAULT_API_KEY_HEADER, "k"),
+            Auth::OAuth2 {
+                token_url: "https://auth.example.com/token".to_string(),
+                client_id: Some("app".to_string()),
+                client_secret: Some("${CLIENT_SECRET}".to_string()),
+                scopes: vec!["read".to_string(), "write".to_string()],
+            },
+            Auth::Custom {
+                kind: "hmac".to_string(),
+                params: IndexMap::from([("key_id".to_string(), "k1".to_string())]),
+            },
+            Auth::None,
+        ];
+        let mut ucdf = parse("t=api.rest;c.url=x;c.auth.type=bearer;c.auth.token=old").unwrap();
+        for auth in auths {
+            ucdf.set_auth(auth.clone());
+            assert_eq!(parse(&ucdf.to_string()).unwrap().auth().unwrap(), auth);
+        }
+        assert_eq!(ucdf.to_string(), "t=api.rest;c.url=x");
+
+        assert_eq!(
+            Auth::basic("Aladdin", "open sesame").headers(),
+            [(
+                "Authorization".to_string(),
+                "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==".to_string()
+            )]
+        );
+        assert_eq!(Auth::basic("a", "").headers()[0].1, "Basic YTo=");
+
+        let ucdf =
+            parse("t=api.rest;c.auth.type=apikey;c.auth.api_key=k;c.auth.scopes=\"a b\"").unwrap();
+        assert_eq!(
+            ucdf.auth().unwrap().headers(),
+            [("X-API-Key".to_string(), "k".to_string())]
+        );
+        for invalid in [
+            "c.auth.token=t",
+            "c.auth.type=bearer",
+            "c.auth.type=oauth2;c.auth.client_id=x",
+        ] {
+            let ucdf = parse(&format!("t=api.rest;{}", invalid)).unwrap();
+            assert!(ucdf.auth().is_err(), "{}", invalid);
+        }
+    }
+}
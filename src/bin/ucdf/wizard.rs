@@ -0,0 +1,255 @@
+//! Interactive descriptor builder for `ucdf new --interactive`
+
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use ucdf::lint::{is_secret_key, is_secret_reference};
+use ucdf::source_types;
+use ucdf::validate::Validator;
+use ucdf::{AccessMode, Error, Result, SourceType, UCDF};
+
+/// Connection key prompted for, with its default value
+type Prompt = (&'static str, Option<&'static str>);
+
+/// Connection keys prompted for a source type
+pub fn connection_prompts(source_type: &SourceType) -> &'static [Prompt] {
+    const PROMPTS: &[(&str, &[Prompt])] = &[
+        ("db.sqlite", &[("path", None)]),
+        (
+            "db.mongodb",
+            &[("uri", Some("mongodb://localhost:27017")), ("db", None)],
+        ),
+        (
+            "db.*",
+            &[
+                ("host", Some("localhost")),
+                ("port", None),
+                ("db", None),
+                ("user", None),
+                ("password", None),
+            ],
+        ),
+        (
+            "file.s3",
+            &[("bucket", None), ("region", None), ("path", None)],
+        ),
+        ("file.gcs", &[("bucket", None), ("path", None)]),
+        (
+            "file.azblob",
+            &[("container", None), ("account_name", None), ("path", None)],
+        ),
+        ("file.fs", &[("root", None)]),
+        ("file.*", &[("path", None), ("encoding", Some("utf-8"))]),
+        ("api.*", &[("url", None), ("auth.type", None)]),
+        (
+            "stream.kafka",
+            &[
+                ("brokers", Some("localhost:9092")),
+                ("topic", None),
+                ("group_id", None),
+            ],
+        ),
+        ("ref", &[("target", None)]),
+    ];
+    PROMPTS
+        .iter()
+        .find(|(pattern, _)| source_type.matches(pattern))
+        .map(|(_, prompts)| *prompts)
+        .unwrap_or_default()
+}
+
+/// Asks for each part of a descriptor, validating answers as they are given
+pub struct Wizard<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Wizard<R, W> {
+    /// Create a wizard reading answers from `input` and writing prompts to `output`
+    pub fn new(input: R, output: W) -> Self {
+        Wizard { input, output }
+    }
+
+    /// Build a descriptor, starting from `source_type` when it is given
+    pub fn run(&mut self, source_type: Option<SourceType>) -> Result<UCDF> {
+        let source_type = match source_type {
+            Some(source_type) => source_type,
+            None => self.ask_until("Source type (e.g. db.postgresql)", None, |answer| {
+                if answer.is_empty() {
+                    return Err(Error::MissingTypeSection);
+                }
+                SourceType::from_str(answer)
+            })?,
+        };
+        if !source_types::is_known(&source_type) {
+            self.note(&format!("`{}` is not a known source type", source_type))?;
+        }
+        let mut ucdf = UCDF::with_source_type(source_type);
+
+        for (key, default) in connection_prompts(&ucdf.source_type) {
+            let value = self.ask_until(&format!("c.{}", key), *default, |answer| {
+                if *key == "port" && !answer.is_empty() {
+                    answer
+                        .parse::<u16>()
+                        .ok()
+                        .filter(|port| *port > 0)
+                        .ok_or_else(|| {
+                            Error::InvalidFormat(format!(
+                                "`{}` is not a port in range 1-65535",
+                                answer
+                            ))
+                        })?;
+                }
+                Ok(answer.to_string())
+            })?;
+            self.connection(&mut ucdf, key, &value)?;
+        }
+        while let Some((key, value)) =
+            self.ask_assignment("Additional connection key=value (blank to finish)")?
+        {
+            self.connection(&mut ucdf, &key, &value)?;
+        }
+
+        let fields = self.ask_until("Fields (name:type,...; blank to skip)", None, |answer| {
+            if answer.is_empty() {
+                Ok(Vec::new())
+            } else {
+                UCDF::parse_fields(answer)
+            }
+        })?;
+        if !fields.is_empty() {
+            ucdf.add_fields(fields);
+        }
+
+        let mode = self.ask_until("Access mode (r, w, rw, a, x, admin)", Some("r"), |answer| {
+            AccessMode::from_str(answer)
+        })?;
+        ucdf.set_access_mode(mode);
+
+        while let Some((key, value)) =
+            self.ask_assignment("Metadata key=value (blank to finish)")?
+        {
+            ucdf.add_metadata(key, value);
+        }
+
+        for issue in Validator::with_defaults().validate(&ucdf).issues {
+            self.note(&issue.to_string())?;
+        }
+        Ok(ucdf)
+    }
+
+    fn connection(&mut self, ucdf: &mut UCDF, key: &str, value: &str) -> Result<()> {
+        if value.is_empty() {
+            return Ok(());
+        }
+        if is_secret_key(key) && !is_secret_reference(value) {
+            self.note("secrets are better stored as a reference, e.g. ${PASSWORD}")?;
+        }
+        ucdf.add_connection(key, value);
+        Ok(())
+    }
+
+    /// Ask for a `key=value` pair, returning `None` on a blank answer
+    fn ask_assignment(&mut self, question: &str) -> Result<Option<(String, String)>> {
+        self.ask_until(question, None, |answer| {
+            if answer.is_empty() {
+                return Ok(None);
+            }
+            answer
+                .split_once('=')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .map(|(key, value)| Some((key.trim().to_string(), value.trim().to_string())))
+                .ok_or_else(|| Error::InvalidFormat("expected key=value".to_string()))
+        })
+    }
+
+    /// Ask until `parse` accepts the answer, reporting rejected answers
+    fn ask_until<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> Result<T>,
+    ) -> Result<T> {
+        loop {
+            let answer = self.ask(question, default)?;
+            match parse(&answer) {
+                Ok(value) => return Ok(value),
+                Err(e) => self.note(&e.to_string())?,
+            }
+        }
+    }
+
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String> {
+        match default {
+            Some(default) => write!(self.output, "{} [{}]: ", question, default)?,
+            None => write!(self.output, "{}: ", question)?,
+        }
+        self.output.flush()?;
+
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer)? == 0 {
+            return Err(Error::InvalidFormat("unexpected end of input".to_string()));
+        }
+        let answer = answer.trim();
+        Ok(match default {
+            Some(default) if answer.is_empty() => default.to_string(),
+            _ => answer.to_string(),
+        })
+    }
+
+    fn note(&mut self, message: &str) -> Result<()> {
+        writeln!(self.output, "  {}", message)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wizard() {
+        let answers = "\n\
+                       db.postgresql\n\
+                       \n\
+                       99999\n\
+                       5432\n\
+                       sales\n\
+                       app\n\
+                       ${PG_PASSWORD}\n\
+                       sslmode=require\n\
+                       \n\
+                       id:int,name\n\
+                       id:int,name:str\n\
+                       rw\n\
+                       owner=data\n\
+                       \n";
+        let mut output = Vec::new();
+        let ucdf = Wizard::new(answers.as_bytes(), &mut output)
+            .run(None)
+            .unwrap();
+
+        assert_eq!(ucdf.get_path("c.host").as_deref(), Some("localhost"));
+        assert_eq!(ucdf.get_path("c.port").as_deref(), Some("5432"));
+        assert_eq!(ucdf.get_path("c.sslmode").as_deref(), Some("require"));
+        assert_eq!(
+            ucdf.get_path("s.fields").as_deref(),
+            Some("id:int,name:str")
+        );
+        assert_eq!(ucdf.get_path("a").as_deref(), Some("rw"));
+        assert_eq!(ucdf.get_path("m.owner").as_deref(), Some("data"));
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Missing required type section"));
+        assert!(output.contains("`99999` is not a port"));
+        assert!(output.contains("expected `name:type`"));
+        assert!(output.contains("c.host [localhost]: "));
+    }
+
+    #[test]
+    fn test_end_of_input() {
+        let mut output = Vec::new();
+        let result = Wizard::new("file.csv\n".as_bytes(), &mut output).run(None);
+        assert!(result.is_err());
+    }
+}
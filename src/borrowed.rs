@@ -0,0 +1,234 @@
+//! Borrowed descriptors for zero-copy deserialization
+//!
+//! [`UCDFRef`] has the serde representation of [`UCDF`], but its strings
+//! borrow from the input where the format allows it. Descriptors embedded in
+//! large JSON payloads can be read without copying every key and value, and
+//! converted into a [`UCDF`] only when needed.
+//!
+//! Strings containing escape sequences can't be borrowed and are copied.
+//! Structure sections and custom sections are always owned.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "with-json")] {
+//! use std::borrow::Cow;
+//! use ucdf::{UCDFRef, UCDF};
+//!
+//! let json = r#"{"type":{"category":"db","subtype":"postgresql"},
+//!     "connection":{"host":"db1"},"structure":{},"access_mode":"r","metadata":{}}"#;
+//! let borrowed = UCDFRef::from_json(json).unwrap();
+//! assert!(matches!(borrowed.connection[0].1, Cow::Borrowed("db1")));
+//!
+//! let ucdf = UCDF::from(borrowed);
+//! assert_eq!(ucdf.to_string(), "t=db.postgresql;c.host=db1;a=r");
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "with-json")]
+use crate::error::{Error, Result};
+use crate::extensions::Extensions;
+use crate::intern::Key;
+use crate::sections::{AccessMode, ConnectionParams, Metadata, SourceType, Structure, UCDF};
+use crate::small_map::SmallMap;
+
+/// Key-value entries in document order
+pub type Entries<'a> = Vec<(Cow<'a, str>, Cow<'a, str>)>;
+
+/// [`SourceType`] borrowing its strings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceTypeRef<'a> {
+    #[serde(borrow)]
+    pub category: Cow<'a, str>,
+    #[serde(default, deserialize_with = "borrow_option")]
+    pub subtype: Option<Cow<'a, str>>,
+}
+
+/// [`UCDF`] borrowing its strings from the deserializer's input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UCDFRef<'a> {
+    #[serde(borrow, rename = "type", alias = "source_type")]
+    pub source_type: SourceTypeRef<'a>,
+    #[serde(
+        default,
+        serialize_with = "serialize_entries",
+        deserialize_with = "borrow_entries"
+    )]
+    pub connection: Entries<'a>,
+    #[serde(default)]
+    pub structure: Structure,
+    #[serde(default)]
+    pub access_mode: Option<AccessMode>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub section_access: IndexMap<String, AccessMode>,
+    #[serde(
+        default,
+        serialize_with = "serialize_entries",
+        deserialize_with = "borrow_entries"
+    )]
+    pub metadata: Entries<'a>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "borrow_option"
+    )]
+    pub encrypted_connection: Option<Cow<'a, str>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "borrow_vec"
+    )]
+    pub includes: Vec<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Extensions::is_empty")]
+    pub extensions: Extensions,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub cleared: BTreeSet<String>,
+}
+
+#[cfg(feature = "with-json")]
+impl<'a> UCDFRef<'a> {
+    /// Deserialize a descriptor from JSON, borrowing strings from `json`
+    pub fn from_json(json: &'a str) -> Result<UCDFRef<'a>> {
+        serde_json::from_str(json).map_err(|e| Error::ParseError(e.to_string()))
+    }
+}
+
+impl From<SourceTypeRef<'_>> for SourceType {
+    fn from(source_type: SourceTypeRef<'_>) -> Self {
+        SourceType::new(source_type.category, source_type.subtype.map(Into::into))
+    }
+}
+
+impl From<UCDFRef<'_>> for UCDF {
+    fn from(ucdf: UCDFRef<'_>) -> Self {
+        let mut owned = UCDF::with_source_type(ucdf.source_type.into());
+        owned.connection = ConnectionParams(owned_entries(ucdf.connection));
+        owned.structure = ucdf.structure;
+        owned.access_mode = ucdf.access_mode;
+        owned.section_access = ucdf.section_access;
+        owned.metadata = Metadata(owned_entries(ucdf.metadata));
+        owned.encrypted_connection = ucdf.encrypted_connection.map(Cow::into_owned);
+        owned.includes = ucdf.includes.into_iter().map(Cow::into_owned).collect();
+        owned.extensions = ucdf.extensions;
+        owned.cleared = ucdf.cleared;
+        owned
+    }
+}
+
+fn owned_entries(entries: Entries<'_>) -> SmallMap<String> {
+    entries
+        .into_iter()
+        .map(|(key, value)| (Key::new(&key), value.into_owned()))
+        .collect()
+}
+
+/// String borrowed from the input when it contains no escapes
+struct CowStr<'a>(Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for CowStr<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct CowStrVisitor;
+
+        impl<'de> Visitor<'de> for CowStrVisitor {
+            type Value = CowStr<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> std::result::Result<Self::Value, E> {
+                Ok(CowStr(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> std::result::Result<Self::Value, E> {
+                Ok(CowStr(Cow::Owned(v.to_string())))
+            }
+
+            fn visit_string<E>(self, v: String) -> std::result::Result<Self::Value, E> {
+                Ok(CowStr(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
+
+fn borrow_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Cow<'de, str>>, D::Error> {
+    Ok(Option::<CowStr<'de>>::deserialize(deserializer)?.map(|s| s.0))
+}
+
+fn borrow_vec<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Cow<'de, str>>, D::Error> {
+    let strings = Vec::<CowStr<'de>>::deserialize(deserializer)?;
+    Ok(strings.into_iter().map(|s| s.0).collect())
+}
+
+fn borrow_entries<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Entries<'de>, D::Error> {
+    struct EntriesVisitor;
+
+    impl<'de> Visitor<'de> for EntriesVisitor {
+        type Value = Entries<'de>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map of strings")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or_default());
+            while let Some((key, value)) = map.next_entry::<CowStr<'de>, CowStr<'de>>()? {
+                entries.push((key.0, value.0));
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(EntriesVisitor)
+}
+
+fn serialize_entries<S: Serializer>(
+    entries: &Entries<'_>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(entries.iter().map(|(key, value)| (key, value)))
+}
+
+#[cfg(all(test, feature = "with-json"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_borrowed_round_trip() {
+        let ucdf = parse(
+            "t=db.mysql;c.host=db1;c.password=\"p;w\";s.fields=id:int;a=rw;m.desc=\"a \\\"b\\\"\"",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&ucdf).unwrap();
+
+        let borrowed = UCDFRef::from_json(&json).unwrap();
+        assert!(matches!(borrowed.source_type.category, Cow::Borrowed("db")));
+        assert!(matches!(borrowed.connection[1].1, Cow::Borrowed("p;w")));
+        // Escaped strings are copied
+        assert!(matches!(borrowed.metadata[0].1, Cow::Owned(_)));
+
+        assert_eq!(serde_json::to_string(&borrowed).unwrap(), json);
+        assert_eq!(UCDF::from(borrowed), ucdf);
+        assert!(UCDFRef::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Size budgets
+//!
+//! Descriptors embedded in size-limited places, such as Kubernetes
+//! annotations or HTTP headers, must stay within a byte budget.
+//! [`UCDF::byte_len`] gives the length of the serialized form without
+//! building it, and [`UCDF::fit_within`] checks it against a limit,
+//! reporting the sections worth trimming or moving elsewhere when it is over.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=db1;s.fields=id:int,name:str,email:str;m.desc=Customer accounts").unwrap();
+//! assert_eq!(ucdf.byte_len(), ucdf.to_string().len());
+//! assert!(ucdf.fit_within(1024).is_ok());
+//!
+//! let report = ucdf.fit_within(60).unwrap_err();
+//! assert_eq!(report.excess(), 26);
+//! assert_eq!(report.to_trim(), [("s.fields".to_string(), 35)]);
+//! ```
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::parser::split_sections;
+use crate::sections::UCDF;
+
+/// Sections to trim to fit a descriptor within a byte budget, see
+/// [`UCDF::fit_within`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OversizeReport {
+    /// Byte budget
+    pub limit: usize,
+    /// Length of the serialized descriptor in bytes
+    pub byte_len: usize,
+    /// Key path and length in bytes of every section but `t`, counting its
+    /// separator, largest first
+    pub sections: Vec<(String, usize)>,
+}
+
+impl OversizeReport {
+    /// Number of bytes over the budget
+    pub fn excess(&self) -> usize {
+        self.byte_len.saturating_sub(self.limit)
+    }
+
+    /// Largest sections whose removal brings the descriptor within the
+    /// budget, or all of them if that isn't enough
+    pub fn to_trim(&self) -> &[(String, usize)] {
+        let mut freed = 0;
+        let count = self
+            .sections
+            .iter()
+            .position(|(_, len)| {
+                freed += len;
+                freed >= self.excess()
+            })
+            .map_or(self.sections.len(), |i| i + 1);
+        &self.sections[..count]
+    }
+}
+
+impl fmt::Display for OversizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "descriptor is {} bytes, {} over the limit of {}",
+            self.byte_len,
+            self.excess(),
+            self.limit
+        )?;
+        let to_trim = self.to_trim();
+        if !to_trim.is_empty() {
+            let sections: Vec<_> = to_trim
+                .iter()
+                .map(|(path, len)| format!("{} ({} bytes)", path, len))
+                .collect();
+            write!(f, "; trim or externalize {}", sections.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OversizeReport {}
+
+// Writer counting the bytes written to it
+struct ByteCounter(usize);
+
+impl fmt::Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+impl UCDF {
+    /// Length of the serialized descriptor in bytes
+    pub fn byte_len(&self) -> usize {
+        use fmt::Write as _;
+        let mut counter = ByteCounter(0);
+        write!(counter, "{}", self).expect("counting bytes cannot fail");
+        counter.0
+    }
+
+    /// Check that the serialized descriptor is at most `limit` bytes
+    pub fn fit_within(&self, limit: usize) -> Result<(), OversizeReport> {
+        let byte_len = self.byte_len();
+        if byte_len <= limit {
+            return Ok(());
+        }
+        let text = self.to_string();
+        let mut sections: Vec<_> = split_sections(&text)
+            .into_iter()
+            .map(|section| {
+                let path = section.split_once('=').map_or(section, |(key, _)| key);
+                (path.to_string(), section.len() + 1)
+            })
+            .filter(|(path, _)| path != "t")
+            .collect();
+        sections.sort_by_key(|(_, len)| Reverse(*len));
+        Err(OversizeReport {
+            limit,
+            byte_len,
+            sections,
+        })
+    }
+
+    /// Fit the descriptor within `limit` bytes, dropping metadata if needed
+    ///
+    /// Metadata entries are dropped largest first until the descriptor fits,
+    /// and their keys returned. If it still doesn't fit without any
+    /// metadata, the descriptor is left unchanged and the report describes
+    /// it as it was.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let mut ucdf = parse("t=file.csv;c.path=/a.csv;m.owner=data;m.desc=\"Nightly export of all accounts\"").unwrap();
+    /// assert_eq!(ucdf.fit_within_dropping_metadata(48).unwrap(), ["desc"]);
+    /// assert_eq!(ucdf.to_string(), "t=file.csv;c.path=/a.csv;m.owner=data");
+    /// ```
+    pub fn fit_within_dropping_metadata(
+        &mut self,
+        limit: usize,
+    ) -> Result<Vec<String>, OversizeReport> {
+        let report = match self.fit_within(limit) {
+            Ok(()) => return Ok(Vec::new()),
+            Err(report) => report,
+        };
+        let mut trimmed = self.clone();
+        let mut dropped = Vec::new();
+        let mut metadata: Vec<_> = report
+            .sections
+            .iter()
+            .filter_map(|(path, _)| path.strip_prefix("m."))
+            .collect();
+        // Sections are listed largest first
+        metadata.reverse();
+        while trimmed.byte_len() > limit {
+            let Some(key) = metadata.pop() else {
+                return Err(report);
+            };
+            trimmed.metadata.remove(key);
+            dropped.push(key.to_string());
+        }
+        *self = trimmed;
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_fit_within() {
+        let mut ucdf = parse(
+            "t=api.rest;c.url=\"https://api.example.com\";s.endpoints=/users:GET,/orders:POST;m.a=1;m.notes=\"x y z\"",
+        )
+        .unwrap();
+        assert_eq!(ucdf.byte_len(), ucdf.to_string().len());
+        let report = ucdf.fit_within(10).unwrap_err();
+        assert_eq!(report.byte_len, 98);
+        assert_eq!(
+            report.sections,
+            [
+                ("s.endpoints".to_string(), 36),
+                ("c.url".to_string(), 32),
+                ("m.notes".to_string(), 14),
+                ("m.a".to_string(), 6),
+            ]
+        );
+        assert_eq!(report.to_trim(), report.sections);
+        assert_eq!(
+            report.to_string(),
+            "descriptor is 98 bytes, 88 over the limit of 10; trim or externalize s.endpoints (36 bytes), c.url (32 bytes), m.notes (14 bytes), m.a (6 bytes)"
+        );
+
+        let original = ucdf.clone();
+        assert!(ucdf.fit_within_dropping_metadata(60).is_err());
+        assert_eq!(ucdf, original);
+        assert_eq!(
+            ucdf.fit_within_dropping_metadata(80).unwrap(),
+            ["notes", "a"]
+        );
+        assert!(ucdf.metadata.is_empty());
+        assert_eq!(
+            ucdf.fit_within_dropping_metadata(80).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::sections::UCDF;
+
+/// Descriptor caching its string forms until it is modified
+///
+/// The fields of [`UCDF`] are public, so a descriptor can't tell when it
+/// changes. `CachedUcdf` only hands out the descriptor mutably through
+/// [`edit`](CachedUcdf::edit), which drops the cached strings. Until then,
+/// [`as_str`](CachedUcdf::as_str) and [`canonical`](CachedUcdf::canonical)
+/// serialize the descriptor once and return the same string afterwards.
+/// Equality and hashing go through the canonical string.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{parse, CachedUcdf};
+///
+/// let mut cached = CachedUcdf::new(parse("t=db.mysql;c.port=3306;c.host=db1").unwrap());
+/// assert_eq!(cached.canonical(), "t=db.mysql;c.host=db1;c.port=3306");
+/// assert!(cached.is_cached());
+///
+/// cached.edit().add_connection("db", "shop");
+/// assert!(!cached.is_cached());
+/// assert_eq!(cached.as_str(), "t=db.mysql;c.port=3306;c.host=db1;c.db=shop");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedUcdf {
+    ucdf: UCDF,
+    display: OnceLock<String>,
+    canonical: OnceLock<String>,
+}
+
+impl CachedUcdf {
+    /// Wrap a descriptor, without serializing it yet
+    pub fn new(ucdf: UCDF) -> Self {
+        CachedUcdf {
+            ucdf,
+            display: OnceLock::new(),
+            canonical: OnceLock::new(),
+        }
+    }
+
+    /// Get the descriptor as `to_string()` returns it
+    pub fn as_str(&self) -> &str {
+        self.display.get_or_init(|| self.ucdf.to_string())
+    }
+
+    /// Get the descriptor as [`UCDF::to_canonical_string`] returns it
+    pub fn canonical(&self) -> &str {
+        self.canonical
+            .get_or_init(|| self.ucdf.to_canonical_string())
+    }
+
+    /// Check if either string form is cached
+    pub fn is_cached(&self) -> bool {
+        self.display.get().is_some() || self.canonical.get().is_some()
+    }
+
+    /// Get the descriptor mutably, dropping the cached strings
+    pub fn edit(&mut self) -> &mut UCDF {
+        self.display = OnceLock::new();
+        self.canonical = OnceLock::new();
+        &mut self.ucdf
+    }
+
+    /// Unwrap the descriptor
+    pub fn into_inner(self) -> UCDF {
+        self.ucdf
+    }
+}
+
+impl Deref for CachedUcdf {
+    type Target = UCDF;
+
+    fn deref(&self) -> &UCDF {
+        &self.ucdf
+    }
+}
+
+impl From<UCDF> for CachedUcdf {
+    fn from(ucdf: UCDF) -> Self {
+        CachedUcdf::new(ucdf)
+    }
+}
+
+impl fmt::Display for CachedUcdf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for CachedUcdf {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for CachedUcdf {}
+
+impl Hash for CachedUcdf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+}
+
+impl Serialize for CachedUcdf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.ucdf.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CachedUcdf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        UCDF::deserialize(deserializer).map(CachedUcdf::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use std::hash::DefaultHasher;
+
+    #[test]
+    fn test_cache_invalidation() {
+        let a = CachedUcdf::new(parse("t=db.mysql;c.host=db1;c.port=3306").unwrap());
+        let mut b = CachedUcdf::new(parse("t=db.mysql;c.port=3306;c.host=db1").unwrap());
+        assert_eq!(a, b);
+        assert!(std::ptr::eq(a.canonical(), a.canonical()));
+
+        let hash = |cached: &CachedUcdf| {
+            let mut hasher = DefaultHasher::new();
+            cached.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+
+        b.edit().set_path("c.host", "db2").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(b.to_string(), "t=db.mysql;c.port=3306;c.host=db2");
+        assert_eq!(b.clone().into_inner(), *b);
+    }
+}
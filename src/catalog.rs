@@ -0,0 +1,561 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::{parse, unquote};
+use crate::sections::UCDF;
+
+/// A collection of named UCDF descriptors
+///
+/// Entries keep their insertion order. The text form has one `name = ucdf`
+/// entry per line; blank lines are ignored. Catalogs can also be stored as
+/// `.ucdf` manifest files, see [`Catalog::load`].
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::Catalog;
+///
+/// let catalog: Catalog = "users = t=file.csv;c.path=/data/users.csv\n\
+///                         sales = t=db.postgresql;c.host=db.prod"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(catalog.len(), 2);
+/// assert!(catalog.get("sales").unwrap().source_type.is_db());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    entries: Vec<(String, UCDF)>,
+    index: HashMap<String, usize>,
+    // Manifest lines in file order, kept so comments survive a load/save round-trip
+    layout: Vec<ManifestLine>,
+    // Names of entries that came from included manifests
+    included: HashSet<String>,
+}
+
+/// A line of a `.ucdf` manifest
+#[derive(Debug, Clone, PartialEq)]
+enum ManifestLine {
+    Blank,
+    Comment(String),
+    Entry(String),
+    Include(String),
+}
+
+impl Catalog {
+    /// Create an empty catalog
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// Add an entry, returning the descriptor it replaced
+    pub fn add(&mut self, name: impl Into<String>, ucdf: UCDF) -> Option<UCDF> {
+        let name = name.into();
+        self.included.remove(&name);
+        match self.index.get(&name) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, ucdf)),
+            None => {
+                self.index.insert(name.clone(), self.entries.len());
+                self.entries.push((name, ucdf));
+                None
+            }
+        }
+    }
+
+    /// Fluent API for adding an entry
+    pub fn with(mut self, name: impl Into<String>, ucdf: UCDF) -> Self {
+        self.add(name, ucdf);
+        self
+    }
+
+    /// Get an entry by name
+    pub fn get(&self, name: &str) -> Option<&UCDF> {
+        self.index.get(name).map(|&i| &self.entries[i].1)
+    }
+
+    /// Get a mutable entry by name
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut UCDF> {
+        self.index.get(name).map(|&i| &mut self.entries[i].1)
+    }
+
+    /// Check if an entry exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Remove an entry by name
+    pub fn remove(&mut self, name: &str) -> Option<UCDF> {
+        let i = self.index.remove(name)?;
+        let (_, ucdf) = self.entries.remove(i);
+        self.reindex();
+        Some(ucdf)
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the catalog has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over entries in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &UCDF)> {
+        self.entries
+            .iter()
+            .map(|(name, ucdf)| (name.as_str(), ucdf))
+    }
+
+    /// Iterate over entry names in insertion order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Find entries matching a filter
+    ///
+    /// Pass a closure, or use [`UcdfPattern`](crate::UcdfPattern) via
+    /// `catalog.find(|ucdf| ucdf.matches(&pattern))`.
+    pub fn find<'a, F>(&'a self, filter: F) -> impl Iterator<Item = (&'a str, &'a UCDF)>
+    where
+        F: Fn(&UCDF) -> bool + 'a,
+    {
+        self.iter().filter(move |(_, ucdf)| filter(ucdf))
+    }
+
+    /// Remove entries whose canonical form duplicates an earlier entry
+    ///
+    /// Returns the names of the removed entries.
+    pub fn dedup(&mut self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut removed = Vec::new();
+        self.entries.retain(|(name, ucdf)| {
+            if seen.insert(ucdf.to_canonical_string()) {
+                true
+            } else {
+                removed.push(name.clone());
+                false
+            }
+        });
+        self.reindex();
+        removed
+    }
+
+    /// Read a catalog from a text file with one `name = ucdf` entry per line
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)?
+            .parse()
+            .with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Write the catalog to a text file with one `name = ucdf` entry per line
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Load a catalog from a `.ucdf` manifest file
+    ///
+    /// Manifests contain one `name: ucdf` entry per line, `#` comment lines,
+    /// blank lines and `@include <path>` lines. Included paths are resolved
+    /// relative to the including file and their entries are added in place;
+    /// later entries replace earlier ones with the same name.
+    ///
+    /// Comments, blank lines and includes are kept, so [`Catalog::save`]
+    /// writes them back unchanged.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut catalog = Catalog::new();
+        catalog.load_manifest(path.as_ref(), &mut Vec::new())?;
+        Ok(catalog)
+    }
+
+    /// Save the catalog as a `.ucdf` manifest file
+    ///
+    /// Entries from included manifests are not written; their `@include`
+    /// lines are kept instead.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_manifest())?;
+        Ok(())
+    }
+
+    /// Parse a manifest from a string, resolving includes relative to the current directory
+    pub fn from_manifest(s: &str) -> Result<Self> {
+        let mut catalog = Catalog::new();
+        catalog.parse_manifest(s, Path::new("."), &mut Vec::new())?;
+        Ok(catalog)
+    }
+
+    /// Convert the catalog to manifest text
+    ///
+    /// Layout from a loaded manifest is preserved; entries added since are appended.
+    pub fn to_manifest(&self) -> String {
+        let mut out = String::new();
+        let mut written = HashSet::new();
+        for line in &self.layout {
+            match line {
+                ManifestLine::Blank => out.push('\n'),
+                ManifestLine::Comment(comment) => {
+                    out.push_str(comment);
+                    out.push('\n');
+                }
+                ManifestLine::Include(path) => {
+                    out.push_str(&format!("@include {}\n", path));
+                }
+                ManifestLine::Entry(name) => {
+                    if let Some(ucdf) = self.get(name) {
+                        if written.insert(name.as_str()) {
+                            out.push_str(&format!("{}: {}\n", name, ucdf));
+                        }
+                    }
+                }
+            }
+        }
+        for (name, ucdf) in &self.entries {
+            if !written.contains(name.as_str()) && !self.included.contains(name) {
+                out.push_str(&format!("{}: {}\n", name, ucdf));
+            }
+        }
+        out
+    }
+
+    /// Parse a document of named descriptors
+    ///
+    /// Each descriptor starts with a `[name]` header line, or with a line
+    /// whose first section is `name=<name>`, and runs to the next one. Its
+    /// sections may span several lines and carry `#` comments, as in the
+    /// multi-line form. Text before the first descriptor may only hold blank
+    /// and comment lines. Later descriptors replace earlier ones with the
+    /// same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Catalog;
+    ///
+    /// let catalog = Catalog::from_document(
+    ///     "# Staging environment
+    /// [users_db]
+    /// t=db.postgresql
+    /// c.host=db1  # primary
+    ///
+    /// name=events;t=stream.kafka;c.topic=events",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(catalog.names().collect::<Vec<_>>(), ["users_db", "events"]);
+    /// assert_eq!(catalog.get("users_db").unwrap().to_string(), "t=db.postgresql;c.host=db1");
+    /// ```
+    pub fn from_document(s: &str) -> Result<Self> {
+        let mut catalog = Catalog::new();
+        // Name, line number and text of the descriptor being read
+        let mut current: Option<(String, usize, String)> = None;
+        for (number, line) in s.lines().enumerate() {
+            let trimmed = line.trim();
+            let Some((name, rest)) = document_header(trimmed) else {
+                match &mut current {
+                    Some((_, _, text)) => {
+                        text.push('\n');
+                        text.push_str(line);
+                    }
+                    None if trimmed.is_empty() || trimmed.starts_with('#') => {}
+                    None => {
+                        return Err(Error::InvalidFormat(format!(
+                            "expected `[name]` or `name=<name>`: {}",
+                            trimmed
+                        )))
+                        .with_context(|| format!("line {}", number + 1))
+                    }
+                }
+                continue;
+            };
+            if let Some(entry) = current.take() {
+                catalog.add_document_entry(entry)?;
+            }
+            if name.is_empty() {
+                return Err(Error::InvalidFormat("empty descriptor name".to_string()))
+                    .with_context(|| format!("line {}", number + 1));
+            }
+            current = Some((name, number + 1, rest.to_string()));
+        }
+        if let Some(entry) = current {
+            catalog.add_document_entry(entry)?;
+        }
+        Ok(catalog)
+    }
+
+    /// Convert the catalog to a document of named descriptors, each under a
+    /// `[name]` header in the multi-line form
+    pub fn to_document(&self) -> String {
+        let blocks: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(name, ucdf)| format!("[{}]\n{}\n", name, ucdf.to_pretty_string()))
+            .collect();
+        blocks.join("\n")
+    }
+
+    fn add_document_entry(&mut self, (name, number, text): (String, usize, String)) -> Result<()> {
+        let ucdf = parse(&text)
+            .with_context(|| format!("in entry `{}`", name))
+            .with_context(|| format!("line {}", number))?;
+        self.add(name, ucdf);
+        Ok(())
+    }
+
+    fn load_manifest(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<()> {
+        let canonical = fs::canonicalize(path)?;
+        if stack.contains(&canonical) {
+            return Err(Error::InvalidFormat(format!(
+                "include cycle at {}",
+                path.display()
+            )));
+        }
+        stack.push(canonical);
+        let text = fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        self.parse_manifest(&text, dir, stack)
+            .with_context(|| format!("in {}", path.display()))?;
+        stack.pop();
+        Ok(())
+    }
+
+    fn parse_manifest(&mut self, s: &str, dir: &Path, stack: &mut Vec<PathBuf>) -> Result<()> {
+        // Only the outermost manifest contributes layout
+        let top_level = stack.len() <= 1;
+        for (number, line) in s.lines().enumerate() {
+            let context = || format!("line {}", number + 1);
+            let trimmed = line.trim();
+            let layout_line = if trimmed.is_empty() {
+                ManifestLine::Blank
+            } else if trimmed.starts_with('#') {
+                ManifestLine::Comment(line.to_string())
+            } else if let Some(include) = trimmed.strip_prefix("@include") {
+                let include = include.trim();
+                let mut nested = Catalog::new();
+                nested
+                    .load_manifest(&dir.join(include), stack)
+                    .with_context(context)?;
+                for (name, ucdf) in nested.entries {
+                    self.add(name.clone(), ucdf);
+                    self.included.insert(name);
+                }
+                ManifestLine::Include(include.to_string())
+            } else {
+                let (name, ucdf) = parse_entry(trimmed).with_context(context)?;
+                self.add(name, ucdf);
+                ManifestLine::Entry(name.to_string())
+            };
+            if top_level {
+                self.layout.push(layout_line);
+            }
+        }
+        Ok(())
+    }
+
+    fn reindex(&mut self) {
+        self.index = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (name.clone(), i))
+            .collect();
+    }
+}
+
+// Split a line starting a descriptor of a multi-descriptor document into
+// the descriptor name and the rest of the line
+fn document_header(line: &str) -> Option<(String, &str)> {
+    if let Some((name, rest)) = line.strip_prefix('[').and_then(|h| h.split_once(']')) {
+        let rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with('#') {
+            return Some((name.trim().to_string(), ""));
+        }
+    }
+    let section = line.strip_prefix("name=")?;
+    let (name, rest) = section.split_once(';').unwrap_or((section, ""));
+    Some((unquote(name.trim()).into_owned(), rest))
+}
+
+// Parse a `name: ucdf` manifest entry
+pub(crate) fn parse_entry(line: &str) -> Result<(&str, UCDF)> {
+    let (name, ucdf_str) = line
+        .split_once(':')
+        .map(|(name, ucdf_str)| (name.trim(), ucdf_str.trim()))
+        .filter(|(name, _)| !name.is_empty())
+        .ok_or_else(|| Error::InvalidFormat(format!("expected `name: ucdf`: {}", line)))?;
+    let ucdf = parse(ucdf_str).with_context(|| format!("in entry `{}`", name))?;
+    Ok((name, ucdf))
+}
+
+impl FromStr for Catalog {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut catalog = Catalog::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let context = || format!("line {}", number + 1);
+            let (name, ucdf_str) = line
+                .split_once('=')
+                .map(|(name, ucdf_str)| (name.trim(), ucdf_str.trim()))
+                .filter(|(name, _)| !name.is_empty())
+                .ok_or_else(|| Error::InvalidFormat(format!("expected `name = ucdf`: {}", line)))
+                .with_context(context)?;
+            let ucdf = parse(ucdf_str)
+                .with_context(|| format!("in entry `{}`", name))
+                .with_context(context)?;
+            catalog.add(name, ucdf);
+        }
+        Ok(catalog)
+    }
+}
+
+impl fmt::Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, ucdf) in &self.entries {
+            writeln!(f, "{} = {}", name, ucdf)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Catalog {
+    type Item = (&'a str, &'a UCDF);
+    type IntoIter = Box<dyn Iterator<Item = (&'a str, &'a UCDF)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, UCDF)> for Catalog {
+    fn from_iter<I: IntoIterator<Item = (S, UCDF)>>(iter: I) -> Self {
+        let mut catalog = Catalog::new();
+        for (name, ucdf) in iter {
+            catalog.add(name, ucdf);
+        }
+        catalog
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_find_dedup() {
+        let mut catalog: Catalog = "\
+            users = t=file.csv;c.path=/data/users.csv;c.encoding=utf-8;a=r\n\
+            \n\
+            users_copy = t=file.csv;c.encoding=utf-8;c.path=/data/users.csv;a=r\n\
+            events = t=stream.kafka;c.brokers=b1:9092;c.topic=events;a=r\n"
+            .parse()
+            .unwrap();
+        assert_eq!(catalog.len(), 3);
+
+        let streams: Vec<&str> = catalog
+            .find(|ucdf| ucdf.source_type.is_stream())
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(streams, vec!["events"]);
+
+        assert_eq!(catalog.dedup(), vec!["users_copy".to_string()]);
+        assert_eq!(catalog.names().collect::<Vec<_>>(), vec!["users", "events"]);
+        assert!(catalog.get("events").is_some());
+
+        let reparsed: Catalog = catalog.to_string().parse().unwrap();
+        assert_eq!(reparsed, catalog);
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ucdf-manifest-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("shared.ucdf"),
+            "# Shared sources\nevents: t=stream.kafka;c.topic=events\n",
+        )
+        .unwrap();
+        let manifest = "\
+# Production inventory
+@include shared.ucdf
+
+# Databases
+sales: t=db.postgresql;c.host=db.prod;a=r
+";
+        fs::write(dir.join("prod.ucdf"), manifest).unwrap();
+
+        let mut catalog = Catalog::load(dir.join("prod.ucdf")).unwrap();
+        assert_eq!(catalog.names().collect::<Vec<_>>(), vec!["events", "sales"]);
+        assert_eq!(catalog.to_manifest(), manifest);
+
+        catalog.add(
+            "users",
+            crate::parse("t=file.csv;c.path=/data/users.csv").unwrap(),
+        );
+        catalog.save(dir.join("prod.ucdf")).unwrap();
+        let saved = fs::read_to_string(dir.join("prod.ucdf")).unwrap();
+        assert_eq!(
+            saved,
+            format!("{}users: t=file.csv;c.path=/data/users.csv\n", manifest)
+        );
+
+        fs::write(dir.join("shared.ucdf"), "@include prod.ucdf\n").unwrap();
+        let err = Catalog::load(dir.join("prod.ucdf")).unwrap_err();
+        assert!(err.root_cause().to_string().contains("include cycle"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_document() {
+        let document = "\
+            # Production\n\
+            \n\
+            [users_db]  # owned by identity\n\
+            t=db.postgresql\n\
+            c.host=db1\n\
+            \n\
+            # replaced below\n\
+            name=events;t=stream.kafka;c.topic=events\n\
+            name=\"events\"\n\
+            t=stream.kafka;c.topic=events.v2\n";
+        let catalog = Catalog::from_document(document).unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(
+            catalog.get("events").unwrap().to_string(),
+            "t=stream.kafka;c.topic=events.v2"
+        );
+        assert_eq!(
+            catalog.to_document(),
+            "[users_db]\nt=db.postgresql\nc.host=db1\n\n[events]\nt=stream.kafka\nc.topic=events.v2\n"
+        );
+        assert_eq!(
+            Catalog::from_document(&catalog.to_document()).unwrap(),
+            Catalog::new()
+                .with("users_db", catalog.get("users_db").unwrap().clone())
+                .with("events", catalog.get("events").unwrap().clone())
+        );
+
+        let err = Catalog::from_document("t=file.csv").unwrap_err();
+        assert!(err.to_string().starts_with("line 1: "));
+        let err = Catalog::from_document("[a]\nt=file.csv\n\n[b]\nc.path=/x").unwrap_err();
+        assert!(err.to_string().starts_with("line 4: in entry `b`"));
+        assert!(Catalog::from_document("[ ]\nt=file.csv").is_err());
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let err = "ok = t=file.csv\nbroken = c.path=/x"
+            .parse::<Catalog>()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 2: in entry `broken`"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Content checksums
+//!
+//! [`UCDF::content_hash`] hashes the canonical form of a descriptor, so two
+//! descriptors with the same sections in any order hash alike. Volatile keys
+//! that change without the source changing, such as `m.updated`, are left
+//! out. [`UCDF::with_checksum`] stores the hash in `m.checksum`, letting
+//! consumers tell whether a stored descriptor has drifted from the live one.
+//!
+//! The hash is 64-bit FNV-1a written as 16 hex digits. It is stable across
+//! releases and platforms but not cryptographic; sign descriptors
+//! (`UCDF::sign`, feature `with-signing`) to detect tampering.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let stored = parse("t=db.postgresql;c.host=db1;c.port=5432;m.updated=2024-05-01")
+//!     .unwrap()
+//!     .with_checksum();
+//! assert!(stored.checksum_matches());
+//!
+//! let live = parse("t=db.postgresql;c.port=5432;c.host=db1;m.updated=2024-06-12").unwrap();
+//! assert_eq!(live.content_hash(), stored.content_hash());
+//! let moved = parse("t=db.postgresql;c.host=db2;c.port=5432").unwrap();
+//! assert_ne!(moved.content_hash(), stored.content_hash());
+//! ```
+
+use crate::sections::{KeyPath, UCDF};
+
+/// Metadata key holding the content hash
+pub const CHECKSUM_KEY: &str = "checksum";
+
+/// Key paths left out of [`UCDF::content_hash`]
+pub const VOLATILE_KEYS: &[&str] = &[
+    "m.updated",
+    "m.modified_at",
+    "m.modified_by",
+    "m.rev",
+    "m.sig",
+];
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl UCDF {
+    /// Hash the canonical form without `m.checksum` and [`VOLATILE_KEYS`]
+    pub fn content_hash(&self) -> String {
+        self.content_hash_excluding(VOLATILE_KEYS)
+    }
+
+    /// Hash the canonical form without `m.checksum` and the key paths in
+    /// `volatile`
+    pub fn content_hash_excluding(&self, volatile: &[&str]) -> String {
+        let mut stable = self.clone();
+        stable.metadata.remove(CHECKSUM_KEY);
+        for path in volatile {
+            match KeyPath::parse(path) {
+                Some(KeyPath::Include) => stable.includes.clear(),
+                Some(KeyPath::EncryptedConnection) => stable.encrypted_connection = None,
+                Some(KeyPath::Access) => stable.access_mode = None,
+                Some(KeyPath::Connection(key)) => {
+                    stable.connection.remove(key);
+                }
+                Some(KeyPath::Structure(key)) => {
+                    stable.structure.remove(key);
+                }
+                Some(KeyPath::SectionAccess(key)) => {
+                    stable.section_access.shift_remove(key);
+                }
+                Some(KeyPath::Meta(key)) => {
+                    stable.metadata.remove(key);
+                }
+                Some(KeyPath::Type | KeyPath::Custom(_, _)) | None => {}
+            }
+        }
+        let hash = stable
+            .to_canonical_string()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            });
+        format!("{:016x}", hash)
+    }
+
+    /// Store [`UCDF::content_hash`] in `m.checksum`, replacing any earlier one
+    pub fn with_checksum(mut self) -> Self {
+        let hash = self.content_hash();
+        self.metadata.insert(CHECKSUM_KEY, hash);
+        self
+    }
+
+    /// Check if `m.checksum` holds the current content hash
+    pub fn checksum_matches(&self) -> bool {
+        self.metadata
+            .get(CHECKSUM_KEY)
+            .is_some_and(|checksum| *checksum == self.content_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_content_hash() {
+        let ucdf = parse("t=file.csv").unwrap();
+        assert_eq!(ucdf.content_hash(), "c1411421a3c18c66");
+
+        let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\";c.etag=abc;a=r").unwrap();
+        assert!(!ucdf.checksum_matches());
+        let stored = ucdf.clone().with_checksum();
+        assert_eq!(stored.clone().with_checksum(), stored);
+        assert_ne!(
+            ucdf.content_hash_excluding(&["c.etag"]),
+            ucdf.content_hash()
+        );
+        assert_eq!(
+            ucdf.content_hash_excluding(&["c.etag"]),
+            ucdf.clone()
+                .with_connection("etag", "def")
+                .content_hash_excluding(&["c.etag", "m.nope", "x"])
+        );
+        let mut drifted = parse(&stored.to_string()).unwrap();
+        assert!(drifted.checksum_matches());
+        drifted.access_mode = None;
+        assert!(!drifted.checksum_matches());
+    }
+}
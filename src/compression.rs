@@ -0,0 +1,107 @@
+//! Compressed descriptors
+//!
+//! Descriptors with large schemas can exceed the size limits of the places
+//! they are embedded in, such as HTTP headers or Kubernetes annotations.
+//! [`UCDF::to_compressed_string`] writes a descriptor as `ucdfz:` followed by
+//! its deflate-compressed text in URL-safe base64 without padding, and
+//! [`UCDF::from_compressed_string`] reads it back. The prefix tells the two
+//! forms apart, so readers can accept both.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{parse, UCDF};
+//!
+//! let fields = (0..200).map(|i| format!("column_{}:str", i)).collect::<Vec<_>>().join(",");
+//! let ucdf = parse(&format!("t=db.postgresql;c.host=db1;s.fields={}", fields)).unwrap();
+//! let compressed = ucdf.to_compressed_string();
+//! assert!(compressed.starts_with("ucdfz:"));
+//! assert!(compressed.len() < ucdf.to_string().len() / 4);
+//! assert_eq!(UCDF::from_compressed_string(&compressed).unwrap(), ucdf);
+//! ```
+
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::error::{Error, Result};
+use crate::parser::parse;
+use crate::sections::UCDF;
+
+/// Prefix marking a compressed descriptor
+pub const COMPRESSED_PREFIX: &str = "ucdfz:";
+
+// Longest decompressed descriptor read, guarding against compression bombs
+const MAX_DECOMPRESSED_LEN: u64 = 16 * 1024 * 1024;
+
+impl UCDF {
+    /// Write the descriptor compressed, prefixed with `ucdfz:`
+    pub fn to_compressed_string(&self) -> String {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(self.to_string().as_bytes())
+            .and_then(|_| encoder.finish())
+            .map(|compressed| {
+                format!(
+                    "{}{}",
+                    COMPRESSED_PREFIX,
+                    URL_SAFE_NO_PAD.encode(compressed)
+                )
+            })
+            .expect("writing to a Vec cannot fail")
+    }
+
+    /// Read a descriptor written by [`UCDF::to_compressed_string`]
+    ///
+    /// Text without the `ucdfz:` prefix is parsed as a plain descriptor.
+    pub fn from_compressed_string(s: &str) -> Result<UCDF> {
+        let s = s.trim();
+        let Some(encoded) = s.strip_prefix(COMPRESSED_PREFIX) else {
+            return parse(s);
+        };
+        let compressed = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| Error::InvalidFormat(format!("invalid compressed descriptor: {}", e)))?;
+        let mut text = String::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_DECOMPRESSED_LEN + 1)
+            .read_to_string(&mut text)
+            .map_err(|e| Error::InvalidFormat(format!("invalid compressed descriptor: {}", e)))?;
+        if text.len() as u64 > MAX_DECOMPRESSED_LEN {
+            return Err(Error::InvalidFormat(format!(
+                "compressed descriptor exceeds {} bytes",
+                MAX_DECOMPRESSED_LEN
+            )));
+        }
+        parse(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let ucdf =
+            parse("t=api.rest;c.url=\"https://api.example.com\";m.desc=\"Zoë; #1\"").unwrap();
+        let compressed = ucdf.to_compressed_string();
+        assert!(compressed[COMPRESSED_PREFIX.len()..]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(UCDF::from_compressed_string(&compressed).unwrap(), ucdf);
+        assert_eq!(
+            UCDF::from_compressed_string(&ucdf.to_string()).unwrap(),
+            ucdf
+        );
+        assert!(matches!(
+            UCDF::from_compressed_string("ucdfz:not base64!"),
+            Err(Error::InvalidFormat(_))
+        ));
+        assert!(UCDF::from_compressed_string("ucdfz:AAAA").is_err());
+    }
+}
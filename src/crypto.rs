@@ -0,0 +1,125 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::error::{Error, Result};
+use crate::parser::{split_sections, unquote};
+use crate::sections::{quote_value, ConnectionParams, UCDF};
+
+/// Length of an AES-256-GCM key in bytes
+pub const KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+
+impl UCDF {
+    /// Encrypt the connection parameters with AES-256-GCM
+    ///
+    /// The plaintext connection section is cleared and replaced with a
+    /// `c!=<base64>` section holding the nonce and ciphertext. Parameters that
+    /// are already encrypted are merged in first, so the descriptor always has
+    /// at most one encrypted section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let key = [7u8; 32];
+    /// let mut ucdf = parse("t=db.postgresql;c.host=db1;c.password=hunter2").unwrap();
+    /// ucdf.encrypt_connection(&key).unwrap();
+    /// assert!(ucdf.connection.is_empty());
+    /// assert!(ucdf.to_string().contains("c!="));
+    ///
+    /// let mut ucdf = parse(&ucdf.to_string()).unwrap();
+    /// ucdf.decrypt_connection(&key).unwrap();
+    /// assert_eq!(ucdf.connection.get("password").unwrap(), "hunter2");
+    /// ```
+    pub fn encrypt_connection(&mut self, key: &[u8; KEY_LEN]) -> Result<()> {
+        if self.encrypted_connection.is_some() {
+            self.decrypt_connection(key)?;
+        }
+        let mut entries: Vec<_> = self.connection.iter().collect();
+        entries.sort();
+        let plaintext = entries
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, quote_value(value)))
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let cipher = Aes256Gcm::new(key.into());
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| Error::Crypto("encryption failed".to_string()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        self.encrypted_connection = Some(STANDARD.encode(payload));
+        self.connection = ConnectionParams::new();
+        Ok(())
+    }
+
+    /// Decrypt the `c!=` section back into plaintext connection parameters
+    ///
+    /// Does nothing if the descriptor has no encrypted section. Decrypted
+    /// parameters override plaintext parameters with the same key.
+    pub fn decrypt_connection(&mut self, key: &[u8; KEY_LEN]) -> Result<()> {
+        let Some(encoded) = &self.encrypted_connection else {
+            return Ok(());
+        };
+        let payload = STANDARD
+            .decode(encoded)
+            .map_err(|e| Error::Crypto(format!("invalid base64: {}", e)))?;
+        if payload.len() < NONCE_LEN {
+            return Err(Error::Crypto("ciphertext is too short".to_string()));
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+        let cipher = Aes256Gcm::new(key.into());
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                Error::Crypto("decryption failed, wrong key or corrupted data".to_string())
+            })?;
+        let plaintext = String::from_utf8(plaintext)
+            .map_err(|_| Error::Crypto("decrypted data is not UTF-8".to_string()))?;
+
+        for section in split_sections(&plaintext) {
+            let (key, value) = section
+                .split_once('=')
+                .ok_or_else(|| Error::InvalidSectionFormat(section.to_string()))?;
+            let value = unquote(value);
+            self.connection.insert(key, value);
+        }
+        self.encrypted_connection = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = [42u8; KEY_LEN];
+        let mut ucdf =
+            parse("t=api.rest;c.url=\"https://api.example.com\";c.token=abc;a=r").unwrap();
+        let original = ucdf.clone();
+
+        ucdf.encrypt_connection(&key).unwrap();
+        let text = ucdf.to_string();
+        assert!(!text.contains("abc"));
+
+        let mut parsed = parse(&text).unwrap();
+        assert_eq!(parsed, ucdf);
+        assert!(matches!(
+            parsed.clone().decrypt_connection(&[0u8; KEY_LEN]),
+            Err(Error::Crypto(_))
+        ));
+        parsed.decrypt_connection(&key).unwrap();
+        assert_eq!(parsed, original);
+    }
+}
@@ -0,0 +1,209 @@
+//! dbt `profiles.yml` export
+//!
+//! [`UCDF::to_dbt_profile`] writes a database descriptor as a dbt profile
+//! with a single target. Connection keys map to the adapter's settings:
+//! `c.host`, `c.port`, `c.user`, `c.db` (as `dbname`) and `c.schema`.
+//! Passwords are never inlined: `${VAR}` and `env:VAR` references become
+//! `env_var('VAR')` calls, and any other password is read from an
+//! environment variable named after the profile, e.g. `ANALYTICS_PASSWORD`.
+//!
+//! | Source                   | Adapter      | Default schema |
+//! |--------------------------|--------------|----------------|
+//! | `db.postgresql`          | `postgres`   | `public`       |
+//! | `db.redshift`            | `redshift`   | `public`       |
+//! | `db.mysql`, `db.mariadb` | `mysql`      | `c.db`         |
+//! | `db.mssql`               | `sqlserver`  | `dbo`          |
+//! | `db.clickhouse`          | `clickhouse` | `c.db`         |
+//! | `db.oracle`              | `oracle`     |                |
+//! | `db.snowflake`           | `snowflake`  |                |
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432;c.user=dbt;c.password=${PG_PASSWORD};c.db=sales").unwrap();
+//! assert_eq!(
+//!     ucdf.to_dbt_profile("analytics", "dev").unwrap(),
+//!     "analytics:
+//!   target: dev
+//!   outputs:
+//!     dev:
+//!       type: postgres
+//!       host: db1
+//!       port: 5432
+//!       user: dbt
+//!       password: \"{{ env_var('PG_PASSWORD') }}\"
+//!       dbname: sales
+//!       schema: public
+//! "
+//! );
+//! ```
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+// Schema used when the descriptor has no `c.schema`
+enum DefaultSchema {
+    Named(&'static str),
+    Database,
+    Required,
+}
+
+// Subtypes, dbt adapter and default schema
+const ADAPTERS: &[(&[&str], &str, DefaultSchema)] = &[
+    (
+        &["postgresql", "postgres"],
+        "postgres",
+        DefaultSchema::Named("public"),
+    ),
+    (&["redshift"], "redshift", DefaultSchema::Named("public")),
+    (&["mysql", "mariadb"], "mysql", DefaultSchema::Database),
+    (
+        &["mssql", "sqlserver"],
+        "sqlserver",
+        DefaultSchema::Named("dbo"),
+    ),
+    (&["clickhouse"], "clickhouse", DefaultSchema::Database),
+    (&["oracle"], "oracle", DefaultSchema::Required),
+    (&["snowflake"], "snowflake", DefaultSchema::Required),
+];
+
+impl UCDF {
+    /// Write a `profiles.yml` entry for profile `profile` with target `target`
+    pub fn to_dbt_profile(&self, profile: &str, target: &str) -> Result<String> {
+        let subtype = self.source_type.subtype.as_deref().unwrap_or_default();
+        let Some((_, adapter, default_schema)) = ADAPTERS
+            .iter()
+            .find(|(subtypes, _, _)| self.source_type.is_db() && subtypes.contains(&subtype))
+        else {
+            return Err(Error::UnsupportedSource(format!(
+                "{} (no dbt adapter)",
+                self.source_type
+            )));
+        };
+        let get = |key: &str| self.connection.get(key).map(String::as_str);
+
+        let schema = match (get("schema"), default_schema) {
+            (Some(schema), _) => schema,
+            (None, DefaultSchema::Named(schema)) => schema,
+            (None, DefaultSchema::Database) => get("db").unwrap_or_default(),
+            (None, DefaultSchema::Required) => "",
+        };
+        if schema.is_empty() {
+            return Err(Error::InvalidFormat(format!(
+                "{} descriptors require c.schema for dbt",
+                self.source_type
+            )));
+        }
+
+        let mut out = format!(
+            "{}:\n  target: {}\n  outputs:\n    {}:\n      type: {}\n",
+            yaml_str(profile),
+            yaml_str(target),
+            yaml_str(target),
+            adapter
+        );
+        let mut setting = |name: &str, value: String| {
+            out.push_str(&format!("      {}: {}\n", name, value));
+        };
+        if let Some(host) = get("host") {
+            setting("host", yaml_str(host));
+        }
+        if let Some(port) = get("port") {
+            let port = match port.parse::<u16>() {
+                Ok(port) => port.to_string(),
+                Err(_) => yaml_str(port),
+            };
+            setting("port", port);
+        }
+        if let Some(user) = get("user") {
+            setting("user", yaml_str(user));
+        }
+        if let Some(password) = get("password") {
+            let var = password
+                .strip_prefix("${")
+                .and_then(|v| v.strip_suffix('}'))
+                .or_else(|| password.strip_prefix("env:"))
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    format!(
+                        "{}_PASSWORD",
+                        profile.replace(['.', '-'], "_").to_uppercase()
+                    )
+                });
+            setting(
+                "password",
+                yaml_str(&format!("{{{{ env_var('{}') }}}}", var)),
+            );
+        }
+        if let Some(db) = get("db") {
+            setting("dbname", yaml_str(db));
+        }
+        setting("schema", yaml_str(schema));
+        Ok(out)
+    }
+}
+
+// YAML scalar, double-quoted unless it is plain text that YAML reads back as
+// the same string
+fn yaml_str(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+        && s.parse::<f64>().is_err()
+        && !matches!(
+            s.to_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+        );
+    if plain {
+        s.to_string()
+    } else {
+        format!("{:?}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_dbt_profile() {
+        let ucdf =
+            parse("t=db.mariadb;c.host=db1;c.port=3306;c.user=dbt;c.password=hunter2;c.db=shop")
+                .unwrap();
+        let profile = ucdf.to_dbt_profile("shop-analytics", "prod").unwrap();
+        assert_eq!(
+            profile,
+            "shop-analytics:
+  target: prod
+  outputs:
+    prod:
+      type: mysql
+      host: db1
+      port: 3306
+      user: dbt
+      password: \"{{ env_var('SHOP_ANALYTICS_PASSWORD') }}\"
+      dbname: shop
+      schema: shop
+"
+        );
+        assert!(!profile.contains("hunter2"));
+
+        let oracle = parse("t=db.oracle;c.host=db1").unwrap();
+        assert!(oracle.to_dbt_profile("a", "dev").is_err());
+        let oracle = parse("t=db.oracle;c.host=db1;c.schema=APP").unwrap();
+        assert!(oracle
+            .to_dbt_profile("a", "dev")
+            .unwrap()
+            .ends_with("schema: APP\n"));
+        assert!(parse("t=db.mongodb")
+            .unwrap()
+            .to_dbt_profile("a", "dev")
+            .is_err());
+        assert!(parse("t=file.csv")
+            .unwrap()
+            .to_dbt_profile("a", "dev")
+            .is_err());
+    }
+}
@@ -0,0 +1,66 @@
+//! Deprecated sources
+//!
+//! A descriptor is marked deprecated with `m.deprecated=true`, and
+//! `m.superseded_by=<name>` names the catalog entry replacing it, which
+//! implies deprecation. [`lint`](crate::lint) warns about deprecated
+//! descriptors and [`lint_catalog`](crate::lint_catalog) about references to
+//! them, so cleanups can be tracked from the catalog itself.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.mysql;c.host=legacy-db;m.deprecated=true;m.superseded_by=orders_pg").unwrap();
+//! let deprecation = ucdf.deprecation().unwrap();
+//! assert_eq!(deprecation.superseded_by.as_deref(), Some("orders_pg"));
+//! ```
+
+use crate::sections::UCDF;
+
+/// Metadata key marking a descriptor deprecated
+pub const DEPRECATED_KEY: &str = "deprecated";
+/// Metadata key naming the catalog entry replacing a deprecated descriptor
+pub const SUPERSEDED_BY_KEY: &str = "superseded_by";
+
+/// Deprecation of a descriptor, see [`UCDF::deprecation`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Name of the catalog entry replacing the descriptor
+    pub superseded_by: Option<String>,
+}
+
+impl UCDF {
+    /// Get the deprecation, or `None` for descriptors in good standing
+    ///
+    /// A descriptor is deprecated if `m.deprecated` is `true` or
+    /// `m.superseded_by` is set, unless `m.deprecated` is `false`.
+    pub fn deprecation(&self) -> Option<Deprecation> {
+        let superseded_by = self
+            .metadata
+            .get(SUPERSEDED_BY_KEY)
+            .filter(|name| !name.is_empty())
+            .cloned();
+        let deprecated = match self.metadata.get(DEPRECATED_KEY) {
+            Some(flag) if flag.eq_ignore_ascii_case("true") => true,
+            Some(flag) if flag.eq_ignore_ascii_case("false") => false,
+            _ => superseded_by.is_some(),
+        };
+        deprecated.then_some(Deprecation { superseded_by })
+    }
+
+    /// Check if the descriptor is deprecated, see [`UCDF::deprecation`]
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecation().is_some()
+    }
+
+    /// Mark the descriptor deprecated, optionally superseded by another entry
+    pub fn deprecate(&mut self, superseded_by: Option<&str>) -> &mut Self {
+        self.metadata.insert(DEPRECATED_KEY, "true");
+        match superseded_by {
+            Some(name) => self.metadata.insert(SUPERSEDED_BY_KEY, name),
+            None => self.metadata.remove(SUPERSEDED_BY_KEY),
+        };
+        self
+    }
+}
@@ -0,0 +1,172 @@
+//! Descriptors with their comments and layout
+//!
+//! Descriptors may carry `#` comments running to the end of the line. A `#`
+//! outside quoted values starts one when it begins a line, or when no
+//! further section follows on its line and it begins a section or, in text
+//! written over several lines, follows whitespace in a value. A descriptor
+//! may be written over several lines, each line break ending a section. [`parse`](crate::parse) drops comments
+//! and layout; a [`Document`] keeps them, so a hand-maintained descriptor
+//! can be read, annotated and written back unchanged.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::Document;
+//!
+//! let text = "# Nightly export of the users table
+//! t=file.csv
+//! c.path=/data/users.csv  # rotated daily
+//! s.fields=id:int,name:str";
+//! let doc: Document = text.parse().unwrap();
+//! assert_eq!(doc.ucdf().to_string(), "t=file.csv;c.path=/data/users.csv;s.fields=id:int,name:str");
+//! assert_eq!(doc.annotation("c.path"), Some("rotated daily"));
+//! assert_eq!(doc.comments().next(), Some("Nightly export of the users table"));
+//! assert_eq!(doc.to_string(), text);
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::parser::{layout_spans, parse, SpanKind};
+use crate::sections::UCDF;
+
+/// Part of the text of a [`Document`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Piece<'a> {
+    /// `key=value` section as written
+    Section(&'a str),
+    /// Comment, including its `#`
+    Comment(&'a str),
+    /// Separators, whitespace and line breaks
+    Space(&'a str),
+}
+
+/// Descriptor text with its comments and layout
+///
+/// The descriptor is parsed once; [`Document::ucdf`] returns it. Writing the
+/// document with `Display` gives back the text it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    text: String,
+    spans: Vec<(SpanKind, Range<usize>)>,
+    ucdf: UCDF,
+}
+
+impl Document {
+    /// Parse descriptor text, keeping its comments and layout
+    pub fn parse(text: impl Into<String>) -> Result<Document> {
+        let text = text.into();
+        let ucdf = parse(&text)?;
+        let spans = layout_spans(text.as_bytes());
+        Ok(Document { text, spans, ucdf })
+    }
+
+    /// Get the descriptor
+    pub fn ucdf(&self) -> &UCDF {
+        &self.ucdf
+    }
+
+    /// Take the descriptor, dropping comments and layout
+    pub fn into_ucdf(self) -> UCDF {
+        self.ucdf
+    }
+
+    /// Iterate over the sections, comments and space in text order
+    pub fn pieces(&self) -> impl Iterator<Item = Piece<'_>> {
+        self.spans.iter().map(|(kind, span)| {
+            let text = &self.text[span.clone()];
+            match kind {
+                SpanKind::Section => Piece::Section(text),
+                SpanKind::Comment => Piece::Comment(text),
+                SpanKind::Space => Piece::Space(text),
+            }
+        })
+    }
+
+    /// Iterate over the comments, without `#` and surrounding whitespace
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.pieces().filter_map(|piece| match piece {
+            Piece::Comment(comment) => Some(comment_text(comment)),
+            _ => None,
+        })
+    }
+
+    /// Get the comment ending the line of the last section with key path
+    /// `path`, without `#` and surrounding whitespace
+    pub fn annotation(&self, path: &str) -> Option<&str> {
+        let pieces: Vec<_> = self.pieces().collect();
+        let index = pieces.iter().rposition(|piece| match piece {
+            Piece::Section(section) => section
+                .split_once('=')
+                .is_some_and(|(key, _)| key.trim() == path),
+            _ => false,
+        })?;
+        for piece in &pieces[index + 1..] {
+            match piece {
+                Piece::Comment(comment) => return Some(comment_text(comment)),
+                Piece::Space(space) if !space.contains('\n') => {}
+                _ => return None,
+            }
+        }
+        None
+    }
+}
+
+// Text of a comment, without `#` and surrounding whitespace
+fn comment_text(comment: &str) -> &str {
+    comment.trim_start_matches('#').trim()
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Document::parse(s)
+    }
+}
+
+impl fmt::Display for Document {
+    /// Write the text the document was parsed from
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments() {
+        let text = "t=db.postgresql;c.host=db1 # primary\r\n\
+                    # c.host=db2\n\
+                    \tc.url=\"http://x/#a b\";m.color=#fff #quoted, ok\n";
+        let doc = Document::parse(text).unwrap();
+        assert_eq!(
+            doc.ucdf(),
+            &parse("t=db.postgresql;c.host=db1;c.url=\"http://x/#a b\";m.color=#fff").unwrap()
+        );
+        assert_eq!(doc.annotation("c.host"), Some("primary"));
+        assert_eq!(doc.annotation("m.color"), Some("quoted, ok"));
+        assert_eq!(doc.annotation("c.url"), None);
+        assert_eq!(doc.annotation("t"), None);
+        assert_eq!(
+            doc.comments().collect::<Vec<_>>(),
+            ["primary", "c.host=db2", "quoted, ok"]
+        );
+        assert_eq!(doc.to_string(), text);
+        assert_eq!(doc.pieces().nth(2), Some(Piece::Section("c.host=db1")));
+
+        let ucdf = parse("t=file.csv;m.color=#fff;c.path=/a.csv").unwrap();
+        assert_eq!(ucdf.get_path("m.color").as_deref(), Some("#fff"));
+        let mut ucdf = parse("t=file.csv").unwrap();
+        ucdf.add_metadata("desc", "line one #1\nline two");
+        assert_eq!(
+            ucdf.to_string(),
+            "t=file.csv;m.desc=\"line one #1\\nline two\""
+        );
+        assert_eq!(parse(&ucdf.to_string()).unwrap(), ucdf);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::UCDF;
+use crate::validate::{Rule, TypeKeys, ValidationReport};
+
+/// Connection keys filled by [`UCDF::apply_dotenv`], and the ones it couldn't fill
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DotenvReport {
+    /// Key paths filled, with the variable each was read from
+    pub satisfied: Vec<(String, String)>,
+    /// Key paths whose variable the file doesn't define
+    pub unsatisfied: Vec<(String, String)>,
+}
+
+impl DotenvReport {
+    /// Check if every reference and missing key was filled
+    pub fn is_complete(&self) -> bool {
+        self.unsatisfied.is_empty()
+    }
+}
+
+impl UCDF {
+    /// Fill connection keys from a `.env` file
+    ///
+    /// Environment references (`${VAR}` and `env:VAR`) are replaced by the
+    /// variable's value from the file. Keys required for the source type
+    /// (see [`TypeKeys`]) that the descriptor lacks are read from variables
+    /// named after them, `c.host` from `HOST` and `c.auth.user` from
+    /// `AUTH_USER`. The process environment is neither read nor changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ucdf::parse;
+    ///
+    /// // .env: PG_PASSWORD=hunter2
+    /// //       HOST=localhost
+    /// let mut ucdf = parse("t=db.postgresql;c.password=${PG_PASSWORD}").unwrap();
+    /// let report = ucdf.apply_dotenv(".env").unwrap();
+    /// assert!(report.is_complete());
+    /// assert_eq!(ucdf.get_path("c.host").as_deref(), Some("localhost"));
+    /// ```
+    pub fn apply_dotenv(&mut self, path: impl AsRef<Path>) -> Result<DotenvReport> {
+        let path = path.as_ref();
+        let vars = dotenvy::from_path_iter(path)
+            .and_then(|iter| iter.collect::<std::result::Result<HashMap<_, _>, _>>())
+            .map_err(|e| Error::ParseError(e.to_string()))
+            .with_context(|| format!("reading {}", path.display()))?;
+
+        let mut report = DotenvReport::default();
+        let mut fill = |ucdf: &mut UCDF, key: &str, var: &str| match vars.get(var) {
+            Some(value) => {
+                ucdf.add_connection(key, value.as_str());
+                report
+                    .satisfied
+                    .push((format!("c.{}", key), var.to_string()));
+            }
+            None => report
+                .unsatisfied
+                .push((format!("c.{}", key), var.to_string())),
+        };
+
+        let references: Vec<(String, String)> = self
+            .connection
+            .iter()
+            .filter_map(|(key, value)| {
+                let var = value
+                    .strip_prefix("${")
+                    .and_then(|v| v.strip_suffix('}'))
+                    .or_else(|| value.strip_prefix("env:"))?;
+                Some((key.to_string(), var.to_string()))
+            })
+            .collect();
+        for (key, var) in references {
+            fill(self, &key, &var);
+        }
+
+        let mut missing = ValidationReport::new();
+        TypeKeys::default().check(self, &mut missing);
+        for issue in missing.issues {
+            if let Some(key) = issue.key.as_deref().and_then(|key| key.strip_prefix("c.")) {
+                let var = key.replace('.', "_").to_uppercase();
+                fill(self, key, &var);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_apply_dotenv() {
+        let path = std::env::temp_dir().join(format!("ucdf-dotenv-{}", std::process::id()));
+        std::fs::write(&path, "# local\nPG_PASSWORD=hunter2\nHOST='db.local'\n").unwrap();
+
+        let mut ucdf =
+            parse("t=db.postgresql;c.user=env:PG_USER;c.password=${PG_PASSWORD};c.db=sales")
+                .unwrap();
+        let report = ucdf.apply_dotenv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            ucdf,
+            parse(
+                "t=db.postgresql;c.user=env:PG_USER;c.password=hunter2;c.db=sales;c.host=db.local"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            report.satisfied,
+            [
+                ("c.password".to_string(), "PG_PASSWORD".to_string()),
+                ("c.host".to_string(), "HOST".to_string()),
+            ]
+        );
+        assert_eq!(
+            report.unsatisfied,
+            [("c.user".to_string(), "PG_USER".to_string())]
+        );
+        assert!(!report.is_complete());
+
+        assert!(ucdf.apply_dotenv(&path).is_err());
+    }
+}
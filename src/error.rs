@@ -1,14 +1,25 @@
 use thiserror::Error;
 
+use crate::validate::Issue;
+
 /// Result type for UCDF operations
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Error enum for UCDF parsing and operations
+///
+/// New error kinds may be added in minor releases, so matches need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Missing required type section (t=...)")]
     MissingTypeSection,
 
+    #[error("Duplicate type section: {0}")]
+    DuplicateTypeSection(String),
+
+    #[error("Trailing input at byte {offset}: `{text}`")]
+    TrailingInput { offset: usize, text: String },
+
     #[error("Invalid section format: {0}")]
     InvalidSectionFormat(String),
 
@@ -27,6 +38,9 @@ pub enum Error {
     #[error("Invalid type declaration: {0}")]
     InvalidTypeDeclaration(String),
 
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
     #[error("Unknown section prefix: {0}")]
     UnknownSectionPrefix(String),
 
@@ -38,6 +52,118 @@ pub enum Error {
 
     #[error("Nom parsing error: {0}")]
     NomError(String),
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("Missing template variables: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+
+    #[error("Unresolved reference: {0}")]
+    UnresolvedReference(String),
+
+    #[error("Reference cycle: {0}")]
+    ReferenceCycle(String),
+
+    #[error("Include cycle: {0}")]
+    IncludeCycle(String),
+
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
+
+    #[error("Unsupported source type: {0}")]
+    UnsupportedSource(String),
+
+    #[error("Secret not found: {0}")]
+    SecretNotFound(String),
+
+    #[error("Secret provider error: {0}")]
+    SecretProvider(String),
+
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Descriptor expired at {0}")]
+    Expired(String),
+
+    #[error("Registry error: {0}")]
+    Registry(String),
+
+    #[error("No resolver registered for source type: {0}")]
+    NoResolver(String),
+
+    #[error("Validation failed: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    Validation(Vec<Issue>),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{context}: {source}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Wrap the error with a description of where it happened
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Error::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get the innermost error, skipping all context layers
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// Iterate over the context descriptions, outermost first
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        let mut current = Some(self);
+        std::iter::from_fn(move || match current? {
+            Error::Context { context, source } => {
+                current = Some(source);
+                Some(context.as_str())
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Extension trait for adding context to UCDF results
+pub trait ResultExt<T> {
+    /// Wrap the error with a description of where it happened
+    fn context(self, context: impl Into<String>) -> Result<T>;
+
+    /// Wrap the error with a lazily built description of where it happened
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T>;
+}
+
+impl<T> ResultExt<T> for Result<T> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|e| e.context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, f: impl FnOnce() -> C) -> Result<T> {
+        self.map_err(|e| e.context(f()))
+    }
+}
+
+/// Non-fatal issue detected while parsing
+#[derive(Error, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Warning {
+    #[error("Unknown source type: {0}")]
+    UnknownSourceType(String),
 }
 
 impl From<nom::Err<nom::error::Error<&str>>> for Error {
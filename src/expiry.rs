@@ -0,0 +1,151 @@
+//! Descriptor expiry
+//!
+//! Temporary credentials and staging sources carry an RFC 3339 expiry time
+//! in `m.expires`. [`UCDF::is_expired`] and [`Catalog::expired`] find the
+//! descriptors past it, and [`UCDF::ensure_unexpired`] refuses them before a
+//! connection is made.
+//!
+//! # Examples
+//!
+//! ```
+//! use chrono::{DateTime, Duration, Utc};
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=staging;m.expires=2025-12-31T00:00:00Z").unwrap();
+//! let now = DateTime::parse_from_rfc3339("2026-01-15T09:30:00Z").unwrap().with_timezone(&Utc);
+//! assert!(ucdf.is_expired(now));
+//! assert!(ucdf.ensure_unexpired(now).is_err());
+//!
+//! let mut ucdf = parse("t=db.postgresql;c.host=staging").unwrap();
+//! ucdf.expire_after(Duration::hours(8));
+//! assert!(!ucdf.is_expired(Utc::now()));
+//! ```
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+
+use crate::catalog::Catalog;
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// Metadata key holding the RFC 3339 expiry time
+pub const EXPIRES_KEY: &str = "expires";
+
+impl UCDF {
+    /// Get the expiry time, or `None` for descriptors that don't expire
+    pub fn expires(&self) -> Result<Option<DateTime<Utc>>> {
+        self.metadata
+            .get(EXPIRES_KEY)
+            .map(|at| {
+                DateTime::parse_from_rfc3339(at)
+                    .map(|at| at.with_timezone(&Utc))
+                    .map_err(|e| Error::InvalidFormat(format!("m.expires is not RFC 3339: {}", e)))
+            })
+            .transpose()
+    }
+
+    /// Set `m.expires` to the given time
+    pub fn set_expires(&mut self, at: DateTime<Utc>) -> &mut Self {
+        self.metadata
+            .insert(EXPIRES_KEY, at.to_rfc3339_opts(SecondsFormat::Secs, true));
+        self
+    }
+
+    /// Set `m.expires` to `ttl` from now
+    pub fn expire_after(&mut self, ttl: Duration) -> &mut Self {
+        self.set_expires(Utc::now() + ttl)
+    }
+
+    /// Check if the descriptor has expired at `now`
+    ///
+    /// A descriptor whose `m.expires` can't be read counts as expired.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match self.expires() {
+            Ok(expires) => expires.is_some_and(|at| at <= now),
+            Err(_) => true,
+        }
+    }
+
+    /// Fail with [`Error::Expired`] if the descriptor has expired at `now`
+    pub fn ensure_unexpired(&self, now: DateTime<Utc>) -> Result<()> {
+        match self.expires()? {
+            Some(at) if at <= now => Err(Error::Expired(
+                at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Catalog {
+    /// Iterate over the entries expired at `now`, see [`UCDF::is_expired`]
+    pub fn expired(&self, now: DateTime<Utc>) -> impl Iterator<Item = (&str, &UCDF)> {
+        self.iter().filter(move |(_, ucdf)| ucdf.is_expired(now))
+    }
+
+    /// Remove the entries expired at `now`, returning their names
+    pub fn remove_expired(&mut self, now: DateTime<Utc>) -> Vec<String> {
+        let names: Vec<String> = self
+            .expired(now)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for name in &names {
+            self.remove(name);
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_expiry() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let now = at("2025-06-01T00:00:00Z");
+        let mut catalog = Catalog::new()
+            .with("prod", parse("t=db.postgresql;c.host=db1").unwrap())
+            .with(
+                "temp",
+                parse("t=db.postgresql;c.host=db2;m.expires=\"2025-06-01T00:00:00+00:00\"")
+                    .unwrap(),
+            )
+            .with("broken", parse("t=file.csv;m.expires=tomorrow").unwrap())
+            .with(
+                "staging",
+                parse("t=file.csv;m.expires=\"2025-06-02T00:00:00Z\"").unwrap(),
+            );
+        assert_eq!(
+            catalog
+                .expired(now)
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            ["temp", "broken"]
+        );
+        assert!(matches!(
+            catalog.get("temp").unwrap().ensure_unexpired(now),
+            Err(Error::Expired(at)) if at == "2025-06-01T00:00:00Z"
+        ));
+        assert!(matches!(
+            catalog.get("broken").unwrap().ensure_unexpired(now),
+            Err(Error::InvalidFormat(_))
+        ));
+        assert!(catalog
+            .get("staging")
+            .unwrap()
+            .ensure_unexpired(now)
+            .is_ok());
+
+        assert_eq!(catalog.remove_expired(now), ["temp", "broken"]);
+        assert_eq!(catalog.names().collect::<Vec<_>>(), ["prod", "staging"]);
+
+        let mut ucdf = parse("t=file.csv").unwrap();
+        ucdf.set_expires(now);
+        assert_eq!(
+            ucdf.to_string(),
+            "t=file.csv;m.expires=\"2025-06-01T00:00:00Z\""
+        );
+        assert_eq!(ucdf.expires().unwrap(), Some(now));
+    }
+}
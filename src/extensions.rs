@@ -0,0 +1,333 @@
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::{parse, split_sections, unquote};
+use crate::sections::{KeyPath, UCDF};
+
+/// Section prefixes defined by the format, which cannot be registered
+pub const RESERVED_PREFIXES: &[&str] = &["t", "i", "c", "c!", "s", "a", "m"];
+
+/// Typed value of a custom section, serialized back through its `Display`
+///
+/// Implemented for every `Clone + Debug + Display` type, including `String`
+/// for sections kept as raw text.
+pub trait CustomSection: Any + fmt::Debug + fmt::Display + Send + Sync {
+    /// Clone into a new box
+    fn clone_box(&self) -> Box<dyn CustomSection>;
+}
+
+impl<T: Any + Clone + fmt::Debug + fmt::Display + Send + Sync> CustomSection for T {
+    fn clone_box(&self) -> Box<dyn CustomSection> {
+        Box::new(self.clone())
+    }
+}
+
+/// Custom sections of a descriptor, keyed by `prefix.key` in insertion order
+///
+/// Extensions compare equal when their serialized forms are equal, and
+/// deserialize as raw `String` values.
+#[derive(Debug, Default)]
+pub struct Extensions(Vec<(String, Box<dyn CustomSection>)>);
+
+impl Extensions {
+    /// Create an empty extension map
+    pub const fn new() -> Self {
+        Extensions(Vec::new())
+    }
+
+    /// Insert a value under `prefix.key`, returning the value it replaced
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl CustomSection,
+    ) -> Option<Box<dyn CustomSection>> {
+        self.insert_boxed(key.into(), Box::new(value))
+    }
+
+    /// Sort the sections by key
+    pub fn sort_keys(&mut self) {
+        self.0.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    pub(crate) fn insert_boxed(
+        &mut self,
+        key: String,
+        value: Box<dyn CustomSection>,
+    ) -> Option<Box<dyn CustomSection>> {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => Some(std::mem::replace(&mut entry.1, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Get a value by `prefix.key`
+    pub fn get(&self, key: &str) -> Option<&dyn CustomSection> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Get a value by `prefix.key` as a concrete type
+    pub fn get_as<T: Any>(&self, key: &str) -> Option<&T> {
+        let value: &dyn Any = self.get(key)?;
+        value.downcast_ref()
+    }
+
+    /// Remove a value by `prefix.key`
+    pub fn remove(&mut self, key: &str) -> Option<Box<dyn CustomSection>> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over `(prefix.key, value)` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &dyn CustomSection)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_ref()))
+    }
+}
+
+impl Clone for Extensions {
+    fn clone(&self) -> Self {
+        Extensions(
+            self.0
+                .iter()
+                .map(|(key, value)| (key.clone(), value.as_ref().clone_box()))
+                .collect(),
+        )
+    }
+}
+
+impl PartialEq for Extensions {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self.iter().all(|(key, value)| {
+                other
+                    .get(key)
+                    .is_some_and(|other| other.to_string() == value.to_string())
+            })
+    }
+}
+
+impl Serialize for Extensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(key, value)| (key, value.to_string())))
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = BTreeMap::<String, String>::deserialize(deserializer)?;
+        let mut extensions = Extensions::new();
+        for (key, value) in raw {
+            extensions.insert(key, value);
+        }
+        Ok(extensions)
+    }
+}
+
+/// Parses the value of a custom section into a typed value
+///
+/// Implemented for closures `Fn(&str, &str) -> Result<Box<dyn CustomSection>>`
+/// taking the key after the prefix and the unquoted value.
+pub trait SectionHandler: Send + Sync {
+    /// Parse the value of `prefix.key`
+    fn parse(&self, key: &str, value: &str) -> Result<Box<dyn CustomSection>>;
+}
+
+impl<F> SectionHandler for F
+where
+    F: Fn(&str, &str) -> Result<Box<dyn CustomSection>> + Send + Sync,
+{
+    fn parse(&self, key: &str, value: &str) -> Result<Box<dyn CustomSection>> {
+        self(key, value)
+    }
+}
+
+/// Handlers for custom section prefixes, such as `q.` for query hints
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{CustomSection, Result, SectionRegistry};
+///
+/// let registry = SectionRegistry::new().register("q", |_: &str, value: &str| -> Result<Box<dyn CustomSection>> {
+///     Ok(Box::new(value.parse::<u32>().map_err(|e| ucdf::Error::ParseError(e.to_string()))?))
+/// });
+/// let ucdf = registry.parse("t=db.postgresql;c.host=db1;q.timeout=30").unwrap();
+/// assert_eq!(ucdf.extensions.get_as::<u32>("q.timeout"), Some(&30));
+/// assert_eq!(ucdf.to_string(), "t=db.postgresql;c.host=db1;q.timeout=30");
+/// ```
+#[derive(Default)]
+pub struct SectionRegistry {
+    handlers: HashMap<String, Box<dyn SectionHandler>>,
+}
+
+impl SectionRegistry {
+    /// Create a registry without handlers
+    pub fn new() -> Self {
+        SectionRegistry::default()
+    }
+
+    /// Register a handler for a prefix, written without the trailing dot
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix is one of [`RESERVED_PREFIXES`].
+    pub fn register(
+        mut self,
+        prefix: impl Into<String>,
+        handler: impl SectionHandler + 'static,
+    ) -> Self {
+        let prefix = prefix.into();
+        assert!(
+            !RESERVED_PREFIXES.contains(&prefix.as_str()),
+            "section prefix `{}` is reserved",
+            prefix
+        );
+        self.handlers.insert(prefix, Box::new(handler));
+        self
+    }
+
+    /// Register a prefix whose values are kept as raw strings
+    pub fn register_raw(self, prefix: impl Into<String>) -> Self {
+        self.register(
+            prefix,
+            |_: &str, value: &str| -> Result<Box<dyn CustomSection>> {
+                Ok(Box::new(value.to_string()))
+            },
+        )
+    }
+
+    /// Check if a handler is registered for a prefix
+    pub fn contains(&self, prefix: &str) -> bool {
+        self.handlers.contains_key(prefix)
+    }
+
+    /// Parse a UCDF string, handling sections with registered prefixes
+    pub fn parse(&self, s: &str) -> Result<UCDF> {
+        let mut standard = Vec::new();
+        let mut custom = Vec::new();
+        for section in split_sections(s) {
+            let handled = section.split_once('=').and_then(|(key, value)| {
+                let KeyPath::Custom(prefix, name) = KeyPath::parse(key)? else {
+                    return None;
+                };
+                let handler = self.handlers.get(prefix)?;
+                Some((key, name, value, handler))
+            });
+            match handled {
+                Some(entry) => custom.push(entry),
+                None => standard.push(section),
+            }
+        }
+
+        let mut ucdf = parse(&standard.join(";"))?;
+        for (key, name, value, handler) in custom {
+            if name.is_empty() {
+                return Err(Error::InvalidSectionFormat(key.to_string()));
+            }
+            let value = unquote(value);
+            let parsed = handler
+                .parse(name, &value)
+                .with_context(|| format!("in section `{}`", key))?;
+            ucdf.extensions.insert_boxed(key.to_string(), parsed);
+        }
+        Ok(ucdf)
+    }
+}
+
+impl fmt::Debug for SectionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut prefixes: Vec<&String> = self.handlers.keys().collect();
+        prefixes.sort();
+        f.debug_struct("SectionRegistry")
+            .field("prefixes", &prefixes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Hint {
+        index: String,
+        parallel: bool,
+    }
+
+    impl fmt::Display for Hint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.index)?;
+            if self.parallel {
+                write!(f, "+parallel")?;
+            }
+            Ok(())
+        }
+    }
+
+    fn parse_hint(_: &str, value: &str) -> Result<Box<dyn CustomSection>> {
+        let (index, parallel) = match value.strip_suffix("+parallel") {
+            Some(index) => (index, true),
+            None => (value, false),
+        };
+        if index.is_empty() {
+            return Err(Error::InvalidFormat("empty index hint".to_string()));
+        }
+        Ok(Box::new(Hint {
+            index: index.to_string(),
+            parallel,
+        }))
+    }
+
+    #[test]
+    fn test_custom_sections() {
+        let registry = SectionRegistry::new()
+            .register("q", parse_hint)
+            .register_raw("x");
+        let ucdf = registry
+            .parse("t=db.postgresql;q.orders=idx_date+parallel;x.note=\"a;b\";m.env=prod")
+            .unwrap();
+        assert_eq!(
+            ucdf.extensions.get_as::<Hint>("q.orders"),
+            Some(&Hint {
+                index: "idx_date".to_string(),
+                parallel: true
+            })
+        );
+        assert_eq!(
+            ucdf.to_string(),
+            "t=db.postgresql;m.env=prod;q.orders=idx_date+parallel;x.note=\"a;b\""
+        );
+        assert_eq!(registry.parse(&ucdf.to_string()).unwrap(), ucdf);
+
+        let err = registry.parse("t=db.postgresql;q.orders=").unwrap_err();
+        assert_eq!(
+            err.contexts().collect::<Vec<_>>(),
+            vec!["in section `q.orders`"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reserved")]
+    fn test_reserved_prefix() {
+        SectionRegistry::new().register_raw("m");
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped_transform, is_not, tag, take_while1},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{all_consuming, map, opt, value},
+    multi::many0,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use crate::catalog::Catalog;
+use crate::error::{Error, Result};
+use crate::sections::{glob_match, AccessMode, UCDF};
+
+/// Compiled filter expression for selecting descriptors
+///
+/// Expressions compare key paths with values and combine the comparisons
+/// with `&&`, `||`, `!` and parentheses:
+///
+/// ```text
+/// t.category == "db" && m.env == "prod" && a contains "w"
+/// ```
+///
+/// Key paths are those of [`UCDF::get_path`] plus `t.category` and
+/// `t.subtype`. The operators are `==`, `!=`, `contains` (substring, or flag
+/// set for `a`) and `matches` (glob with `*` and `?`). A key path on its own
+/// checks that the key is present. Values are double-quoted strings or bare
+/// words; a comparison with a missing key is false.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{parse, Filter};
+///
+/// let filter: Filter = r#"t.category == "db" && (m.env == prod || m.env matches "stag*")"#
+///     .parse()
+///     .unwrap();
+/// assert!(filter.matches(&parse("t=db.mysql;m.env=staging").unwrap()));
+/// assert!(!filter.matches(&parse("t=db.mysql;m.env=dev").unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Exists(String),
+    Compare(String, Op, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Contains,
+    Matches,
+}
+
+impl Filter {
+    /// Check if a descriptor satisfies the filter
+    pub fn matches(&self, ucdf: &UCDF) -> bool {
+        self.expr.eval(ucdf)
+    }
+
+    /// Iterate over the catalog entries satisfying the filter
+    pub fn select<'a>(&'a self, catalog: &'a Catalog) -> impl Iterator<Item = (&'a str, &'a UCDF)> {
+        catalog.find(move |ucdf| self.matches(ucdf))
+    }
+}
+
+impl Expr {
+    fn eval(&self, ucdf: &UCDF) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(ucdf) && b.eval(ucdf),
+            Expr::Or(a, b) => a.eval(ucdf) || b.eval(ucdf),
+            Expr::Not(inner) => !inner.eval(ucdf),
+            Expr::Exists(path) => lookup(ucdf, path).is_some(),
+            Expr::Compare(path, op, expected) => {
+                let Some(actual) = lookup(ucdf, path) else {
+                    return false;
+                };
+                match op {
+                    Op::Eq => actual == *expected,
+                    Op::Ne => actual != *expected,
+                    Op::Contains if path == "a" => {
+                        match (actual.parse::<AccessMode>(), expected.parse::<AccessMode>()) {
+                            (Ok(actual), Ok(expected)) => actual.contains(expected),
+                            _ => false,
+                        }
+                    }
+                    Op::Contains => actual.contains(expected.as_str()),
+                    Op::Matches => glob_match(expected, &actual),
+                }
+            }
+        }
+    }
+}
+
+fn lookup(ucdf: &UCDF, path: &str) -> Option<String> {
+    match path {
+        "t.category" => Some(ucdf.source_type.category.clone()),
+        "t.subtype" => ucdf.source_type.subtype.clone(),
+        _ => ucdf.get_path(path),
+    }
+}
+
+// Parsers for the filter grammar, lowest precedence first
+
+fn ws<'a, O>(
+    inner: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn or_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = and_expr(input)?;
+    let (input, rest) = many0(preceded(ws(tag("||")), and_expr))(input)?;
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |a, b| Expr::Or(Box::new(a), Box::new(b))),
+    ))
+}
+
+fn and_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = unary_expr(input)?;
+    let (input, rest) = many0(preceded(ws(tag("&&")), unary_expr))(input)?;
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |a, b| Expr::And(Box::new(a), Box::new(b))),
+    ))
+}
+
+fn unary_expr(input: &str) -> IResult<&str, Expr> {
+    ws(alt((
+        map(preceded(char('!'), unary_expr), |e| Expr::Not(Box::new(e))),
+        delimited(char('('), or_expr, char(')')),
+        comparison,
+    )))(input)
+}
+
+fn comparison(input: &str) -> IResult<&str, Expr> {
+    let (input, path) = path(input)?;
+    let (input, rhs) = opt(pair(ws(operator), literal))(input)?;
+    Ok((
+        input,
+        match rhs {
+            Some((op, literal)) => Expr::Compare(path.to_string(), op, literal),
+            None => Expr::Exists(path.to_string()),
+        },
+    ))
+}
+
+fn path(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'))(input)
+}
+
+fn operator(input: &str) -> IResult<&str, Op> {
+    alt((
+        value(Op::Eq, tag("==")),
+        value(Op::Ne, tag("!=")),
+        value(Op::Contains, terminated(tag("contains"), multispace1)),
+        value(Op::Matches, terminated(tag("matches"), multispace1)),
+    ))(input)
+}
+
+fn literal(input: &str) -> IResult<&str, String> {
+    alt((
+        map(tag("\"\""), |_| String::new()),
+        delimited(
+            char('"'),
+            escaped_transform(
+                is_not("\\\""),
+                '\\',
+                alt((value("\\", char('\\')), value("\"", char('"')))),
+            ),
+            char('"'),
+        ),
+        map(
+            take_while1(|c: char| {
+                c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '*' | '?' | '/' | ':')
+            }),
+            str::to_string,
+        ),
+    ))(input)
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (_, expr) = all_consuming(tuple((or_expr, multispace0)))(s)
+            .map(|(rest, (expr, _))| (rest, expr))
+            .map_err(|e| {
+                let position = match &e {
+                    nom::Err::Error(e) | nom::Err::Failure(e) => s.len() - e.input.len(),
+                    nom::Err::Incomplete(_) => s.len(),
+                };
+                Error::InvalidFilter(format!("unexpected input at position {}: {}", position, s))
+            })?;
+        Ok(Filter {
+            source: s.to_string(),
+            expr,
+        })
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_filter_expressions() {
+        let filter: Filter = r#"t.category == "db" && m.env == "prod" && a contains "w""#
+            .parse()
+            .unwrap();
+        assert!(filter.matches(&parse("t=db.postgresql;a=rw;m.env=prod").unwrap()));
+        assert!(!filter.matches(&parse("t=db.postgresql;a=r;m.env=prod").unwrap()));
+        assert!(!filter.matches(&parse("t=file.csv;a=rw;m.env=prod").unwrap()));
+
+        let filter: Filter = "!m.owner || c.host matches *.internal".parse().unwrap();
+        assert!(filter.matches(&parse("t=db.mysql").unwrap()));
+        assert!(filter.matches(&parse("t=db.mysql;c.host=pg.internal;m.owner=x").unwrap()));
+        assert!(!filter.matches(&parse("t=db.mysql;c.host=pg.example;m.owner=x").unwrap()));
+
+        let filter: Filter = r#"m.desc != "say \"hi\"""#.parse().unwrap();
+        assert!(!filter.matches(&parse("t=db.mysql").unwrap()));
+        assert!(filter.matches(&parse("t=db.mysql;m.desc=hello").unwrap()));
+    }
+
+    #[test]
+    fn test_select_from_catalog() {
+        let catalog: Catalog = "sales = t=db.postgresql;m.env=prod\n\
+                                users = t=file.csv;m.env=prod\n\
+                                dev = t=db.mysql;m.env=dev"
+            .parse()
+            .unwrap();
+        let filter: Filter = "t.category == db && m.env == prod".parse().unwrap();
+        let names: Vec<&str> = filter.select(&catalog).map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["sales"]);
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for input in ["", "t ==", "t == db &&", "(t == db", "t = db", "t contains"] {
+            assert!(
+                matches!(input.parse::<Filter>(), Err(Error::InvalidFilter(_))),
+                "{}",
+                input
+            );
+        }
+    }
+}
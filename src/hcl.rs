@@ -0,0 +1,193 @@
+//! Terraform/HCL export
+//!
+//! [`UCDF::to_hcl`] writes a descriptor as a `locals` block, so Terraform
+//! code can use the same connection settings as the data catalog. Secret
+//! connection keys (see [`is_secret_key`]) are never inlined: each becomes a
+//! sensitive input variable, referenced from the locals as `var.<name>`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=db1;c.password=${PG_PASSWORD};a=r").unwrap();
+//! assert_eq!(
+//!     ucdf.to_hcl("warehouse"),
+//!     r#"variable "warehouse_password" {
+//!   type      = string
+//!   sensitive = true
+//! }
+//!
+//! locals {
+//!   warehouse = {
+//!     type = "db.postgresql"
+//!     connection = {
+//!       host     = "db1"
+//!       password = var.warehouse_password
+//!     }
+//!     access_mode = "r"
+//!   }
+//! }
+//! "#
+//! );
+//! ```
+
+use crate::lint::is_secret_key;
+use crate::sections::UCDF;
+
+impl UCDF {
+    /// Write the descriptor as HCL: a sensitive variable per secret
+    /// connection key, then a `locals` block holding the descriptor as
+    /// object `name`
+    pub fn to_hcl(&self, name: &str) -> String {
+        let name = identifier(name);
+        let mut out = String::new();
+
+        let mut connection = Vec::new();
+        for (key, value) in self.connection.iter() {
+            if is_secret_key(key) {
+                let var = format!("{}_{}", name, identifier(key));
+                out.push_str(&format!(
+                    "variable \"{}\" {{\n  type      = string\n  sensitive = true\n}}\n\n",
+                    var
+                ));
+                connection.push((key.to_string(), format!("var.{}", var)));
+            } else {
+                connection.push((key.to_string(), string(value)));
+            }
+        }
+
+        out.push_str(&format!("locals {{\n  {} = {{\n", name));
+        out.push_str(&format!(
+            "    type = {}\n",
+            string(&self.source_type.to_string())
+        ));
+        object(&mut out, "connection", connection);
+        object(
+            &mut out,
+            "structure",
+            self.structure
+                .iter()
+                .map(|(key, data)| (key.clone(), string(&data.to_string())))
+                .collect(),
+        );
+        if let Some(mode) = self.access_mode {
+            out.push_str(&format!(
+                "    access_mode = {}\n",
+                string(&mode.to_string())
+            ));
+        }
+        object(
+            &mut out,
+            "metadata",
+            self.metadata
+                .iter()
+                .map(|(key, value)| (key.to_string(), string(value)))
+                .collect(),
+        );
+        out.push_str("  }\n}\n");
+        out
+    }
+}
+
+// Write a nested object attribute, aligning the `=` of its entries as
+// `terraform fmt` does
+fn object(out: &mut String, name: &str, entries: Vec<(String, String)>) {
+    if entries.is_empty() {
+        return;
+    }
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(key, value)| (attribute_name(&key), value))
+        .collect();
+    let width = entries.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    out.push_str(&format!("    {} = {{\n", name));
+    for (key, value) in entries {
+        out.push_str(&format!(
+            "      {:width$} = {}\n",
+            key,
+            value,
+            width = width
+        ));
+    }
+    out.push_str("    }\n");
+}
+
+// Object key, quoted unless it is a valid identifier
+fn attribute_name(key: &str) -> String {
+    if identifier(key) == key && !key.starts_with(|c: char| c.is_ascii_digit()) {
+        key.to_string()
+    } else {
+        string(key)
+    }
+}
+
+// Identifier made of the alphanumeric characters of `s`, others replaced by `_`
+fn identifier(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// Quoted HCL string, escaping quotes, control characters and template
+// sequences
+fn string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                quoted.push(c);
+                quoted.push(c);
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_to_hcl() {
+        let ucdf = parse(
+            "t=file.csv;c.path=/data/${day}.csv;c.auth.token=abc;s.fields=id:int;m.desc=\"say \\\"hi\\\"\"",
+        )
+        .unwrap();
+        let hcl = ucdf.to_hcl("daily-users");
+        assert_eq!(
+            hcl,
+            r#"variable "daily_users_auth_token" {
+  type      = string
+  sensitive = true
+}
+
+locals {
+  daily_users = {
+    type = "file.csv"
+    connection = {
+      path         = "/data/$${day}.csv"
+      "auth.token" = var.daily_users_auth_token
+    }
+    structure = {
+      fields = "id:int"
+    }
+    metadata = {
+      desc = "say \"hi\""
+    }
+  }
+}
+"#
+        );
+        assert!(!hcl.contains("abc"));
+    }
+}
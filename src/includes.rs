@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::catalog::Catalog;
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::parse;
+use crate::sections::UCDF;
+
+/// Loads the descriptors named by `i=` include sections
+///
+/// Implemented for [`Catalog`] (lookup by entry name), [`FileLoader`] (one
+/// descriptor per file) and closures `Fn(&str) -> Result<UCDF>`.
+pub trait Loader {
+    /// Load the descriptor for an include source
+    fn load(&self, source: &str) -> Result<UCDF>;
+}
+
+impl<F: Fn(&str) -> Result<UCDF>> Loader for F {
+    fn load(&self, source: &str) -> Result<UCDF> {
+        self(source)
+    }
+}
+
+impl Loader for Catalog {
+    fn load(&self, source: &str) -> Result<UCDF> {
+        self.get(source)
+            .cloned()
+            .ok_or_else(|| Error::UnresolvedReference(source.to_string()))
+    }
+}
+
+/// Loads includes from files containing a single descriptor
+#[derive(Debug, Clone, Default)]
+pub struct FileLoader {
+    base: Option<PathBuf>,
+}
+
+impl FileLoader {
+    /// Create a loader resolving paths as written
+    pub fn new() -> Self {
+        FileLoader::default()
+    }
+
+    /// Resolve relative paths against a base directory
+    pub fn base(mut self, base: impl Into<PathBuf>) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+}
+
+impl Loader for FileLoader {
+    fn load(&self, source: &str) -> Result<UCDF> {
+        let path = match &self.base {
+            Some(base) => base.join(source),
+            None => PathBuf::from(source),
+        };
+        let text = fs::read_to_string(&path)
+            .map_err(Error::from)
+            .with_context(|| format!("reading {}", path.display()))?;
+        parse(text.trim())
+    }
+}
+
+impl UCDF {
+    /// Expand `i=` include sections using a loader
+    ///
+    /// Included descriptors are expanded recursively and applied in
+    /// declaration order, then this descriptor's own sections override them.
+    /// The source type is always this descriptor's. Fails on include cycles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{parse, Catalog};
+    ///
+    /// let catalog = Catalog::new().with(
+    ///     "pg-prod",
+    ///     parse("t=db.postgresql;c.host=db.prod;c.port=5432;a=r").unwrap(),
+    /// );
+    /// let ucdf = parse("t=db.postgresql;i=pg-prod;c.db=sales").unwrap();
+    /// let resolved = ucdf.resolve_includes(&catalog).unwrap();
+    /// assert_eq!(resolved.connection.get("host").unwrap(), "db.prod");
+    /// assert_eq!(resolved.connection.get("db").unwrap(), "sales");
+    /// assert!(resolved.includes.is_empty());
+    /// ```
+    pub fn resolve_includes(&self, loader: &impl Loader) -> Result<UCDF> {
+        self.resolve_includes_in(loader, &mut Vec::new())
+    }
+
+    fn resolve_includes_in(&self, loader: &impl Loader, chain: &mut Vec<String>) -> Result<UCDF> {
+        let mut resolved = UCDF::with_source_type(self.source_type.clone());
+        for source in &self.includes {
+            if chain.contains(source) {
+                chain.push(source.clone());
+                return Err(Error::IncludeCycle(chain.join(" -> ")));
+            }
+            chain.push(source.clone());
+            let included = loader
+                .load(source)
+                .and_then(|included| included.resolve_includes_in(loader, chain))
+                .with_context(|| format!("in include `{}`", source))?;
+            chain.pop();
+            resolved.merge(&included);
+        }
+        let mut own = self.clone();
+        own.includes.clear();
+        resolved.merge(&own);
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_includes() {
+        let catalog: Catalog = "defaults = t=db.postgresql;c.port=5432;a=r;m.team=data\n\
+                                prod = t=db.postgresql;i=defaults;c.host=db.prod;c.sslmode=require"
+            .parse()
+            .unwrap();
+        let ucdf = parse("t=db.postgresql;i=prod;c.db=sales;a=rw").unwrap();
+        assert_eq!(ucdf.to_string(), "t=db.postgresql;i=prod;c.db=sales;a=rw");
+
+        let resolved = ucdf.resolve_includes(&catalog).unwrap();
+        assert_eq!(
+            resolved.to_canonical_string(),
+            "t=db.postgresql;c.db=sales;c.host=db.prod;c.port=5432;c.sslmode=require;a=rw;m.team=data"
+        );
+
+        let loader = |source: &str| parse(&format!("t=file.csv;c.path=/data/{}.csv", source));
+        let resolved = parse("t=file.csv;i=users")
+            .unwrap()
+            .resolve_includes(&loader)
+            .unwrap();
+        assert_eq!(resolved.connection.get("path").unwrap(), "/data/users.csv");
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let catalog: Catalog = "a = t=db.mysql;i=b\nb = t=db.mysql;i=a".parse().unwrap();
+        let err = parse("t=db.mysql;i=a")
+            .unwrap()
+            .resolve_includes(&catalog)
+            .unwrap_err();
+        assert!(matches!(err.root_cause(), Error::IncludeCycle(chain) if chain == "a -> b -> a"));
+    }
+}
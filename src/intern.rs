@@ -0,0 +1,301 @@
+//! Interned section keys
+//!
+//! Catalogs repeat the same few keys (`host`, `port`, `path`, `desc`, ...) in
+//! every descriptor. [`Key`] stores well-known keys as `&'static str` and
+//! shares a single `Arc<str>` between all other occurrences of a key, so
+//! holding many descriptors doesn't hold as many copies of their keys.
+//!
+//! The shared keys live in a global table of at most [`MAX_INTERNED`]
+//! entries; keys seen after it is full are allocated per occurrence.
+//! [`stats`] reports how many key allocations interning avoided.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{intern, parse};
+//!
+//! let before = intern::stats();
+//! let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432").unwrap();
+//! assert!(intern::stats().saved_bytes >= before.saved_bytes + "hostport".len() as u64);
+//! assert_eq!(ucdf.connection.iter().next().unwrap().0, "host");
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Maximum number of distinct keys kept in the global table
+pub const MAX_INTERNED: usize = 4096;
+
+/// Keys stored as `&'static str` without touching the global table
+pub const WELL_KNOWN_KEYS: &[&str] = &[
+    "host",
+    "port",
+    "path",
+    "db",
+    "user",
+    "password",
+    "url",
+    "uri",
+    "brokers",
+    "topic",
+    "group_id",
+    "encoding",
+    "delimiter",
+    "quote",
+    "header",
+    "params",
+    "target",
+    "auth.type",
+    "auth.token",
+    "desc",
+    "owner",
+    "team",
+    "env",
+    "tags",
+    "version",
+];
+
+static TABLE: OnceLock<RwLock<HashSet<Arc<str>>>> = OnceLock::new();
+static STATIC_HITS: AtomicU64 = AtomicU64::new(0);
+static SHARED_HITS: AtomicU64 = AtomicU64::new(0);
+static SAVED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Key of a connection or metadata entry, interned on creation
+#[derive(Clone)]
+pub struct Key(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+impl Key {
+    /// Create a key, reusing a well-known or already interned copy
+    pub fn new(key: &str) -> Key {
+        Key::lookup(key).unwrap_or_else(|| Key::insert(Arc::from(key)))
+    }
+
+    /// Create a key from a static string without interning it
+    pub const fn from_static(key: &'static str) -> Key {
+        Key(Repr::Static(key))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Static(key) => key,
+            Repr::Shared(key) => key,
+        }
+    }
+
+    fn lookup(key: &str) -> Option<Key> {
+        if let Some(known) = WELL_KNOWN_KEYS.iter().find(|known| **known == key) {
+            STATIC_HITS.fetch_add(1, Ordering::Relaxed);
+            SAVED_BYTES.fetch_add(key.len() as u64, Ordering::Relaxed);
+            return Some(Key(Repr::Static(known)));
+        }
+        let table = table().read().unwrap_or_else(|e| e.into_inner());
+        let shared = table.get(key)?;
+        SHARED_HITS.fetch_add(1, Ordering::Relaxed);
+        SAVED_BYTES.fetch_add(key.len() as u64, Ordering::Relaxed);
+        Some(Key(Repr::Shared(shared.clone())))
+    }
+
+    fn insert(key: Arc<str>) -> Key {
+        let mut table = table().write().unwrap_or_else(|e| e.into_inner());
+        if let Some(shared) = table.get(&key) {
+            return Key(Repr::Shared(shared.clone()));
+        }
+        if table.len() < MAX_INTERNED {
+            table.insert(key.clone());
+        }
+        Key(Repr::Shared(key))
+    }
+}
+
+fn table() -> &'static RwLock<HashSet<Arc<str>>> {
+    TABLE.get_or_init(Default::default)
+}
+
+/// Counters of the interning done since the process started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// Keys resolved to a well-known `&'static str`
+    pub static_hits: u64,
+    /// Keys resolved to an already interned `Arc<str>`
+    pub shared_hits: u64,
+    /// Distinct keys in the global table
+    pub interned: usize,
+    /// Bytes held by the keys in the global table
+    pub interned_bytes: usize,
+    /// Bytes of key text not allocated thanks to interning
+    pub saved_bytes: u64,
+}
+
+/// Get the interning counters
+pub fn stats() -> InternStats {
+    let table = table().read().unwrap_or_else(|e| e.into_inner());
+    InternStats {
+        static_hits: STATIC_HITS.load(Ordering::Relaxed),
+        shared_hits: SHARED_HITS.load(Ordering::Relaxed),
+        interned: table.len(),
+        interned_bytes: table.iter().map(|key| key.len()).sum(),
+        saved_bytes: SAVED_BYTES.load(Ordering::Relaxed),
+    }
+}
+
+impl Deref for Key {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Key {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for Key {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialEq<str> for Key {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Key {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Key {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<Key> for &str {
+    fn eq(&self, other: &Key) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&str> for Key {
+    fn from(key: &str) -> Self {
+        Key::new(key)
+    }
+}
+
+impl From<&String> for Key {
+    fn from(key: &String) -> Self {
+        Key::new(key)
+    }
+}
+
+impl From<&Key> for Key {
+    fn from(key: &Key) -> Self {
+        key.clone()
+    }
+}
+
+impl From<String> for Key {
+    fn from(key: String) -> Self {
+        Key::lookup(&key).unwrap_or_else(|| Key::insert(Arc::from(key)))
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.as_str().to_string()
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let key = std::borrow::Cow::<str>::deserialize(deserializer)?;
+        Ok(Key::new(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interning() {
+        let host = Key::new("host");
+        assert!(matches!(host.0, Repr::Static(_)));
+
+        let a = Key::from("x-intern-test".to_string());
+        let b = Key::new("x-intern-test");
+        match (&a.0, &b.0) {
+            (Repr::Shared(a), Repr::Shared(b)) => assert!(Arc::ptr_eq(a, b)),
+            _ => panic!("expected shared keys"),
+        }
+        assert_eq!(a, b);
+        assert_eq!(a, "x-intern-test");
+
+        let stats = stats();
+        assert!(stats.static_hits >= 1 && stats.shared_hits >= 1);
+        assert!(stats.interned >= 1);
+    }
+}
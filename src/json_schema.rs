@@ -0,0 +1,154 @@
+//! JSON Schemas of the serde representation, for OpenAPI documents
+//!
+//! Most types derive [`JsonSchema`]; the types below serialize through
+//! hand-written impls and describe themselves here.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "with-json")] {
+//! let schema = schemars::schema_for!(ucdf::UCDF);
+//! let properties = schema.get("properties").unwrap();
+//! assert!(properties.get("type").is_some());
+//! assert_eq!(schema.get("required").unwrap(), &serde_json::json!(["type"]));
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::borrowed::UCDFRef;
+use crate::extensions::Extensions;
+use crate::sections::{AccessMode, StructureData, UCDF};
+use crate::small_map::SmallMap;
+use crate::types::{Endpoint, Field};
+
+/// Serialized as a map with string keys
+impl<V: JsonSchema> JsonSchema for SmallMap<V> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        BTreeMap::<String, V>::schema_name()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        BTreeMap::<String, V>::schema_id()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        BTreeMap::<String, V>::json_schema(generator)
+    }
+}
+
+/// Serialized as a map of custom section keys to their values as written
+impl JsonSchema for Extensions {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Extensions".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        BTreeMap::<String, String>::json_schema(generator)
+    }
+}
+
+/// Serialized as written after `a=`, such as `rw` or `r+admin`
+impl JsonSchema for AccessMode {
+    fn schema_name() -> Cow<'static, str> {
+        "AccessMode".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "pattern": "^(admin|[rwax]+)(\\+(admin|[rwax]+))*$",
+        })
+    }
+}
+
+// Serialized form of `StructureData`, whose lazy values serialize as the
+// structure they parse to and typed values as their `Display` output
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+#[schemars(rename = "StructureData")]
+enum StructureDataSchema {
+    Fields(Vec<Field>),
+    Endpoints(Vec<Endpoint>),
+    Format(String),
+    Custom(String),
+    Typed(String),
+}
+
+impl JsonSchema for StructureData {
+    fn schema_name() -> Cow<'static, str> {
+        StructureDataSchema::schema_name()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        StructureDataSchema::json_schema(generator)
+    }
+}
+
+/// Same representation as [`UCDF`]
+impl JsonSchema for UCDFRef<'_> {
+    fn schema_name() -> Cow<'static, str> {
+        UCDF::schema_name()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        UCDF::schema_id()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        UCDF::json_schema(generator)
+    }
+}
+
+#[cfg(all(test, feature = "with-json"))]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::types::DataValue;
+
+    #[test]
+    fn test_schema_matches_serialization() {
+        let schema = serde_json::to_value(schemars::schema_for!(UCDF)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        let mut ucdf = parse(
+            "t=db.mysql;c.host=db1;c.password=;s.fields=id:int;s.endpoints=/users:GET;\
+             s.format=csv;a=rw;a.fields=r;m.owner=data;i=base;c!=YWJj",
+        )
+        .unwrap();
+        ucdf.add_fields(vec![Field::new("id", "int", Some(DataValue::Integer(1)))]);
+        let json = serde_json::to_value(&ucdf).unwrap();
+        for key in json.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "{}", key);
+        }
+
+        let defs = schema["$defs"].as_object().unwrap();
+        for name in [
+            "AccessMode",
+            "StructureData",
+            "Field",
+            "DataValue",
+            "Endpoint",
+        ] {
+            assert!(defs.contains_key(name), "{}", name);
+        }
+        assert_eq!(
+            defs["ConnectionParams"]["additionalProperties"]["type"],
+            "string"
+        );
+        assert_eq!(
+            serde_json::to_value(schemars::schema_for!(UCDFRef)).unwrap(),
+            schema
+        );
+    }
+}
@@ -16,8 +16,8 @@
 //!
 //! // Create a UCDF object using builder pattern
 //! let source_type = SourceType::builder()
-//!     .category("db".to_string())
-//!     .subtype(Some("postgresql".to_string()))
+//!     .category("db")
+//!     .subtype("postgresql")
 //!     .build();
 //!
 //! let ucdf = UCDF::builder()
@@ -25,27 +25,180 @@
 //!     .build()
 //!     .with_connection("host", "localhost")
 //!     .with_connection("port", "5432")
-//!     .with_access_mode(AccessMode::ReadWrite);
+//!     .with_access_mode(AccessMode::READ_WRITE);
 //!
 //! // Convert back to string
 //! let ucdf_str = ucdf.to_string();
 //! ```
 
+#[cfg(feature = "with-bumpalo")]
+mod arena;
+#[cfg(feature = "with-auth")]
+mod auth;
+mod borrowed;
+mod budget;
+mod cached;
+mod catalog;
+mod checksum;
+#[cfg(feature = "with-compression")]
+mod compression;
+pub mod convert;
+#[cfg(feature = "with-encryption")]
+mod crypto;
+mod dbt;
+mod deprecation;
+mod document;
+#[cfg(feature = "with-dotenv")]
+mod dotenv;
 mod error;
+#[cfg(feature = "with-chrono")]
+mod expiry;
+mod extensions;
+mod filter;
+mod hcl;
+mod includes;
+pub mod intern;
+#[cfg(feature = "with-schemars")]
+mod json_schema;
+mod lineage;
+pub mod lint;
+mod metrics;
+pub mod migrate;
+#[cfg(any(feature = "with-diesel", feature = "with-sea-orm"))]
+mod orm;
+#[cfg(feature = "with-opentelemetry")]
+mod otel;
+mod pagination;
 mod parser;
+mod pattern;
+mod pool;
+#[cfg(feature = "with-preview")]
+mod preview;
+#[cfg(feature = "with-probe")]
+mod probe;
+mod profiles;
+mod properties;
+mod rate_limit;
+mod refs;
+#[cfg(feature = "with-http")]
+pub mod registry;
+pub mod resolve;
+#[cfg(feature = "with-chrono")]
+mod revision;
+#[cfg(feature = "with-json")]
+mod schema;
+pub mod secrets;
 mod sections;
+#[cfg(feature = "with-signing")]
+mod signing;
+pub mod small_map;
+pub mod source_types;
+#[cfg(feature = "with-tracing")]
+mod spans;
+mod static_ucdf;
+#[cfg(feature = "with-opendal")]
+mod storage;
+#[cfg(feature = "with-tokio")]
+mod stream;
+mod summary;
+#[cfg(feature = "with-arrow")]
+mod table;
+mod template;
 mod types;
+pub mod validate;
+#[cfg(feature = "with-wasm")]
+pub mod wasm;
 
-pub use error::{Error, Result};
-pub use parser::{parse, Parser};
+#[cfg(feature = "with-bumpalo")]
+pub use arena::{parse_in, UcdfIn};
+#[cfg(feature = "with-auth")]
+pub use auth::{Auth, DEFAULT_API_KEY_HEADER};
+pub use borrowed::{Entries, SourceTypeRef, UCDFRef};
+pub use budget::OversizeReport;
+pub use cached::CachedUcdf;
+pub use catalog::Catalog;
+pub use checksum::{CHECKSUM_KEY, VOLATILE_KEYS};
+#[cfg(feature = "with-compression")]
+pub use compression::COMPRESSED_PREFIX;
+#[cfg(feature = "with-encryption")]
+pub use crypto::KEY_LEN;
+pub use deprecation::{Deprecation, DEPRECATED_KEY, SUPERSEDED_BY_KEY};
+pub use document::{Document, Piece};
+#[cfg(feature = "with-dotenv")]
+pub use dotenv::DotenvReport;
+pub use error::{Error, Result, ResultExt, Warning};
+#[cfg(feature = "with-chrono")]
+pub use expiry::EXPIRES_KEY;
+pub use extensions::{
+    CustomSection, Extensions, SectionHandler, SectionRegistry, RESERVED_PREFIXES,
+};
+pub use filter::Filter;
+pub use includes::{FileLoader, Loader};
+pub use intern::Key;
+pub use lineage::{LineageGraph, DERIVES_FROM_KEY};
+pub use lint::{lint, lint_catalog, Lint};
+pub use metrics::METRIC_LABEL_NAMES;
+pub use pagination::{Pagination, PAGINATION_KEY};
+pub use parser::{
+    normalize, parse, parse_bytes, parse_many, section_spans, KeyPolicy, Parser, StructureParser,
+};
+pub use pattern::UcdfPattern;
+pub use pool::PoolConfig;
+#[cfg(feature = "with-preview")]
+pub use preview::{preview, Record};
+#[cfg(feature = "with-probe")]
+pub use probe::{probe, ProbeReport};
+pub use profiles::Profiles;
+pub use rate_limit::{RateLimit, RATE_LIMIT_KEY};
+pub use refs::REF_CATEGORY;
+#[cfg(feature = "with-chrono")]
+pub use revision::{Revision, MODIFIED_AT_KEY, MODIFIED_BY_KEY, REVISION_KEY};
+#[cfg(feature = "with-json")]
+pub use schema::Schema;
 pub use sections::{
-    AccessMode, ConnectionParams, DataType, Metadata, Section, SourceType, StructureData, UCDF,
+    AccessMode, Category, ConnectionParams, DataType, KeyPath, LazyStructure, Metadata, RawValue,
+    Section, SourceType, Structure, StructureData, StructureValue, UCDF,
 };
+#[cfg(feature = "with-signing")]
+pub use signing::SIGNATURE_KEY;
+pub use small_map::SmallMap;
+pub use static_ucdf::{StaticEntries, StaticUcdf};
+#[cfg(feature = "with-opendal")]
+pub use storage::OpendalConfig;
+#[cfg(feature = "with-tokio")]
+pub use stream::read_catalog;
+pub use summary::SourceSummary;
+#[cfg(feature = "with-arrow")]
+pub use table::{TableFormat, TableRegistration};
+pub use template::Template;
 pub use types::{DataValue, Endpoint, Field};
 
 // Re-export nom for public use
 pub use nom;
 
+/// Re-export the `indexmap` crate, whose maps hold the sections of a [`UCDF`]
+pub use indexmap;
+
+/// Re-export the `arrow-schema` crate, whose schemas
+/// [`UCDF::arrow_schema`] builds
+#[cfg(feature = "with-arrow")]
+pub use arrow_schema;
+#[cfg(feature = "with-bb8")]
+pub use bb8;
+/// Re-export the `bumpalo` crate, whose arenas [`parse_in`] parses into
+#[cfg(feature = "with-bumpalo")]
+pub use bumpalo;
+#[cfg(feature = "with-deadpool")]
+pub use deadpool;
+#[cfg(feature = "with-opentelemetry")]
+pub use opentelemetry;
+#[cfg(feature = "with-decimal")]
+pub use rust_decimal;
+#[cfg(feature = "with-sea-orm")]
+pub use sea_orm;
+#[cfg(feature = "with-uuid")]
+pub use uuid;
+
 /// Parse a UCDF string into a UCDF structure
 ///
 /// # Examples
@@ -76,6 +229,23 @@ pub fn to_string(ucdf: &UCDF) -> String {
     ucdf.to_string()
 }
 
+/// Create a UCDF structure from a connection string of `from_scheme`
+///
+/// The scheme names one of the converters of
+/// [`DsnRegistry::with_defaults`](convert::DsnRegistry::with_defaults);
+/// use a [`DsnRegistry`](convert::DsnRegistry) directly to add others.
+///
+/// # Examples
+///
+/// ```
+/// let ucdf = ucdf::convert("mongodb", "mongodb://app@mongo1:27017/shop?authSource=admin").unwrap();
+/// assert_eq!(ucdf.get_path("c.params.authSource").as_deref(), Some("admin"));
+/// assert!(ucdf::convert("gopher", "gopher://host").is_err());
+/// ```
+pub fn convert(from_scheme: &str, input: &str) -> Result<UCDF> {
+    convert::DsnRegistry::with_defaults().parse(from_scheme, input)
+}
+
 /// Re-export the `bon` crate for convenient access to the builder macros
 pub use bon;
 
@@ -0,0 +1,211 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::catalog::Catalog;
+use crate::parser::unquote;
+use crate::sections::{quote_with, UCDF};
+use crate::types::split_list;
+
+/// Metadata key listing the sources a descriptor derives from
+pub const DERIVES_FROM_KEY: &str = "derives_from";
+
+// Characters that need quoting in an upstream source, as the list is split
+// on commas outside `<...>`
+const SOURCE_DELIMITERS: &[char] = &[',', '<', '>'];
+
+impl UCDF {
+    /// Get the sources this descriptor derives from (`m.derives_from`)
+    ///
+    /// Upstream sources are catalog entry names or URNs, separated by commas.
+    /// Sources containing commas, such as
+    /// `urn:li:dataset:(urn:li:dataPlatform:hive,db.t,PROD)`, are quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let urn = "urn:li:dataset:(urn:li:dataPlatform:hive,db.t,PROD)";
+    /// let ucdf = parse("t=db.hive;c.host=h1")
+    ///     .unwrap()
+    ///     .with_derives_from("orders")
+    ///     .with_derives_from(urn);
+    /// let ucdf = parse(&ucdf.to_string()).unwrap();
+    /// assert_eq!(ucdf.derives_from(), ["orders", urn]);
+    /// ```
+    pub fn derives_from(&self) -> Vec<Cow<'_, str>> {
+        self.metadata
+            .get(DERIVES_FROM_KEY)
+            .map(|value| {
+                split_list(value)
+                    .into_iter()
+                    .map(str::trim)
+                    .filter(|source| !source.is_empty())
+                    .map(unquote)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record that this descriptor derives from another source, quoting it if
+    /// it contains commas
+    pub fn add_derives_from(&mut self, source: impl Into<String>) -> &mut Self {
+        let source = source.into();
+        let mut sources = self.derives_from();
+        if !sources.contains(&Cow::Borrowed(source.as_str())) {
+            sources.push(Cow::Owned(source));
+            let value = sources
+                .iter()
+                .map(|source| quote_with(source, SOURCE_DELIMITERS).to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            self.metadata.insert(DERIVES_FROM_KEY, value);
+        }
+        self
+    }
+
+    /// Fluent API for recording an upstream source
+    pub fn with_derives_from(mut self, source: impl Into<String>) -> Self {
+        self.add_derives_from(source);
+        self
+    }
+}
+
+/// Lineage of catalog entries as an adjacency list from each source to its upstreams
+///
+/// Nodes are catalog entry names plus any upstream names or URNs that are not
+/// in the catalog.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineageGraph {
+    upstream: BTreeMap<String, Vec<String>>,
+}
+
+impl LineageGraph {
+    /// Iterate over all nodes in name order
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.upstream.keys().map(String::as_str)
+    }
+
+    /// Get the sources a node derives from directly
+    pub fn upstream(&self, name: &str) -> &[String] {
+        self.upstream.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the nodes deriving directly from a source
+    pub fn downstream(&self, name: &str) -> Vec<&str> {
+        self.upstream
+            .iter()
+            .filter(|(_, upstream)| upstream.iter().any(|source| source == name))
+            .map(|(node, _)| node.as_str())
+            .collect()
+    }
+
+    /// Get all transitive upstream sources of a node, nearest first
+    pub fn ancestors(&self, name: &str) -> Vec<&str> {
+        let mut ancestors: Vec<&str> = Vec::new();
+        let mut queue: Vec<&str> = vec![name];
+        while !queue.is_empty() {
+            let node = queue.remove(0);
+            for source in self.upstream(node) {
+                if source != name && !ancestors.contains(&source.as_str()) {
+                    ancestors.push(source);
+                    queue.push(source);
+                }
+            }
+        }
+        ancestors
+    }
+
+    /// Iterate over `(source, upstream)` edges
+    pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.upstream.iter().flat_map(|(node, upstream)| {
+            upstream
+                .iter()
+                .map(move |source| (node.as_str(), source.as_str()))
+        })
+    }
+}
+
+impl Catalog {
+    /// Build the lineage graph from the `m.derives_from` entries of all descriptors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Catalog;
+    ///
+    /// let catalog: Catalog = "raw = t=file.csv;c.path=/data/raw.csv\n\
+    ///                         clean = t=db.postgresql;c.host=db1;m.derives_from=raw\n\
+    ///                         report = t=file.parquet;c.path=/out;m.derives_from=clean"
+    ///     .parse()
+    ///     .unwrap();
+    /// let graph = catalog.lineage_graph();
+    /// assert_eq!(graph.downstream("raw"), vec!["clean"]);
+    /// assert_eq!(graph.ancestors("report"), vec!["clean", "raw"]);
+    /// ```
+    pub fn lineage_graph(&self) -> LineageGraph {
+        let mut upstream: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, ucdf) in self.iter() {
+            let sources = ucdf.derives_from();
+            for source in &sources {
+                upstream.entry(source.to_string()).or_default();
+            }
+            upstream
+                .entry(name.to_string())
+                .or_default()
+                .extend(sources.into_iter().map(Cow::into_owned));
+        }
+        LineageGraph { upstream }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_lineage_graph() {
+        let mut joined = parse("t=db.postgresql;c.host=db1").unwrap();
+        joined
+            .add_derives_from("orders")
+            .add_derives_from("urn:s3:customers")
+            .add_derives_from("orders");
+        assert_eq!(joined.derives_from(), vec!["orders", "urn:s3:customers"]);
+        let joined = parse(&joined.to_string()).unwrap();
+
+        let catalog = Catalog::new()
+            .with("orders", parse("t=stream.kafka;c.topic=orders").unwrap())
+            .with("joined", joined)
+            .with(
+                "daily",
+                parse("t=file.parquet;c.path=/out")
+                    .unwrap()
+                    .with_derives_from("joined"),
+            );
+        let graph = catalog.lineage_graph();
+        assert_eq!(
+            graph.nodes().collect::<Vec<_>>(),
+            vec!["daily", "joined", "orders", "urn:s3:customers"]
+        );
+        assert_eq!(graph.upstream("joined"), ["orders", "urn:s3:customers"]);
+        assert_eq!(graph.downstream("orders"), vec!["joined"]);
+        assert_eq!(
+            graph.ancestors("daily"),
+            vec!["joined", "orders", "urn:s3:customers"]
+        );
+        assert_eq!(graph.edges().count(), 3);
+
+        // Sources containing commas are quoted
+        let urn = "urn:li:dataset:(urn:li:dataPlatform:hive,db.t,PROD)";
+        let mut hive = parse("t=db.hive;c.host=h1").unwrap();
+        hive.add_derives_from(urn).add_derives_from("orders");
+        assert_eq!(
+            *hive.metadata.get(DERIVES_FROM_KEY).unwrap(),
+            format!("\"{}\",orders", urn)
+        );
+        let hive = parse(&hive.to_string()).unwrap();
+        let graph = Catalog::new().with("hive", hive).lineage_graph();
+        assert_eq!(graph.upstream("hive"), [urn, "orders"]);
+    }
+}
@@ -0,0 +1,350 @@
+//! Linter for UCDF descriptors
+//!
+//! Lints flag descriptors that are valid but likely to cause problems, such as
+//! plaintext credentials or deprecated key names. Each lint has a stable code
+//! and, where possible, a suggested fix.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{lint, parse};
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=localhost;c.password=secret").unwrap();
+//! let codes: Vec<&str> = lint(&ucdf).iter().map(|l| l.code).collect();
+//! assert_eq!(codes, vec!["plaintext-secret", "missing-access-mode"]);
+//! ```
+
+use std::fmt;
+
+use crate::catalog::Catalog;
+use crate::deprecation::{DEPRECATED_KEY, SUPERSEDED_BY_KEY};
+use crate::intern::Key;
+use crate::sections::UCDF;
+use crate::source_types;
+use crate::validate::Severity;
+
+/// Deprecated key paths and their replacements
+pub const DEPRECATED_KEYS: &[(&str, &str)] = &[
+    ("c.username", "c.user"),
+    ("c.hostname", "c.host"),
+    ("c.pass", "c.password"),
+    ("c.passwd", "c.password"),
+    ("c.database", "c.db"),
+    ("c.dbname", "c.db"),
+    ("m.description", "m.desc"),
+];
+
+/// Connection key names that hold secrets
+pub const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "pass",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "private_key",
+    "client_secret",
+    "secret_access_key",
+    "session_token",
+    "account_key",
+    "sas_token",
+];
+
+/// A problem reported by the linter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lint {
+    /// Stable identifier of the lint, e.g. `plaintext-secret`
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Key path the lint refers to, e.g. `c.password`
+    pub key: Option<String>,
+    /// Suggested fix
+    pub suggestion: Option<String>,
+}
+
+impl Lint {
+    fn new(code: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        Lint {
+            code,
+            severity,
+            message: message.into(),
+            key: None,
+            suggestion: None,
+        }
+    }
+
+    fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.severity, self.code)?;
+        if let Some(key) = &self.key {
+            write!(f, " {}:", key)?;
+        }
+        write!(f, " {}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (help: {})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check if a connection key holds a secret, looking at its last segment
+pub fn is_secret_key(key: &str) -> bool {
+    let name = key.rsplit('.').next().unwrap_or(key).to_lowercase();
+    SECRET_KEYS.contains(&name.as_str())
+}
+
+/// Check if a value refers to a secret stored elsewhere (`${VAR}`, `env:VAR`, `secret:...`)
+pub fn is_secret_reference(value: &str) -> bool {
+    (value.starts_with("${") && value.ends_with('}'))
+        || value.starts_with("env:")
+        || value.starts_with("file:")
+        || value.starts_with("secret:")
+}
+
+/// Lint a descriptor
+pub fn lint(ucdf: &UCDF) -> Vec<Lint> {
+    let mut lints = Vec::new();
+
+    let mut keys: Vec<&Key> = ucdf.connection.0.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &ucdf.connection.0[key];
+        if is_secret_key(key) && !value.is_empty() && !is_secret_reference(value) {
+            let var = key.replace('.', "_").to_uppercase();
+            lints.push(
+                Lint::new(
+                    "plaintext-secret",
+                    Severity::Error,
+                    "secret is stored in plaintext",
+                )
+                .key(format!("c.{}", key))
+                .suggest(format!("reference it instead, e.g. c.{}=${{{}}}", key, var)),
+            );
+        }
+    }
+
+    if ucdf.access_mode.is_none() {
+        lints.push(
+            Lint::new(
+                "missing-access-mode",
+                Severity::Warning,
+                "access mode is not declared",
+            )
+            .key("a")
+            .suggest("declare it explicitly, e.g. a=r"),
+        );
+    }
+
+    if !source_types::is_known(&ucdf.source_type) {
+        let mut lint = Lint::new(
+            "unknown-source-type",
+            Severity::Warning,
+            format!("unknown source type `{}`", ucdf.source_type),
+        )
+        .key("t");
+        if let Some(closest) = closest_known_type(&ucdf.source_type.to_string()) {
+            lint = lint.suggest(format!("did you mean `{}`?", closest));
+        }
+        lints.push(lint);
+    }
+
+    for (deprecated, replacement) in DEPRECATED_KEYS {
+        if ucdf.get_path(deprecated).is_some() {
+            lints.push(
+                Lint::new(
+                    "deprecated-key",
+                    Severity::Warning,
+                    format!("`{}` is deprecated", deprecated),
+                )
+                .key(*deprecated)
+                .suggest(format!("rename it to `{}`", replacement)),
+            );
+        }
+    }
+
+    if let Some(deprecation) = ucdf.deprecation() {
+        let mut lint = Lint::new(
+            "deprecated-source",
+            Severity::Warning,
+            "source is deprecated",
+        );
+        lint = match deprecation.superseded_by {
+            Some(name) => lint
+                .key(format!("m.{}", SUPERSEDED_BY_KEY))
+                .suggest(format!("use `{}` instead", name)),
+            None => lint.key(format!("m.{}", DEPRECATED_KEY)),
+        };
+        lints.push(lint);
+    }
+
+    for url_key in ["url", "uri"] {
+        let Some(url) = ucdf.connection.get(url_key) else {
+            continue;
+        };
+        for key in ["host", "port", "db", "user"] {
+            let duplicated = ucdf
+                .connection
+                .get(key)
+                .is_some_and(|value| !value.is_empty() && url.contains(value.as_str()));
+            if duplicated {
+                lints.push(
+                    Lint::new(
+                        "duplicated-info",
+                        Severity::Info,
+                        format!("value is already part of c.{}", url_key),
+                    )
+                    .key(format!("c.{}", key))
+                    .suggest(format!(
+                        "keep it in one place, either c.{} or c.{}",
+                        url_key, key
+                    )),
+                );
+            }
+        }
+    }
+
+    lints
+}
+
+/// Lint every entry of a catalog, returning the entry name with each lint
+///
+/// Besides the lints of [`lint`], references (`t=ref;c.target=<name>`) to
+/// deprecated entries get a `deprecated-ref` warning.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{lint_catalog, Catalog};
+///
+/// let catalog: Catalog = "old = t=db.mysql;c.host=db1;a=r;m.superseded_by=new
+///                         new = t=db.postgresql;c.host=db2;a=r
+///                         report = t=ref;c.target=old;a=r"
+///     .parse()
+///     .unwrap();
+/// let lints: Vec<_> = lint_catalog(&catalog)
+///     .into_iter()
+///     .map(|(name, lint)| format!("{} {}", name, lint.code))
+///     .collect();
+/// assert_eq!(lints, ["old deprecated-source", "report deprecated-ref"]);
+/// ```
+pub fn lint_catalog(catalog: &Catalog) -> Vec<(String, Lint)> {
+    let mut lints = Vec::new();
+    for (name, ucdf) in catalog.iter() {
+        lints.extend(lint(ucdf).into_iter().map(|lint| (name.to_string(), lint)));
+
+        let Some(target) = ucdf.ref_target() else {
+            continue;
+        };
+        let Some(deprecation) = catalog.get(target).and_then(UCDF::deprecation) else {
+            continue;
+        };
+        let mut lint = Lint::new(
+            "deprecated-ref",
+            Severity::Warning,
+            format!("`{}` is deprecated", target),
+        )
+        .key("c.target");
+        if let Some(replacement) = deprecation.superseded_by {
+            lint = lint.suggest(format!("point it at `{}` instead", replacement));
+        }
+        lints.push((name.to_string(), lint));
+    }
+    lints
+}
+
+/// Find the known source type closest to `name`, if it is a plausible typo
+fn closest_known_type(name: &str) -> Option<String> {
+    source_types::known_types()
+        .map(|known| (edit_distance(name, &known), known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j + 1])
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_lints() {
+        let ucdf = parse(
+            "t=db.postgress;c.url=postgresql://pg1:5432/sales;c.host=pg1;c.username=app;c.password=${PG_PASSWORD}",
+        )
+        .unwrap();
+        let lints = lint(&ucdf);
+        let codes: Vec<&str> = lints.iter().map(|l| l.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "missing-access-mode",
+                "unknown-source-type",
+                "deprecated-key",
+                "duplicated-info"
+            ]
+        );
+        assert_eq!(
+            lints[1].suggestion.as_deref(),
+            Some("did you mean `db.postgresql`?")
+        );
+        assert_eq!(lints[2].key.as_deref(), Some("c.username"));
+    }
+
+    #[test]
+    fn test_deprecated_source() {
+        let mut ucdf = parse("t=file.csv;c.path=/old.csv;a=r;m.deprecated=true").unwrap();
+        assert_eq!(
+            lint(&ucdf)[0].to_string(),
+            "warning[deprecated-source] m.deprecated: source is deprecated"
+        );
+        ucdf.deprecate(Some("users_v2"));
+        assert_eq!(
+            lint(&ucdf)[0].to_string(),
+            "warning[deprecated-source] m.superseded_by: source is deprecated (help: use `users_v2` instead)"
+        );
+        let kept =
+            parse("t=file.csv;c.path=/a.csv;a=r;m.deprecated=false;m.superseded_by=b").unwrap();
+        assert!(!kept.is_deprecated());
+        assert!(lint(&kept).is_empty());
+    }
+
+    #[test]
+    fn test_clean_descriptor() {
+        let ucdf = parse("t=file.csv;c.path=/data/users.csv;a=r").unwrap();
+        assert!(lint(&ucdf).is_empty());
+        let ucdf = parse("t=file.s3;c.bucket=lake;a=r").unwrap();
+        assert!(lint(&ucdf).is_empty());
+    }
+}
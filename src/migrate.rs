@@ -0,0 +1,249 @@
+//! Upgrades of descriptors written with older conventions
+//!
+//! Older descriptors used long section prefixes (`type=`, `conn.host=`),
+//! spelled-out access modes (`a=readwrite`) and key names that are now
+//! deprecated (see [`DEPRECATED_KEYS`]). The functions in this module rewrite
+//! them into the current form and report every change they make.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::migrate::migrate_str;
+//!
+//! let (ucdf, report) = migrate_str("type=db.postgresql;conn.hostname=db1;access=read").unwrap();
+//! assert_eq!(ucdf.to_string(), "t=db.postgresql;c.host=db1;a=r");
+//! assert_eq!(report.changes.len(), 5);
+//! ```
+
+use std::fmt;
+
+use crate::catalog::Catalog;
+use crate::error::Result;
+use crate::lint::DEPRECATED_KEYS;
+use crate::parser::{parse, split_sections};
+use crate::sections::UCDF;
+
+/// Legacy section prefixes and their current form
+pub const LEGACY_PREFIXES: &[(&str, &str)] = &[
+    ("type", "t"),
+    ("access", "a"),
+    ("conn.", "c."),
+    ("connection.", "c."),
+    ("struct.", "s."),
+    ("structure.", "s."),
+    ("meta.", "m."),
+    ("metadata.", "m."),
+];
+
+/// Legacy access mode spellings and their current form
+pub const LEGACY_ACCESS_MODES: &[(&str, &str)] = &[
+    ("read", "r"),
+    ("readonly", "r"),
+    ("read-only", "r"),
+    ("ro", "r"),
+    ("write", "w"),
+    ("writeonly", "w"),
+    ("wo", "w"),
+    ("readwrite", "rw"),
+    ("read-write", "rw"),
+    ("read_write", "rw"),
+    ("r/w", "rw"),
+    ("append", "a"),
+    ("exec", "x"),
+    ("execute", "x"),
+];
+
+/// A single rewrite made by a migration
+///
+/// Renamed keys are recorded by key path only, so reports never contain
+/// connection values such as passwords. Access mode rewrites are recorded
+/// as `a=<mode>` with the spellings from [`LEGACY_ACCESS_MODES`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Key path or access mode before the change
+    pub from: String,
+    /// Key path or access mode after the change
+    pub to: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {} ({})", self.from, self.to, self.reason)
+    }
+}
+
+/// Changes made while migrating a descriptor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationReport {
+    pub changes: Vec<Change>,
+}
+
+impl MigrationReport {
+    /// Check if the descriptor was already up to date
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn push(&mut self, from: String, to: String, reason: &'static str) {
+        self.changes.push(Change { from, to, reason });
+    }
+}
+
+/// Migrate a parsed descriptor in place, renaming deprecated keys
+///
+/// A deprecated key is only renamed if its replacement is not set.
+pub fn migrate(ucdf: &mut UCDF) -> MigrationReport {
+    let mut report = MigrationReport::default();
+    for (deprecated, replacement) in DEPRECATED_KEYS {
+        let (Some(old), Some(new)) = (deprecated.split_once('.'), replacement.split_once('.'))
+        else {
+            continue;
+        };
+        let section = match old.0 {
+            "c" => &mut ucdf.connection.0,
+            "m" => &mut ucdf.metadata.0,
+            _ => continue,
+        };
+        if section.contains_key(new.1) {
+            continue;
+        }
+        if let Some((index, _, value)) = section.remove_full(old.1) {
+            report.push(
+                deprecated.to_string(),
+                replacement.to_string(),
+                "deprecated key",
+            );
+            // The renamed key keeps its position
+            section.insert_at(index, new.1.to_string(), value);
+        }
+    }
+    report
+}
+
+/// Migrate a UCDF string that may not parse with current conventions
+///
+/// Legacy prefixes and access mode spellings are rewritten before parsing,
+/// then deprecated keys are renamed as by [`migrate`].
+pub fn migrate_str(s: &str) -> Result<(UCDF, MigrationReport)> {
+    let mut report = MigrationReport::default();
+    let mut sections = Vec::new();
+    for section in split_sections(s) {
+        let Some((key, value)) = section.split_once('=') else {
+            sections.push(section.to_string());
+            continue;
+        };
+        let mut key = key.to_string();
+        for (legacy, current) in LEGACY_PREFIXES {
+            let renamed = match legacy.strip_suffix('.') {
+                Some(_) => key
+                    .strip_prefix(legacy)
+                    .map(|rest| format!("{}{}", current, rest)),
+                None => (key == *legacy).then(|| current.to_string()),
+            };
+            if let Some(renamed) = renamed {
+                report.push(key.clone(), renamed.clone(), "legacy section prefix");
+                key = renamed;
+                break;
+            }
+        }
+
+        let mut value = value.to_string();
+        if key == "a" {
+            let lowered = value.to_lowercase();
+            if let Some((legacy, current)) = LEGACY_ACCESS_MODES
+                .iter()
+                .find(|(legacy, _)| *legacy == lowered)
+            {
+                report.push(
+                    format!("a={}", legacy),
+                    format!("a={}", current),
+                    "legacy access mode",
+                );
+                value = current.to_string();
+            }
+        }
+        sections.push(format!("{}={}", key, value));
+    }
+
+    let mut ucdf = parse(&sections.join(";"))?;
+    report.changes.extend(migrate(&mut ucdf).changes);
+    Ok((ucdf, report))
+}
+
+/// Migrate every descriptor of a catalog, returning reports for the changed entries
+pub fn migrate_catalog(catalog: &mut Catalog) -> Vec<(String, MigrationReport)> {
+    let names: Vec<String> = catalog.names().map(str::to_string).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let report = migrate(catalog.get_mut(&name)?);
+            (!report.is_empty()).then_some((name, report))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_legacy_string() {
+        let (ucdf, report) = migrate_str(
+            "type=db.postgresql;connection.username=app;conn.user=other;meta.description=\"Sales, EU\";access=Read-Write",
+        )
+        .unwrap();
+        assert_eq!(
+            ucdf.to_canonical_string(),
+            "t=db.postgresql;c.user=other;c.username=app;a=rw;m.desc=\"Sales, EU\""
+        );
+        let reasons: Vec<&str> = report.changes.iter().map(|c| c.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "legacy section prefix",
+                "legacy section prefix",
+                "legacy section prefix",
+                "legacy section prefix",
+                "legacy section prefix",
+                "legacy access mode",
+                "deprecated key",
+            ]
+        );
+        assert_eq!(
+            report.changes.last().unwrap().to_string(),
+            "m.description -> m.desc (deprecated key)"
+        );
+        assert_eq!(
+            report.changes[5].to_string(),
+            "a=read-write -> a=rw (legacy access mode)"
+        );
+
+        // Values are left out of the report
+        let (_, report) = migrate_str("conn.pass=hunter2;t=db.mysql").unwrap();
+        let changes: Vec<String> = report.changes.iter().map(Change::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                "conn.pass -> c.pass (legacy section prefix)",
+                "c.pass -> c.password (deprecated key)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_catalog() {
+        let mut catalog: Catalog =
+            "a = t=db.mysql;c.hostname=db1;c.port=3306\nb = t=db.mysql;c.host=db2"
+                .parse()
+                .unwrap();
+        let reports = migrate_catalog(&mut catalog);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].0, "a");
+        assert_eq!(
+            catalog.get("a").unwrap().to_string(),
+            "t=db.mysql;c.host=db1;c.port=3306"
+        );
+        assert!(migrate_catalog(&mut catalog).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! OpenTelemetry semantic-convention attributes
+//!
+//! [`UCDF::otel_attributes`] maps a descriptor to the attributes the
+//! OpenTelemetry conventions define for database and messaging spans:
+//!
+//! - `db.system` and `db.name` (`c.db`) for `db.*` sources
+//! - `messaging.system` and `messaging.destination` (`c.topic` or
+//!   `c.queue`) for `stream.*` sources
+//! - `server.address` and `server.port` for any source, from `c.host`,
+//!   `c.url` or the first of `c.brokers`, and `c.port`
+//!
+//! Secret keys and unresolved references are never used.
+//!
+//! # Examples
+//!
+//! ```
+//! use opentelemetry::KeyValue;
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432;c.db=sales;c.password=hunter2").unwrap();
+//! assert_eq!(
+//!     ucdf.otel_attributes(),
+//!     [
+//!         KeyValue::new("db.system", "postgresql"),
+//!         KeyValue::new("db.name", "sales"),
+//!         KeyValue::new("server.address", "db1"),
+//!         KeyValue::new("server.port", 5432),
+//!     ]
+//! );
+//! ```
+
+use opentelemetry::KeyValue;
+
+use crate::lint::is_secret_reference;
+use crate::metrics::hostname;
+use crate::sections::UCDF;
+
+impl UCDF {
+    /// Get the OpenTelemetry attributes describing the source
+    pub fn otel_attributes(&self) -> Vec<KeyValue> {
+        let get = |key: &str| {
+            self.connection
+                .get(key)
+                .map(String::as_str)
+                .filter(|value| !value.is_empty() && !is_secret_reference(value))
+        };
+        let subtype = self.source_type.subtype.clone();
+        let mut attributes = Vec::new();
+
+        match self.source_type.category.as_str() {
+            "db" => {
+                if let Some(subtype) = subtype {
+                    attributes.push(KeyValue::new("db.system", db_system(&subtype)));
+                }
+                if let Some(db) = get("db") {
+                    attributes.push(KeyValue::new("db.name", db.to_string()));
+                }
+            }
+            "stream" => {
+                if let Some(subtype) = subtype {
+                    attributes.push(KeyValue::new("messaging.system", subtype));
+                }
+                if let Some(destination) = get("topic").or_else(|| get("queue")) {
+                    attributes.push(KeyValue::new(
+                        "messaging.destination",
+                        destination.to_string(),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        let host = get("host")
+            .or_else(|| get("url"))
+            .or_else(|| get("brokers").and_then(|brokers| brokers.split(',').next()))
+            .map(hostname)
+            .filter(|host| !host.is_empty());
+        if let Some(host) = host {
+            attributes.push(KeyValue::new("server.address", host.to_string()));
+        }
+        if let Some(port) = get("port").and_then(|port| port.parse::<u16>().ok()) {
+            attributes.push(KeyValue::new("server.port", i64::from(port)));
+        }
+        attributes
+    }
+}
+
+// `db.system` value of a `db.*` subtype
+fn db_system(subtype: &str) -> String {
+    match subtype {
+        "postgres" => "postgresql",
+        "sqlserver" => "mssql",
+        "mongo" => "mongodb",
+        other => other,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_otel_attributes() {
+        let ucdf = parse(
+            "t=stream.kafka;c.brokers=\"k1.internal:9092,k2.internal:9092\";c.topic=orders;c.password=secret",
+        )
+        .unwrap();
+        assert_eq!(
+            ucdf.otel_attributes(),
+            [
+                KeyValue::new("messaging.system", "kafka"),
+                KeyValue::new("messaging.destination", "orders"),
+                KeyValue::new("server.address", "k1.internal"),
+            ]
+        );
+
+        let ucdf = parse("t=db.postgres;c.host=${DB_HOST};c.port=x").unwrap();
+        assert_eq!(
+            ucdf.otel_attributes(),
+            [KeyValue::new("db.system", "postgresql")]
+        );
+        let api = parse("t=api.rest;c.url=\"https://api.example.com/v1\"").unwrap();
+        assert_eq!(
+            api.otel_attributes(),
+            [KeyValue::new("server.address", "api.example.com")]
+        );
+    }
+}
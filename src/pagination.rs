@@ -0,0 +1,175 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::sections::{StructureData, UCDF};
+
+/// Structure key declaring how an API paginates collections
+pub const PAGINATION_KEY: &str = "pagination";
+
+/// How an API splits collections into pages, declared in `s.pagination`
+///
+/// | Declaration               | Pagination                                    |
+/// |---------------------------|-----------------------------------------------|
+/// | `cursor:<token>`          | `token` from a response fetches the next page |
+/// | `offset:<limit>,<offset>` | `limit` items starting at item `offset`       |
+/// | `page:<page>,<size>`      | page number `page` of `size` items            |
+///
+/// Names are the request parameters (and, for cursors, the response field)
+/// a client sets to walk the collection.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{parse, Pagination};
+///
+/// let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\";s.pagination=cursor:next_token").unwrap();
+/// assert_eq!(
+///     ucdf.pagination().unwrap(),
+///     Some(Pagination::Cursor { token: "next_token".to_string() })
+/// );
+///
+/// let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\"")
+///     .unwrap()
+///     .with_pagination(Pagination::offset("limit", "offset"));
+/// assert_eq!(ucdf.to_string(), "t=api.rest;c.url=\"https://api.example.com\";s.pagination=offset:limit,offset");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pagination {
+    /// Opaque cursor returned in field `token` and sent back as parameter
+    /// `token` for the next page
+    Cursor { token: String },
+    /// Items counted from the start, with parameters for the page size and
+    /// the index of the first item
+    Offset { limit: String, offset: String },
+    /// Numbered pages, with parameters for the page number and page size
+    Page { page: String, size: String },
+}
+
+impl Pagination {
+    /// Create a cursor pagination
+    pub fn cursor(token: impl Into<String>) -> Self {
+        Pagination::Cursor {
+            token: token.into(),
+        }
+    }
+
+    /// Create an offset pagination
+    pub fn offset(limit: impl Into<String>, offset: impl Into<String>) -> Self {
+        Pagination::Offset {
+            limit: limit.into(),
+            offset: offset.into(),
+        }
+    }
+
+    /// Create a page-number pagination
+    pub fn page(page: impl Into<String>, size: impl Into<String>) -> Self {
+        Pagination::Page {
+            page: page.into(),
+            size: size.into(),
+        }
+    }
+
+    /// Get the name of the pagination style: `cursor`, `offset` or `page`
+    pub fn style(&self) -> &'static str {
+        match self {
+            Pagination::Cursor { .. } => "cursor",
+            Pagination::Offset { .. } => "offset",
+            Pagination::Page { .. } => "page",
+        }
+    }
+}
+
+impl FromStr for Pagination {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::InvalidFormat(format!(
+                "expected `cursor:<token>`, `offset:<limit>,<offset>` or `page:<page>,<size>`: {}",
+                s
+            ))
+        };
+        let (style, names) = s.split_once(':').ok_or_else(invalid)?;
+        let names: Vec<&str> = names.split(',').map(str::trim).collect();
+        if names.iter().any(|name| name.is_empty()) {
+            return Err(invalid());
+        }
+        match (style.trim(), names.as_slice()) {
+            ("cursor", [token]) => Ok(Pagination::cursor(*token)),
+            ("offset", [limit, offset]) => Ok(Pagination::offset(*limit, *offset)),
+            ("page", [page, size]) => Ok(Pagination::page(*page, *size)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Pagination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pagination::Cursor { token } => write!(f, "cursor:{}", token),
+            Pagination::Offset { limit, offset } => write!(f, "offset:{},{}", limit, offset),
+            Pagination::Page { page, size } => write!(f, "page:{},{}", page, size),
+        }
+    }
+}
+
+impl UCDF {
+    /// Read the pagination declared in `s.pagination`
+    pub fn pagination(&self) -> Result<Option<Pagination>> {
+        let Some(data) = self.structure.get(PAGINATION_KEY) else {
+            return Ok(None);
+        };
+        if let Some(pagination) = data.as_typed::<Pagination>() {
+            return Ok(Some(pagination.clone()));
+        }
+        data.to_string()
+            .parse()
+            .map(Some)
+            .map_err(|e: Error| e.context(format!("s.{}", PAGINATION_KEY)))
+    }
+
+    /// Fluent API for declaring the pagination in `s.pagination`
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.structure
+            .insert(PAGINATION_KEY.to_string(), StructureData::typed(pagination));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_pagination() {
+        let pagination = |s: &str| {
+            parse(&format!("t=api.rest;c.url=x;s.pagination=\"{}\"", s))
+                .unwrap()
+                .pagination()
+        };
+        assert_eq!(
+            pagination("page: page, per_page").unwrap(),
+            Some(Pagination::page("page", "per_page"))
+        );
+        assert_eq!(parse("t=api.rest").unwrap().pagination().unwrap(), None);
+        for invalid in [
+            "cursor",
+            "cursor:a,b",
+            "offset:limit",
+            "offset:,x",
+            "keyset:id",
+        ] {
+            let err = pagination(invalid).unwrap_err();
+            assert!(err.to_string().starts_with("s.pagination: "), "{}", err);
+        }
+
+        let ucdf = parse("t=api.graphql;c.url=x")
+            .unwrap()
+            .with_pagination(Pagination::cursor("endCursor"));
+        assert_eq!(ucdf.pagination().unwrap().unwrap().style(), "cursor");
+        let reparsed = parse(&ucdf.to_string()).unwrap();
+        assert_eq!(reparsed.pagination().unwrap(), ucdf.pagination().unwrap());
+    }
+}
@@ -1,227 +1,1273 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use memchr::{memchr, memchr3_iter};
 use nom::{
     branch::alt,
-    bytes::complete::{escaped, take_till, take_while1},
+    bytes::complete::{escaped, tag, take_till, take_while1},
     character::complete::{char, none_of, one_of},
-    combinator::map_res,
-    error::{ErrorKind, Error as NomError},
-    multi::separated_list0,
-    sequence::{delimited, separated_pair},
+    combinator::{eof, map, opt, peek},
+    error::{ErrorKind, FromExternalError, ParseError},
+    sequence::{delimited, terminated},
     Err as NomErr, IResult,
 };
 
-use crate::error::{Error, Result};
-use crate::sections::{
-    AccessMode, Section, SourceType, StructureData, UCDF,
-};
-use crate::types::{Endpoint, Field};
+use crate::error::{Error, Result, Warning};
+use crate::extensions::Extensions;
+use crate::intern::Key;
+use crate::sections::{AccessMode, DataType, KeyPath, Section, SourceType, StructureData, UCDF};
+use crate::source_types;
+use crate::types::{split_list, Endpoint, Field};
+
+/// Nom error that keeps the UCDF error which caused a failure
+#[derive(Debug)]
+struct NomError {
+    code: ErrorKind,
+    cause: Option<Error>,
+}
+
+impl NomError {
+    fn new(code: ErrorKind) -> Self {
+        NomError { code, cause: None }
+    }
+
+    fn with_cause(cause: Error) -> Self {
+        NomError {
+            code: ErrorKind::MapRes,
+            cause: Some(cause),
+        }
+    }
+
+    // Attach the position and key of the failing section to the cause; the
+    // value is left out, as it may be a secret
+    fn in_section(self, index: usize, input: &str) -> Self {
+        let key = excerpt(section_key(input));
+        NomError {
+            code: self.code,
+            cause: self
+                .cause
+                .map(|cause| cause.context(format!("in section {} (`{}`)", index, key))),
+        }
+    }
+}
+
+// Key of the section starting `input`, before any `=` or `:`
+fn section_key(input: &str) -> &str {
+    input
+        .split([';', '\n', '=', ':'])
+        .next()
+        .unwrap_or_default()
+}
+
+// Section text, shortened for error messages
+fn excerpt(text: &str) -> String {
+    const MAX_LEN: usize = 40;
+
+    match text.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+// Error for the text at `offset` where the parser stopped reading sections,
+// quoting only its key-like prefix
+pub(crate) fn trailing_input(offset: usize, rest: &str) -> Error {
+    Error::TrailingInput {
+        offset,
+        text: excerpt(section_key(rest)),
+    }
+}
+
+impl ParseError<&str> for NomError {
+    fn from_error_kind(_: &str, code: ErrorKind) -> Self {
+        NomError::new(code)
+    }
+
+    fn append(_: &str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl FromExternalError<&str, Error> for NomError {
+    fn from_external_error(_: &str, _: ErrorKind, e: Error) -> Self {
+        NomError::with_cause(e)
+    }
+}
+
+type PResult<'a, T> = IResult<&'a str, T, NomError>;
+
+/// Parses the value of a structure section `s.<key>` into typed data
+///
+/// Implemented for closures `Fn(&str) -> Result<StructureData>` taking the
+/// unquoted value.
+pub trait StructureParser: Send + Sync {
+    /// Parse the value of the section
+    fn parse(&self, value: &str) -> Result<StructureData>;
+}
+
+impl<F> StructureParser for F
+where
+    F: Fn(&str) -> Result<StructureData> + Send + Sync,
+{
+    fn parse(&self, value: &str) -> Result<StructureData> {
+        self(value)
+    }
+}
+
+// Structure parsers registered on a `Parser`, keyed by structure key
+type StructureParsers = HashMap<String, Arc<dyn StructureParser>>;
+
+// Options of a `Parser` that affect how sections are parsed
+#[derive(Clone, Default)]
+struct ParseOptions {
+    structure_parsers: StructureParsers,
+    lazy_structure: bool,
+    allow_duplicate_type: bool,
+    keep_sections: bool,
+    strict_types: bool,
+    allowed_types: HashSet<String>,
+    key_policy: KeyPolicy,
+    max_key_depth: Option<usize>,
+    #[cfg(feature = "with-unicode")]
+    nfc_keys: bool,
+}
+
+impl ParseOptions {
+    // Normalize a section key and check its segments and characters
+    fn section_key<'k>(&self, key: &'k str) -> Result<Cow<'k, str>> {
+        #[cfg(feature = "with-unicode")]
+        let key = if self.nfc_keys && !unicode_normalization::is_nfc(key) {
+            use unicode_normalization::UnicodeNormalization;
+            Cow::Owned(key.nfc().collect())
+        } else {
+            Cow::Borrowed(key)
+        };
+        #[cfg(not(feature = "with-unicode"))]
+        let key = Cow::Borrowed(key);
+
+        if let Some(name) = KeyPath::parse(&key).and_then(|path| path.key()) {
+            if name.split('.').any(str::is_empty) {
+                return Err(Error::InvalidKey(format!("empty segment in `{}`", key)));
+            }
+            let depth = name.split('.').count();
+            if let Some(max) = self.max_key_depth.filter(|&max| depth > max) {
+                return Err(Error::InvalidKey(format!(
+                    "`{}` has {} segments, at most {} allowed",
+                    key, depth, max
+                )));
+            }
+            self.key_policy.check(name)?;
+        }
+        Ok(key)
+    }
+}
+
+/// Characters allowed in the keys of two-level sections (`c.<key>`,
+/// `m.<key>`, ...), see [`Parser::key_policy`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyPolicy {
+    /// Any character the format allows, that is all but `=` and `;`
+    #[default]
+    Any,
+    /// ASCII letters and digits, `_`, `-` and `.`
+    Ascii,
+    /// Unicode letters and digits, `_`, `-` and `.`
+    Alphanumeric,
+}
+
+impl KeyPolicy {
+    /// Check the characters of a key
+    pub fn check(&self, key: &str) -> Result<()> {
+        let (allowed, description): (fn(char) -> bool, _) = match self {
+            KeyPolicy::Any => return Ok(()),
+            KeyPolicy::Ascii => (|c| c.is_ascii_alphanumeric(), "ASCII letters, digits"),
+            KeyPolicy::Alphanumeric => (char::is_alphanumeric, "letters, digits"),
+        };
+        match key
+            .chars()
+            .find(|&c| !allowed(c) && !matches!(c, '_' | '-' | '.'))
+        {
+            Some(c) => Err(Error::InvalidKey(format!(
+                "unexpected {:?} (U+{:04X}) in `{}`, keys may only contain {}, `_`, `-` and `.`",
+                c, c as u32, key, description
+            ))),
+            None => Ok(()),
+        }
+    }
+}
 
 /// Function to parse a UCDF string into a UCDF structure
 pub fn parse(s: &str) -> Result<UCDF> {
-    match ucdf_parser(s) {
-        Ok((_, ucdf)) => Ok(ucdf),
-        Err(err) => {
-            match err {
-                NomErr::Incomplete(_) => Err(Error::InvalidFormat("Incomplete input".to_string())),
-                NomErr::Error(e) => Err(Error::InvalidFormat(format!("Parser error: {:?}", e.code))),
-                NomErr::Failure(e) => {
-                    if e.code == ErrorKind::Tag {
-                        // For specific errors like invalid access mode
-                        Err(Error::InvalidAccessMode(format!("Invalid input at: {}", s)))
-                    } else {
-                        Err(Error::InvalidFormat(format!("Parser failure: {:?}", e.code)))
-                    }
-                },
+    parse_with(s, &ParseOptions::default())
+}
+
+/// Parse a UCDF string and write it back in canonical form
+///
+/// Equal descriptors normalize to the same string, whatever the order of
+/// their keys and the quoting of their values.
+///
+/// # Examples
+///
+/// ```
+/// let s = ucdf::normalize("t=db.mysql;c.port=3306;c.host=\"db1\"").unwrap();
+/// assert_eq!(s, "t=db.mysql;c.host=db1;c.port=3306");
+/// ```
+pub fn normalize(s: &str) -> Result<String> {
+    parse(s).map(|ucdf| ucdf.to_canonical_string())
+}
+
+/// Parse a UCDF string from bytes, see [`Parser::parse_bytes`]
+pub fn parse_bytes(bytes: &[u8]) -> Result<UCDF> {
+    Parser::new().parse_bytes(bytes)
+}
+
+/// Parse many UCDF strings, see [`Parser::parse_many`]
+///
+/// # Examples
+///
+/// ```
+/// let results = ucdf::parse_many(&["t=db.mysql;c.host=db1", "c.host=db2"]);
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
+/// ```
+pub fn parse_many<S: AsRef<str> + Sync>(inputs: &[S]) -> Vec<Result<UCDF>> {
+    Parser::new().parse_many(inputs)
+}
+
+// Parse a UCDF string with the given options
+fn parse_with(s: &str, options: &ParseOptions) -> Result<UCDF> {
+    let mut sections = Vec::new();
+    let source_type = parse_sections(s, options, &mut sections)?;
+    let mut ucdf = UCDF::with_source_type(source_type);
+    apply_sections(&mut ucdf, sections.drain(..), None, options);
+    Ok(ucdf)
+}
+
+// Parse the sections of a UCDF string into `sections`, returning the source type
+fn parse_sections(
+    s: &str,
+    options: &ParseOptions,
+    sections: &mut Vec<Section>,
+) -> Result<SourceType> {
+    let (start, s) = trim_input(s);
+    let s = strip_str_comments(s);
+    ucdf_parser(&s, options, sections)
+        .map(|(_, source_type)| source_type)
+        .map_err(|err| match nom_error(err) {
+            // Offsets are reported in the untrimmed input
+            Error::TrailingInput { offset, text } => Error::TrailingInput {
+                offset: start + offset,
+                text,
+            },
+            e => e,
+        })
+}
+
+// Strip a byte order mark and surrounding whitespace, such as the `\r\n` of
+// a line read from a file, returning the offset of the remaining text
+pub(crate) fn trim_input(s: &str) -> (usize, &str) {
+    let trimmed = s.strip_prefix('\u{feff}').unwrap_or(s).trim_start();
+    (s.len() - trimmed.len(), trimmed.trim_end())
+}
+
+// Like `trim_input` on bytes, stripping ASCII whitespace only
+fn trim_bytes(bytes: &[u8]) -> (usize, &[u8]) {
+    let trimmed = bytes
+        .strip_prefix(b"\xef\xbb\xbf")
+        .unwrap_or(bytes)
+        .trim_ascii_start();
+    (bytes.len() - trimmed.len(), trimmed.trim_ascii_end())
+}
+
+// Convert a parser error into a UCDF error
+//
+// Each failure of the section parsers carries the UCDF error that caused it.
+// Other errors come from nom combinators failing on their own and are only
+// described by their error kind.
+fn nom_error(err: NomErr<NomError>) -> Error {
+    match err {
+        NomErr::Failure(NomError {
+            cause: Some(cause), ..
+        }) => cause,
+        NomErr::Incomplete(_) => Error::InvalidFormat("Incomplete input".to_string()),
+        NomErr::Error(e) | NomErr::Failure(e) => {
+            Error::InvalidFormat(format!("Parser error: {:?}", e.code))
+        }
+    }
+}
+
+// Like `parse_sections` on a byte buffer, checking each section is UTF-8 only
+// when the parser reaches it
+fn parse_byte_sections(
+    bytes: &[u8],
+    options: &ParseOptions,
+    sections: &mut Vec<Section>,
+) -> Result<SourceType> {
+    let mut source_type = None;
+    let (offset, bytes) = trim_bytes(bytes);
+    let stripped = strip_comments(bytes);
+    let bytes = stripped.as_ref();
+    for (index, span) in (1..).zip(section_bounds(bytes)) {
+        let start = offset + span.start;
+        let s = std::str::from_utf8(&bytes[span]).map_err(|e| {
+            Error::InvalidFormat(format!("invalid UTF-8 at byte {}", start + e.valid_up_to()))
+                .context(format!("in section {}", index))
+        })?;
+        if s.is_empty() {
+            continue;
+        }
+        let rest = match section_parser(s, options) {
+            Ok((rest, Section::Type(parsed))) => {
+                if options.keep_sections {
+                    sections.push(Section::Type(parsed.clone()));
+                }
+                set_source_type(&mut source_type, parsed, options)
+                    .map_err(|e| nom_error(e.map(|e| e.in_section(index, s))))?;
+                rest
+            }
+            Ok((rest, section)) => {
+                sections.push(section);
+                rest
             }
+            Err(NomErr::Error(_)) => s,
+            Err(e) => return Err(nom_error(e.map(|e| e.in_section(index, s)))),
+        };
+        // Like `ucdf_parser`, fail on a section that can't be read or on text
+        // following a quoted value
+        if !rest.is_empty() {
+            return Err(trailing_input(start + s.len() - rest.len(), rest));
         }
     }
+    source_type.ok_or(Error::MissingTypeSection)
 }
 
-// Primary parser for UCDF strings
-fn ucdf_parser(input: &str) -> IResult<&str, UCDF> {
-    let (input, sections) = separated_list0(char(';'), section_parser)(input)?;
+// Primary parser for UCDF strings, pushing `;`-separated sections other than
+// the type section, which is returned. The whole input must be consumed.
+fn ucdf_parser<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+    sections: &mut Vec<Section>,
+) -> PResult<'a, SourceType> {
+    let mut source_type = None;
+    let rest = section_list(input, options, sections, &mut source_type)?.0;
+    if !rest.is_empty() {
+        let e = trailing_input(input.len() - rest.len(), rest);
+        return Err(NomErr::Failure(NomError::with_cause(e)));
+    }
 
-    // Extract and validate type section
-    let type_section = sections.iter().find_map(|section| {
-        if let Section::Type(source_type) = section {
-            Some(source_type.clone())
-        } else {
-            None
-        }
-    });
+    match source_type {
+        Some(source_type) => Ok((rest, source_type)),
+        None => Err(NomErr::Failure(NomError::with_cause(
+            Error::MissingTypeSection,
+        ))),
+    }
+}
 
-    let source_type = match type_section {
-        Some(source_type) => source_type,
-        None => return Err(NomErr::Error(NomError::new(input, ErrorKind::Tag))),
-    };
+// Like `separated_list0(char(';'), section_parser)`, without collecting a new
+// Vec. The type section is moved into `source_type` and empty sections are
+// skipped. Stops at the first section that can't be read.
+fn section_list<'a>(
+    mut input: &'a str,
+    options: &ParseOptions,
+    sections: &mut Vec<Section>,
+    source_type: &mut Option<SourceType>,
+) -> PResult<'a, ()> {
+    for index in 1.. {
+        if !input.is_empty() && !input.starts_with(';') {
+            match section_parser(input, options) {
+                Ok((after, Section::Type(parsed))) => {
+                    if options.keep_sections {
+                        sections.push(Section::Type(parsed.clone()));
+                    }
+                    set_source_type(source_type, parsed, options)
+                        .map_err(|e| e.map(|e| e.in_section(index, input)))?;
+                    input = after;
+                }
+                Ok((after, section)) => {
+                    sections.push(section);
+                    input = after;
+                }
+                Err(NomErr::Error(_)) => break,
+                Err(e) => return Err(e.map(|e| e.in_section(index, input))),
+            }
+        }
+        match input.strip_prefix(';') {
+            Some(after) => input = after,
+            None => break,
+        }
+    }
+    Ok((input, ()))
+}
 
-    // Create base UCDF with type
-    let mut ucdf = UCDF::builder().source_type(source_type).build();
+// Keep the first type section in `source_type`. Later ones fail unless the
+// parser allows them, in which case they are dropped.
+fn set_source_type(
+    source_type: &mut Option<SourceType>,
+    parsed: SourceType,
+    options: &ParseOptions,
+) -> std::result::Result<(), NomErr<NomError>> {
+    match source_type {
+        None => *source_type = Some(parsed),
+        Some(_) if options.allow_duplicate_type => {}
+        Some(first) => {
+            let e = Error::DuplicateTypeSection(format!("{} after {}", parsed, first));
+            return Err(NomErr::Failure(NomError::with_cause(e)));
+        }
+    }
+    Ok(())
+}
 
-    // Process all sections
+// Move parsed sections into a descriptor, interning keys through `keys` if
+// given and keeping a copy of each section if the options ask for it
+fn apply_sections(
+    ucdf: &mut UCDF,
+    sections: impl Iterator<Item = Section>,
+    mut keys: Option<&mut KeyTable>,
+    options: &ParseOptions,
+) {
+    let mut intern = |key: String| match keys.as_deref_mut() {
+        Some(keys) => keys.get(&key),
+        None => Key::from(key),
+    };
+    ucdf.sections.clear();
     for section in sections {
+        if options.keep_sections {
+            ucdf.sections.push(section.clone());
+        }
         match section {
-            Section::Type(_) => {} // Already handled
+            // Returned by `ucdf_parser`, and only kept sections are unknown
+            Section::Type(_) | Section::Unknown(_, _) => {}
             Section::Connection(key, value) => {
-                ucdf.add_connection(&key, &value);
+                ucdf.add_connection(intern(key), value);
+            }
+            Section::Structure(key, structure) => {
+                // Stored under the section key, which may differ from the
+                // default key of the data kind when produced by a hook
+                ucdf.structure.insert(key, structure);
             }
-            Section::Structure(key, structure) => match structure {
-                StructureData::Fields(fields) => {
-                    ucdf.add_fields(fields);
-                }
-                StructureData::Endpoints(endpoints) => {
-                    ucdf.add_endpoints(endpoints);
-                }
-                StructureData::Format(format) => {
-                    ucdf.add_format(&format);
-                }
-                StructureData::Custom(_, value) => {
-                    ucdf.add_custom_structure(&key, &value);
-                }
-            },
             Section::Access(access_mode) => {
                 ucdf.set_access_mode(access_mode);
             }
+            Section::SectionAccess(key, access_mode) => {
+                ucdf.set_section_access(key, access_mode);
+            }
             Section::Meta(key, value) => {
-                ucdf.add_metadata(&key, &value);
+                ucdf.add_metadata(intern(key), value);
+            }
+            Section::EncryptedConnection(ciphertext) => {
+                ucdf.encrypted_connection = Some(ciphertext);
+            }
+            Section::Include(source) => {
+                ucdf.includes.push(source);
+            }
+            Section::Cleared(path) => {
+                // Only produced for valid keys by `section_parser`
+                let _ = ucdf.clear_path(&path);
             }
         }
     }
+}
 
-    Ok((input, ucdf))
+// Keys already seen by a `Parser`, to intern without locking the global table
+#[derive(Debug, Clone, Default)]
+struct KeyTable(HashSet<Key>);
+
+impl KeyTable {
+    // Number of keys kept per parser
+    const CAPACITY: usize = 1024;
+
+    fn get(&mut self, key: &str) -> Key {
+        if let Some(key) = self.0.get(key) {
+            return key.clone();
+        }
+        let key = Key::new(key);
+        if self.0.len() < Self::CAPACITY {
+            self.0.insert(key.clone());
+        }
+        key
+    }
+}
+
+// Parse the value of a built-in structure section `s.<key>`
+pub(crate) fn structure_value(key: &str, value: &str) -> Result<StructureData> {
+    let result = match key {
+        "fields" => parse_fields(value).map(|(_, fields)| StructureData::Fields(fields)),
+        "endpoints" => {
+            parse_endpoints(value).map(|(_, endpoints)| StructureData::Endpoints(endpoints))
+        }
+        "format" => Ok(StructureData::Format(value.to_string())),
+        _ => Ok(StructureData::Custom(value.to_string())),
+    };
+    result.map_err(|e| match e {
+        NomErr::Failure(NomError {
+            cause: Some(cause), ..
+        }) => cause,
+        _ => Error::InvalidFormat(format!("invalid s.{} value: {}", key, value)),
+    })
 }
 
 // Parse a section: key=value
-fn section_parser(input: &str) -> IResult<&str, Section> {
-// Parse key=value pair, returning error if format is invalid
-let (input, (key, value)) = separated_pair(
-    key_parser,
-    char('='),
-    alt((quoted_value_parser, simple_value_parser)),
-)(input)?;
-    
-// Check if the key is non-empty
-if key.is_empty() {
-    return Err(NomErr::Error(NomError::new(input, ErrorKind::Tag)));
-}
-
-let result = if key == "t" {
-        // Type section
-        match SourceType::from_str(value) {
-            Ok(source_type) => Section::Type(source_type),
-            Err(_) => return Err(NomErr::Error(NomError::new(input, ErrorKind::Tag))),
-        }
-    } else if let Some(conn_key) = key.strip_prefix("c.") {
-        // Connection section
-        Section::Connection(conn_key.to_string(), value.to_string())
-    } else if let Some(struct_key) = key.strip_prefix("s.") {
-        // Structure section
-        match struct_key {
-            "fields" => {
-                let (_, fields) = parse_fields(value)?;
-                Section::Structure(struct_key.to_string(), StructureData::Fields(fields))
+fn section_parser<'a>(input: &'a str, options: &ParseOptions) -> PResult<'a, Section> {
+    // Parse key=value pair, returning error if format is invalid
+    let (rest, (key, value)) = split_section(input)?;
+    let failure = |e| NomErr::Failure(NomError::with_cause(e));
+    let unknown = |prefix: &str| failure(Error::UnknownSectionPrefix(prefix.to_string()));
+
+    // `c.key=` without a value, as opposed to `c.key=""`
+    let bare = value.is_empty() && !input[key.len() + 1..].starts_with('"');
+    let key = options.section_key(key).map_err(failure)?;
+
+    let Some(key_path) = KeyPath::parse(&key) else {
+        if options.keep_sections {
+            return Ok((rest, Section::Unknown(key.into_owned(), value.into_owned())));
+        }
+        return Err(unknown(&key));
+    };
+    let result = match key_path {
+        KeyPath::Connection(name) | KeyPath::Meta(name) if bare && !name.is_empty() => {
+            Section::Cleared(key.to_string())
+        }
+        KeyPath::Type => Section::Type(SourceType::from_str(&value).map_err(failure)?),
+        KeyPath::Include => Section::Include(value.into_owned()),
+        KeyPath::EncryptedConnection => Section::EncryptedConnection(value.into_owned()),
+        KeyPath::Connection(key) => Section::Connection(key.to_string(), value.into_owned()),
+        KeyPath::Structure(key) => {
+            // Registered parsers take precedence
+            let data = match options.structure_parsers.get(key) {
+                Some(hook) => hook.parse(&value).map_err(failure)?,
+                None if options.lazy_structure && matches!(key, "fields" | "endpoints") => {
+                    StructureData::lazy(key, value)
+                }
+                None => structure_value(key, &value).map_err(failure)?,
+            };
+            if options.strict_types {
+                check_field_types(&data, &options.allowed_types).map_err(failure)?;
             }
-            "endpoints" => {
-                let (_, endpoints) = parse_endpoints(value)?;
-                Section::Structure(struct_key.to_string(), StructureData::Endpoints(endpoints))
+            Section::Structure(key.to_string(), data)
+        }
+        KeyPath::Access => Section::Access(AccessMode::from_str(&value).map_err(failure)?),
+        KeyPath::SectionAccess(key) => Section::SectionAccess(
+            key.to_string(),
+            AccessMode::from_str(&value).map_err(failure)?,
+        ),
+        KeyPath::Meta(key) => Section::Meta(key.to_string(), value.into_owned()),
+        KeyPath::Custom(..) if options.keep_sections => {
+            Section::Unknown(key.to_string(), value.into_owned())
+        }
+        KeyPath::Custom(prefix, _) => return Err(unknown(prefix)),
+    };
+
+    Ok((rest, result))
+}
+
+// Fail on fields whose type is neither a standard `DataType` nor named in
+// `allowed`, which holds type names without their parameters (`varchar` for
+// `varchar(255)`)
+fn check_field_types(data: &StructureData, allowed: &HashSet<String>) -> Result<()> {
+    fn is_known(dtype: &DataType, allowed: &HashSet<String>) -> bool {
+        match dtype {
+            DataType::Custom(name) => {
+                let base = name.split(['<', '(']).next().unwrap_or_default();
+                allowed.contains(base)
             }
-            "format" => Section::Structure(
-                struct_key.to_string(),
-                StructureData::Format(value.to_string()),
-            ),
-            _ => Section::Structure(
-                struct_key.to_string(),
-                StructureData::Custom(struct_key.to_string(), value.to_string()),
-            ),
+            DataType::Array(inner) => is_known(inner, allowed),
+            _ => true,
         }
-    } else if key == "a" {
-        // Access mode section
-        match AccessMode::from_str(value) {
-            Ok(access_mode) => Section::Access(access_mode),
-            Err(_) => return Err(NomErr::Failure(NomError::new(input, ErrorKind::Tag))),
-        }
-    } else if let Some(meta_key) = key.strip_prefix("m.") {
-        // Metadata section
-        Section::Meta(meta_key.to_string(), value.to_string())
-    } else {
-        return Err(NomErr::Error(NomError::new(input, ErrorKind::Tag)));
+    }
+
+    for field in data.as_fields().unwrap_or_default() {
+        if !is_known(&DataType::from_str(&field.dtype)?, allowed) {
+            return Err(Error::InvalidFieldFormat(format!(
+                "`{}`: unknown type `{}`",
+                field, field.dtype
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Split a `key=value` section, taking the memchr fast path without quotes.
+// Escapes in quoted values are decoded. Structure values may instead quote
+// some of their tokens, as in `s.fields="a:b":str`, and are then kept as
+// written.
+fn split_section(input: &str) -> PResult<'_, (&str, Cow<'_, str>)> {
+    if let Some(split) = scan_section(input) {
+        return split.map(|(input, (key, value))| (input, (key, Cow::Borrowed(value))));
+    }
+    let (input, key) = terminated(key_parser, char('='))(input)?;
+    let (input, value) = match KeyPath::parse(key) {
+        Some(KeyPath::Structure(_)) => alt((
+            map(
+                terminated(quoted_value_parser, peek(alt((eof, tag(";"))))),
+                unescape,
+            ),
+            map(quoted_tokens_parser, Cow::Borrowed),
+        ))(input)?,
+        _ => alt((
+            map(quoted_value_parser, unescape),
+            map(simple_value_parser, Cow::Borrowed),
+        ))(input)?,
     };
+    Ok((input, (key, value)))
+}
 
-    Ok((input, result))
+// Split the next section off `input` as `(key, value, rest)`, or `None` where
+// the parser stops reading sections
+#[cfg(feature = "with-bumpalo")]
+pub(crate) fn next_section(input: &str) -> Option<(&str, Cow<'_, str>, &str)> {
+    let (rest, (key, value)) = split_section(input).ok()?;
+    Some((key, value, rest))
+}
+
+// Split an unquoted `key=value` section, or return `None` if it contains a quote
+//
+// Matches the nom parsers below on such sections: the key runs to the first
+// `=` and must not be empty, the value runs to the next `;`.
+fn scan_section(input: &str) -> Option<PResult<'_, (&str, &str)>> {
+    let bytes = input.as_bytes();
+    let end = memchr(b';', bytes).unwrap_or(bytes.len());
+    if memchr(b'"', &bytes[..end]).is_some() {
+        return None;
+    }
+    Some(match memchr(b'=', &bytes[..end]) {
+        Some(eq) if eq > 0 => Ok((&input[end..], (&input[..eq], &input[eq + 1..end]))),
+        Some(_) => Err(NomErr::Error(NomError::new(ErrorKind::TakeWhile1))),
+        None => Err(NomErr::Error(NomError::new(ErrorKind::Char))),
+    })
 }
 
 // Key parser: any character except '=' and ';'
-fn key_parser(input: &str) -> IResult<&str, &str> {
+fn key_parser(input: &str) -> PResult<'_, &str> {
     take_while1(|c| c != '=' && c != ';')(input)
 }
 
 // Simple value parser: any character except ';'
-fn simple_value_parser(input: &str) -> IResult<&str, &str> {
+fn simple_value_parser(input: &str) -> PResult<'_, &str> {
     take_till(|c| c == ';')(input)
 }
 
-// Parse a quoted string value
-fn quoted_value_parser(input: &str) -> IResult<&str, &str> {
+// Value parser running to the first ';' outside quoted tokens, failing on an
+// unterminated quote
+fn quoted_tokens_parser(input: &str) -> PResult<'_, &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => return Ok((&input[i..], &input[..i])),
+            _ => {}
+        }
+    }
+    if quoted {
+        return Err(NomErr::Error(NomError::new(ErrorKind::Char)));
+    }
+    Ok(("", input))
+}
+
+// Parse a quoted string value, which may be empty
+fn quoted_value_parser(input: &str) -> PResult<'_, &str> {
     delimited(
         char('"'),
-        escaped(none_of("\\\""), '\\', one_of("\"\\nrt")),
+        map(
+            opt(escaped(none_of("\\\""), '\\', one_of("\"\\nrt"))),
+            Option::unwrap_or_default,
+        ),
         char('"'),
     )(input)
 }
 
-// Helper function to parse fields
-fn parse_fields(input: &str) -> IResult<&str, Vec<Field>> {
-    separated_list0(
-        char::<&str, nom::error::Error<&str>>(','),
-        map_res(
-            separated_pair(
-                take_while1(|c| c != ':' && c != ',' && c != ';'),
-                char::<&str, nom::error::Error<&str>>(':'),
-                take_while1(|c| c != ',' && c != ';'),
-            ),
-            |(name, dtype)| -> Result<Field> {
-                Ok(Field::builder()
-                    .name(name.to_string())
-                    .dtype(dtype.to_string())
-                    .build())
-            },
-        ),
-    )(input)
+// Decode the escapes of a quoted value written by `Quoted`
+fn unescape(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(c) => value.push(c),
+            None => value.push('\\'),
+        }
+    }
+    Cow::Owned(value)
 }
 
-// Helper function to parse endpoints
-fn parse_endpoints(input: &str) -> IResult<&str, Vec<Endpoint>> {
-    separated_list0(
-        char::<&str, nom::error::Error<&str>>(','),
-        map_res(
-            separated_pair(
-                take_while1(|c| c != ':' && c != ',' && c != ';'),
-                char::<&str, nom::error::Error<&str>>(':'),
-                take_while1(|c| c != ',' && c != ';'),
-            ),
-            |(path, method)| -> Result<Endpoint> {
-                Ok(Endpoint::builder()
-                    .path(path.to_string())
-                    .method(method.to_string())
-                    .build())
-            },
-        ),
-    )(input)
+// Split a quoted token off the start of a list item, as in `"a:b":str`,
+// returning the decoded token and the text after it
+pub(crate) fn split_quoted(s: &str) -> Option<(Cow<'_, str>, &str)> {
+    let (rest, token) = quoted_value_parser(s).ok()?;
+    Some((unescape(token), rest))
+}
+
+// Strip the quotes of a value taken from a section split by hand, decoding
+// its escapes
+pub(crate) fn unquote(value: &str) -> Cow<'_, str> {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(quoted) => unescape(quoted),
+        None => Cow::Borrowed(value),
+    }
+}
+
+// Helper function to parse fields, sharing the field grammar with `Field::from_str`
+fn parse_fields(input: &str) -> PResult<'_, Vec<Field>> {
+    let fields = split_list(input)
+        .into_iter()
+        .map(Field::from_str)
+        .collect::<Result<Vec<Field>>>()
+        .map_err(|e| NomErr::Failure(NomError::with_cause(e)))?;
+    Ok(("", fields))
+}
+
+// Helper function to parse endpoints, sharing the endpoint grammar with
+// `Endpoint::from_str`
+fn parse_endpoints(input: &str) -> PResult<'_, Vec<Endpoint>> {
+    let endpoints = split_list(input)
+        .into_iter()
+        .map(Endpoint::from_str)
+        .collect::<Result<Vec<Endpoint>>>()
+        .map_err(|e| NomErr::Failure(NomError::with_cause(e)))?;
+    Ok(("", endpoints))
+}
+
+// Split a UCDF string into `key=value` sections, keeping `;` inside quoted values
+pub(crate) fn split_sections(s: &str) -> Vec<&str> {
+    section_spans(s).into_iter().map(|span| &s[span]).collect()
+}
+
+/// Byte ranges of the `key=value` sections of a UCDF string
+///
+/// Separators inside quoted values are skipped and empty sections are left
+/// out, so the ranges line up with the sections seen by the parser.
+///
+/// # Examples
+///
+/// ```
+/// let s = "t=file.csv;m.desc=\"a;b\"";
+/// let sections: Vec<&str> = ucdf::section_spans(s).into_iter().map(|span| &s[span]).collect();
+/// assert_eq!(sections, ["t=file.csv", "m.desc=\"a;b\""]);
+/// ```
+pub fn section_spans(s: &str) -> Vec<Range<usize>> {
+    let mut spans = section_bounds(s.as_bytes());
+    spans.retain(|span| !span.is_empty());
+    spans
+}
+
+// Byte ranges between the `;` separators outside quoted values, empty ones
+// included
+fn section_bounds(bytes: &[u8]) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    // Position of a character escaped by a backslash, skipped when reached
+    let mut escaped = None;
+    for i in memchr3_iter(b';', b'"', b'\\', bytes) {
+        if escaped == Some(i) {
+            continue;
+        }
+        match bytes[i] {
+            b'\\' if quoted => escaped = Some(i + 1),
+            b'"' => quoted = !quoted,
+            b';' if !quoted => {
+                spans.push(start..i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    spans.push(start..bytes.len());
+    spans
+}
+
+// Kind of a span of descriptor text, see `layout_spans`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpanKind {
+    Section,
+    Comment,
+    Space,
+}
+
+// Split descriptor text into sections, `#` comments, and the separators and
+// whitespace between them, covering all of `bytes`
+//
+// Outside quoted values, a section ends at a `;` or a line break. A `#`
+// starts a comment running to the end of the line when it begins a line, or
+// when it begins a section or follows whitespace in a value and no further
+// section follows on its line. A `#` inside a value only starts a comment in
+// text written over several lines, so single-line descriptors keep the
+// meaning they had before comments were supported. Any other `#` is part of
+// the section, and a section that starts with one fails to parse.
+pub(crate) fn layout_spans(bytes: &[u8]) -> Vec<(SpanKind, Range<usize>)> {
+    let is_space = |b: u8| matches!(b, b';' | b' ' | b'\t' | b'\r' | b'\n');
+    let multiline = memchr(b'\n', bytes).is_some();
+    let mut spans = Vec::new();
+    let mut line_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        if is_space(bytes[i]) {
+            while i < bytes.len() && is_space(bytes[i]) {
+                if bytes[i] == b'\n' {
+                    line_start = true;
+                } else if bytes[i] == b';' {
+                    line_start = false;
+                }
+                i += 1;
+            }
+            spans.push((SpanKind::Space, start..i));
+        } else if bytes[i] == b'#' && (line_start || ends_line(&bytes[i..])) {
+            i = memchr(b'\n', &bytes[i..]).map_or(bytes.len(), |n| i + n);
+            if bytes[i - 1] == b'\r' {
+                i -= 1;
+            }
+            spans.push((SpanKind::Comment, start..i));
+        } else {
+            line_start = false;
+            let mut quoted = false;
+            let mut escaped = false;
+            // End of the section without trailing whitespace
+            let mut end = i;
+            while i < bytes.len() {
+                let b = bytes[i];
+                match b {
+                    _ if escaped => escaped = false,
+                    b'\\' if quoted => escaped = true,
+                    b'"' => quoted = !quoted,
+                    b';' | b'\n' if !quoted => break,
+                    b'#' if !quoted
+                        && multiline
+                        && matches!(bytes[i - 1], b' ' | b'\t')
+                        && ends_line(&bytes[i..]) =>
+                    {
+                        break
+                    }
+                    _ => {}
+                }
+                i += 1;
+                if quoted || !matches!(b, b' ' | b'\t' | b'\r') {
+                    end = i;
+                }
+            }
+            spans.push((SpanKind::Section, start..end));
+            i = end;
+        }
+    }
+    spans
+}
+
+// Check that no section follows on the line starting `bytes`, i.e. that it
+// has no `;` outside quotes
+fn ends_line(bytes: &[u8]) -> bool {
+    let line = memchr(b'\n', bytes).map_or(bytes, |n| &bytes[..n]);
+    let mut quoted = false;
+    let mut escaped = false;
+    for &b in line {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' if quoted => escaped = true,
+            b'"' => quoted = !quoted,
+            b';' if !quoted => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+// Remove `#` comments and join the lines of a trimmed descriptor written
+// over several lines with `;`, borrowing the input if it has neither. Error
+// offsets only count the input text if it is borrowed.
+fn strip_comments(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if memchr::memchr2(b'#', b'\n', bytes).is_none() {
+        return Cow::Borrowed(bytes);
+    }
+    let mut stripped = Vec::with_capacity(bytes.len());
+    for (kind, span) in layout_spans(bytes) {
+        if kind == SpanKind::Section {
+            if !stripped.is_empty() {
+                stripped.push(b';');
+            }
+            stripped.extend_from_slice(&bytes[span]);
+        }
+    }
+    if stripped == bytes {
+        return Cow::Borrowed(bytes);
+    }
+    Cow::Owned(stripped)
+}
+
+// Like `strip_comments` on a string
+pub(crate) fn strip_str_comments(s: &str) -> Cow<'_, str> {
+    match strip_comments(s.as_bytes()) {
+        Cow::Borrowed(_) => Cow::Borrowed(s),
+        // Sections are split at ASCII characters
+        Cow::Owned(bytes) => Cow::Owned(String::from_utf8(bytes).expect("sections are UTF-8")),
+    }
 }
 
 /// Parser for UCDF strings
-pub struct Parser;
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{Field, Parser, StructureData};
+///
+/// // Key columns share the field grammar of `s.fields`
+/// let parser = Parser::new().structure_parser("keys", |value: &str| {
+///     let fields = value.split(',').map(str::parse).collect::<ucdf::Result<Vec<Field>>>()?;
+///     Ok(StructureData::Fields(fields))
+/// });
+/// let ucdf = parser.parse("t=db.postgresql;c.host=db1;s.keys=id:int").unwrap();
+/// assert!(matches!(ucdf.structure.get("keys"), Some(StructureData::Fields(f)) if f[0].name == "id"));
+/// ```
+#[derive(Clone, Default)]
+pub struct Parser {
+    check_source_types: bool,
+    options: ParseOptions,
+    /// Scratch buffer for the sections of the string being parsed
+    sections: Vec<Section>,
+    keys: KeyTable,
+}
 
 impl Parser {
     /// Create a new Parser
     pub fn new() -> Self {
-        Parser
+        Parser::default()
+    }
+
+    /// Warn about source types missing from the known source type registry
+    pub fn check_source_types(mut self, enabled: bool) -> Self {
+        self.check_source_types = enabled;
+        self
+    }
+
+    /// Register a parser for structure sections `s.<key>`
+    ///
+    /// Registered parsers take precedence over the built-in handling of
+    /// `fields`, `endpoints` and `format`.
+    pub fn structure_parser(
+        mut self,
+        key: impl Into<String>,
+        parser: impl StructureParser + 'static,
+    ) -> Self {
+        self.options
+            .structure_parsers
+            .insert(key.into(), Arc::new(parser));
+        self
+    }
+
+    /// Defer parsing `s.fields` and `s.endpoints` values until first access
+    ///
+    /// The values are kept as written in a [`StructureData::Lazy`] and parsed
+    /// by the `as_*` accessors, so consumers reading only connection
+    /// parameters skip the work. Malformed values are then reported by
+    /// [`StructureData::parsed`] rather than by the parser.
+    pub fn lazy_structure(mut self, enabled: bool) -> Self {
+        self.options.lazy_structure = enabled;
+        self
+    }
+
+    /// Reject field types other than the standard [`DataType`]s
+    ///
+    /// Without this, an unknown type such as a misspelled `strr` is accepted
+    /// as [`DataType::Custom`]. Types registered with
+    /// [`allow_type`](Parser::allow_type) are accepted as well. Lazy
+    /// structure values are parsed to check their types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Parser;
+    ///
+    /// let parser = Parser::new().strict_types(true).allow_type("geometry");
+    /// assert!(parser.parse("t=db.postgis;s.fields=id:int,area:geometry").is_ok());
+    /// assert!(parser.parse("t=db.postgis;s.fields=id:int,name:strr").is_err());
+    /// ```
+    pub fn strict_types(mut self, enabled: bool) -> Self {
+        self.options.strict_types = enabled;
+        self
+    }
+
+    /// Accept a custom field type in [`strict_types`](Parser::strict_types)
+    /// mode, named without its parameters (`map` for `map<str,int>`, `varchar`
+    /// for `varchar(255)`)
+    pub fn allow_type(mut self, name: impl Into<String>) -> Self {
+        self.options.allowed_types.insert(name.into());
+        self
+    }
+
+    /// Restrict the characters of section keys
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{KeyPolicy, Parser};
+    ///
+    /// let parser = Parser::new().key_policy(KeyPolicy::Ascii);
+    /// assert!(parser.parse("t=db.mysql;c.db_host=db1").is_ok());
+    /// let err = parser.parse("t=db.mysql;c.db\u{2011}host=db1").unwrap_err();
+    /// assert_eq!(
+    ///     err.root_cause().to_string(),
+    ///     "Invalid key: unexpected '‑' (U+2011) in `db‑host`, \
+    ///      keys may only contain ASCII letters, digits, `_`, `-` and `.`"
+    /// );
+    /// ```
+    pub fn key_policy(mut self, policy: KeyPolicy) -> Self {
+        self.options.key_policy = policy;
+        self
+    }
+
+    /// Limit the number of dot-separated segments in section keys
+    ///
+    /// The prefix isn't counted, so `c.pool.max` has two segments. Empty
+    /// segments, as in `c..host` or `m.owner.`, are always rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Parser;
+    ///
+    /// let parser = Parser::new().max_key_depth(2);
+    /// assert!(parser.parse("t=db.mysql;c.pool.max=10").is_ok());
+    /// let err = parser.parse("t=db.mysql;c.pool.max.idle=10").unwrap_err();
+    /// assert_eq!(
+    ///     err.root_cause().to_string(),
+    ///     "Invalid key: `c.pool.max.idle` has 3 segments, at most 2 allowed"
+    /// );
+    /// ```
+    pub fn max_key_depth(mut self, depth: usize) -> Self {
+        self.options.max_key_depth = Some(depth);
+        self
+    }
+
+    /// Normalize section keys to Unicode NFC before they are checked and stored
+    ///
+    /// Keys that look the same but were written in different normalization
+    /// forms, such as a precomposed `é` and `e` followed by a combining
+    /// accent, then map to the same entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Parser;
+    ///
+    /// let parser = Parser::new().nfc_keys(true);
+    /// let ucdf = parser.parse("t=db.mysql;m.caf\u{e9}=a;m.cafe\u{301}=b").unwrap();
+    /// assert_eq!(ucdf.metadata.len(), 1);
+    /// assert_eq!(ucdf.get_path("m.caf\u{e9}").as_deref(), Some("b"));
+    /// ```
+    #[cfg(feature = "with-unicode")]
+    pub fn nfc_keys(mut self, enabled: bool) -> Self {
+        self.options.nfc_keys = enabled;
+        self
+    }
+
+    /// Keep the first type section instead of failing on later ones
+    ///
+    /// By default, a string with more than one `t=` section is rejected with
+    /// [`Error::DuplicateTypeSection`], even if the types are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{Error, Parser};
+    ///
+    /// let s = "t=file.csv;t=db.postgresql";
+    /// let err = Parser::new().parse(s).unwrap_err();
+    /// assert!(matches!(err.root_cause(), Error::DuplicateTypeSection(_)));
+    ///
+    /// let ucdf = Parser::new().allow_duplicate_type(true).parse(s).unwrap();
+    /// assert_eq!(ucdf.source_type.to_string(), "file.csv");
+    /// ```
+    pub fn allow_duplicate_type(mut self, enabled: bool) -> Self {
+        self.options.allow_duplicate_type = enabled;
+        self
+    }
+
+    /// Keep the sections of parsed strings as written, see [`UCDF::sections`]
+    ///
+    /// Besides the sections folded into the descriptor, this keeps every
+    /// type section and repeated key, and sections with an unknown prefix,
+    /// which are then kept as [`Section::Unknown`] instead of failing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{Parser, Section};
+    ///
+    /// let parser = Parser::new().keep_sections(true);
+    /// let ucdf = parser.parse("t=db.mysql;c.host=db1;c.host=db2;q.retry=3").unwrap();
+    /// assert_eq!(ucdf.get_path("c.host").as_deref(), Some("db2"));
+    /// let kept: Vec<_> = ucdf.sections().map(|section| (section.prefix(), section.key())).collect();
+    /// assert_eq!(
+    ///     kept,
+    ///     [("t", None), ("c", Some("host")), ("c", Some("host")), ("q", Some("retry"))]
+    /// );
+    /// assert_eq!(ucdf.sections().last(), Some(&Section::Unknown("q.retry".into(), "3".into())));
+    /// ```
+    pub fn keep_sections(mut self, enabled: bool) -> Self {
+        self.options.keep_sections = enabled;
+        self
     }
 
     /// Parse a UCDF string into a UCDF structure
+    ///
+    /// A leading byte order mark and whitespace around the descriptor, such
+    /// as the line ending of a line read from a file, are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Parser;
+    ///
+    /// let ucdf = Parser::new().parse("\u{feff}t=db.mysql;c.host=db1\r\n").unwrap();
+    /// assert_eq!(ucdf.to_string(), "t=db.mysql;c.host=db1");
+    /// ```
     pub fn parse(&self, s: &str) -> Result<UCDF> {
-        parse(s)
+        self.parse_with_warnings(s).map(|(ucdf, _)| ucdf)
+    }
+
+    /// Parse a UCDF string from bytes not known to be UTF-8
+    ///
+    /// Instead of checking the whole buffer upfront, each section is checked
+    /// when the parser reaches it. Invalid UTF-8 is reported with the
+    /// section number and the byte offset in `bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse_bytes;
+    ///
+    /// let ucdf = parse_bytes(b"t=file.csv;c.path=/data/users.csv").unwrap();
+    /// assert_eq!(ucdf.get_path("c.path").as_deref(), Some("/data/users.csv"));
+    ///
+    /// let err = parse_bytes(b"t=file.csv;m.desc=caf\xe9").unwrap_err();
+    /// assert_eq!(err.to_string(), "in section 2: Invalid format: invalid UTF-8 at byte 21");
+    /// ```
+    pub fn parse_bytes(&self, bytes: &[u8]) -> Result<UCDF> {
+        let mut sections = Vec::new();
+        let source_type = parse_byte_sections(bytes, &self.options, &mut sections)?;
+        let mut ucdf = UCDF::with_source_type(source_type);
+        apply_sections(&mut ucdf, sections.drain(..), None, &self.options);
+        Ok(ucdf)
+    }
+
+    /// Parse a UCDF string, collecting warnings for enabled checks
+    pub fn parse_with_warnings(&self, s: &str) -> Result<(UCDF, Vec<Warning>)> {
+        let ucdf = parse_with(s, &self.options)?;
+        let warnings = self.warnings(&ucdf);
+        Ok((ucdf, warnings))
+    }
+
+    /// Parse a UCDF string into an existing descriptor, reusing its allocations
+    ///
+    /// All sections of `ucdf` are replaced. The parser keeps its scratch
+    /// buffers and the keys it has seen between calls, so parsing many
+    /// strings with one parser and one descriptor allocates little. On error,
+    /// `ucdf` is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{parse, Parser};
+    ///
+    /// let mut parser = Parser::new();
+    /// let mut ucdf = parse("t=db.mysql;c.host=db1").unwrap();
+    /// for line in ["t=db.mysql;c.host=db2;c.port=3307", "t=db.mysql;c.host=db3"] {
+    ///     parser.parse_into(&mut ucdf, line).unwrap();
+    ///     println!("{}", ucdf.get_path("c.host").unwrap());
+    /// }
+    /// assert_eq!(ucdf.get_path("c.port"), None);
+    /// ```
+    pub fn parse_into(&mut self, ucdf: &mut UCDF, s: &str) -> Result<Vec<Warning>> {
+        self.sections.clear();
+        let source_type = parse_sections(s, &self.options, &mut self.sections)?;
+
+        ucdf.source_type = source_type;
+        ucdf.connection.0.clear();
+        ucdf.structure.0.clear();
+        ucdf.access_mode = None;
+        ucdf.section_access.clear();
+        ucdf.metadata.0.clear();
+        ucdf.encrypted_connection = None;
+        ucdf.includes.clear();
+        ucdf.extensions = Extensions::new();
+        ucdf.cleared.clear();
+        apply_sections(
+            ucdf,
+            self.sections.drain(..),
+            Some(&mut self.keys),
+            &self.options,
+        );
+        Ok(self.warnings(ucdf))
+    }
+
+    /// Parse many UCDF strings, in parallel with the `with-rayon` feature
+    ///
+    /// Results are in the order of `inputs`. Each worker reuses one scratch
+    /// buffer and key table, starting from the keys this parser has seen;
+    /// keys missing from it are interned in the global table shared by all
+    /// workers. Warnings of enabled checks are not reported.
+    pub fn parse_many<S: AsRef<str> + Sync>(&self, inputs: &[S]) -> Vec<Result<UCDF>> {
+        let options = &self.options;
+        let parse = |(sections, keys): &mut (Vec<Section>, KeyTable), s: &S| {
+            sections.clear();
+            let source_type = parse_sections(s.as_ref(), options, sections)?;
+            let mut ucdf = UCDF::with_source_type(source_type);
+            apply_sections(&mut ucdf, sections.drain(..), Some(keys), options);
+            Ok(ucdf)
+        };
+
+        #[cfg(feature = "with-rayon")]
+        {
+            use rayon::prelude::*;
+            inputs
+                .par_iter()
+                .map_init(|| (Vec::new(), self.keys.clone()), parse)
+                .collect()
+        }
+        #[cfg(not(feature = "with-rayon"))]
+        {
+            let mut scratch = (Vec::new(), self.keys.clone());
+            inputs.iter().map(|s| parse(&mut scratch, s)).collect()
+        }
+    }
+
+    fn warnings(&self, ucdf: &UCDF) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if self.check_source_types && !source_types::is_known(&ucdf.source_type) {
+            warnings.push(Warning::UnknownSourceType(ucdf.source_type.to_string()));
+        }
+        warnings
+    }
+}
+
+impl fmt::Debug for Parser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&String> = self.options.structure_parsers.keys().collect();
+        keys.sort();
+        f.debug_struct("Parser")
+            .field("check_source_types", &self.check_source_types)
+            .field("structure_parsers", &keys)
+            .field("lazy_structure", &self.options.lazy_structure)
+            .field("allow_duplicate_type", &self.options.allow_duplicate_type)
+            .field("strict_types", &self.options.strict_types)
+            .field("key_policy", &self.options.key_policy)
+            .field("max_key_depth", &self.options.max_key_depth)
+            .field("interned_keys", &self.keys.0.len())
+            .finish()
     }
 }
 
@@ -229,6 +1275,7 @@ impl Parser {
 mod tests {
     use super::*;
     use crate::sections::*;
+    use nom::sequence::separated_pair;
 
     #[test]
     fn test_parse_csv_file() {
@@ -241,7 +1288,7 @@ mod tests {
             ucdf.connection.get("path"),
             Some(&"/data/users.csv".to_string())
         );
-        assert_eq!(ucdf.access_mode, Some(AccessMode::Read));
+        assert_eq!(ucdf.access_mode, Some(AccessMode::READ));
         assert_eq!(ucdf.metadata.get("desc"), Some(&"User data".to_string()));
 
         if let Some(StructureData::Fields(fields)) = ucdf.structure.get("fields") {
@@ -265,7 +1312,7 @@ mod tests {
         assert_eq!(ucdf.connection.get("host"), Some(&"db.prod".to_string()));
         assert_eq!(ucdf.connection.get("user"), Some(&"readonly".to_string()));
         assert_eq!(ucdf.connection.get("db"), Some(&"sales".to_string()));
-        assert_eq!(ucdf.access_mode, Some(AccessMode::Read));
+        assert_eq!(ucdf.access_mode, Some(AccessMode::READ));
 
         if let Some(StructureData::Fields(fields)) = ucdf.structure.get("fields") {
             assert_eq!(fields.len(), 3);
@@ -293,6 +1340,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quoted_tokens() {
+        let s = "t=api.rest;s.fields=\"weird:name\":str,\"a,b\":int:pk,id:int;s.endpoints=\"/tags/a,b\":GET";
+        let ucdf = parse(s).unwrap();
+        let fields = ucdf.structure["fields"].as_fields().unwrap();
+        assert_eq!(fields[0].name, "weird:name");
+        assert_eq!(fields[1].name, "a,b");
+        assert_eq!(fields[1].modifiers, ["pk"]);
+        let endpoints = ucdf.structure["endpoints"].as_endpoints().unwrap();
+        assert_eq!(endpoints[0].path, "/tags/a,b");
+        assert_eq!(ucdf.to_string(), s);
+
+        assert!(parse("t=api.rest;s.fields=\"weird:str").is_err());
+        assert!(parse("t=api.rest;s.fields=we\"ird:str").is_err());
+    }
+
+    #[test]
+    fn test_endpoint_colons() {
+        let s = "t=api.rest;s.endpoints=/v1/things:batchGet:POST,https://x/y:GET";
+        let ucdf = parse(s).unwrap();
+        let endpoints = ucdf.structure["endpoints"].as_endpoints().unwrap();
+        assert_eq!(endpoints[0], Endpoint::new("/v1/things:batchGet", "POST"));
+        assert_eq!(endpoints[1], Endpoint::new("https://x/y", "GET"));
+        assert_eq!(ucdf.to_string(), s);
+        assert_eq!(
+            "\"https://x/y\":GET".parse::<Endpoint>().unwrap(),
+            endpoints[1]
+        );
+        assert!("/things".parse::<Endpoint>().is_err());
+        assert!("/things:".parse::<Endpoint>().is_err());
+    }
+
     #[test]
     fn test_empty_sections() {
         // Empty sections should be ignored
@@ -303,6 +1382,16 @@ mod tests {
         assert!(ucdf.connection.0.is_empty());
     }
 
+    #[test]
+    fn test_category_without_subtype() {
+        let ucdf = parse("t=custom;c.path=/a").unwrap();
+        assert_eq!(ucdf.source_type, SourceType::new("custom", None));
+        assert_eq!(
+            parse("t=db.mysql").unwrap().source_type,
+            SourceType::new("db", Some("mysql".into()))
+        );
+    }
+
     #[test]
     fn test_missing_type() {
         // Missing type section should return error
@@ -336,12 +1425,32 @@ mod tests {
         }
         
         // Check access mode
-        assert_eq!(ucdf.access_mode, Some(AccessMode::ReadWrite));
+        assert_eq!(ucdf.access_mode, Some(AccessMode::READ_WRITE));
         
         // Check metadata
         assert_eq!(ucdf.metadata.get("desc"), Some(&"Test database".to_string()));
     }
 
+    #[test]
+    fn test_order_preserved() {
+        let ucdf_str = "t=db.mysql;c.user=app;c.host=db1;c.port=3306;s.format=json;s.fields=id:int;a=r;m.team=data;m.env=prod";
+        let ucdf = parse(ucdf_str).unwrap();
+        assert_eq!(ucdf.to_string(), ucdf_str);
+        let keys: Vec<&str> = ucdf
+            .connection
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(keys, ["user", "host", "port"]);
+
+        let mut ucdf = ucdf;
+        ucdf.connection.remove("user");
+        assert_eq!(
+            ucdf.to_string(),
+            "t=db.mysql;c.host=db1;c.port=3306;s.format=json;s.fields=id:int;a=r;m.team=data;m.env=prod"
+        );
+    }
+
     #[test]
     fn test_special_characters() {
         let ucdf_str = "t=file.csv;c.path=\"/path/with spaces/and;special=chars.csv\";m.desc=\"Line 1\\nLine 2\"";
@@ -356,10 +1465,10 @@ mod tests {
             Some(&"/path/with spaces/and;special=chars.csv".to_string())
         );
         
-        // Check that escaped characters are handled correctly
+        // Check that escaped characters are decoded
         assert_eq!(
             ucdf.metadata.get("desc"),
-            Some(&"Line 1\\nLine 2".to_string())
+            Some(&"Line 1\nLine 2".to_string())
         );
     }
 
@@ -391,6 +1500,622 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extended_access_modes() {
+        let ucdf = parse("t=stream.kafka;a=ra").unwrap();
+        let mode = ucdf.access_mode.unwrap();
+        assert!(mode.can_read());
+        assert!(mode.can_append());
+        assert!(!mode.can_write());
+        assert_eq!(mode.to_string(), "ra");
+
+        let ucdf = parse("t=api.rest;a=x").unwrap();
+        assert_eq!(ucdf.access_mode, Some(AccessMode::EXECUTE));
+
+        let ucdf = parse("t=db.postgresql;a=wr+admin").unwrap();
+        let mode = ucdf.access_mode.unwrap();
+        assert_eq!(mode, AccessMode::READ_WRITE | AccessMode::ADMIN);
+        assert_eq!(mode.to_string(), "rw+admin");
+
+        assert_eq!(AccessMode::ADMIN.to_string(), "admin");
+        assert!(parse("t=file.csv;a=rr").is_err());
+        assert!(parse("t=file.csv;a=rw+").is_err());
+
+        assert_eq!(AccessMode::default().to_string(), "none");
+        assert_eq!(AccessMode::from_str("none").unwrap(), AccessMode::default());
+        assert!(AccessMode::from_str("r+none").is_err());
+
+        #[cfg(feature = "with-json")]
+        for (json, mode) in [
+            ("\"Read\"", AccessMode::READ),
+            ("\"Write\"", AccessMode::WRITE),
+            ("\"ReadWrite\"", AccessMode::READ_WRITE),
+            ("\"ra\"", AccessMode::READ | AccessMode::APPEND),
+            ("\"none\"", AccessMode::empty()),
+        ] {
+            assert_eq!(serde_json::from_str::<AccessMode>(json).unwrap(), mode);
+        }
+    }
+
+    #[test]
+    fn test_unknown_source_type_warning() {
+        let parser = Parser::new().check_source_types(true);
+
+        let (_, warnings) = parser.parse_with_warnings("t=db.postgresql").unwrap();
+        assert!(warnings.is_empty());
+
+        let (_, warnings) = parser.parse_with_warnings("t=db.postgress").unwrap();
+        assert_eq!(
+            warnings,
+            vec![Warning::UnknownSourceType("db.postgress".to_string())]
+        );
+
+        // The check is opt-in
+        let (_, warnings) = Parser::new().parse_with_warnings("t=db.postgress").unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_into() {
+        let mut parser = Parser::new().check_source_types(true);
+        let mut ucdf = parse("t=db.mysql;c.host=db1;s.fields=id:int;a=rw;m.owner=data").unwrap();
+
+        let warnings = parser
+            .parse_into(&mut ucdf, "t=db.vitess;c.host=db2;m.env=prod")
+            .unwrap();
+        assert_eq!(ucdf, parse("t=db.vitess;c.host=db2;m.env=prod").unwrap());
+        assert_eq!(warnings.len(), 1);
+
+        assert!(parser.parse_into(&mut ucdf, "c.host=db3").is_err());
+        assert_eq!(ucdf.get_path("c.host").as_deref(), Some("db2"));
+        assert!(format!("{:?}", parser).contains("interned_keys: 2"));
+
+        let mut ucdf = parse("t=db.mysql;c.host=db1;a=r;a.fields=rw").unwrap();
+        parser
+            .parse_into(&mut ucdf, "t=db.mysql;c.host=db1")
+            .unwrap();
+        assert_eq!(ucdf.to_string(), "t=db.mysql;c.host=db1");
+        assert!(ucdf.section_access.is_empty());
+
+        let mut ucdf = parse("t=db.mysql;c.password=").unwrap();
+        parser
+            .parse_into(&mut ucdf, "t=db.mysql;c.password=\"\"")
+            .unwrap();
+        assert_eq!(ucdf.to_string(), "t=db.mysql;c.password=\"\"");
+        assert!(matches!(
+            ucdf.get_raw("c.password"),
+            Some(RawValue::Value(value)) if value.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_type_section_moved() {
+        let mut sections = Vec::new();
+        let options = ParseOptions {
+            allow_duplicate_type: true,
+            ..Default::default()
+        };
+        let source_type = parse_sections(
+            "c.host=db1;t=db.mysql;t=db.postgresql;a=r",
+            &options,
+            &mut sections,
+        )
+        .unwrap();
+        assert_eq!(source_type.to_string(), "db.mysql");
+        assert_eq!(sections.len(), 2);
+        assert!(!sections.iter().any(|s| matches!(s, Section::Type(_))));
+    }
+
+    #[test]
+    fn test_trailing_input() {
+        let err = parse("t=file.csv;garbage here").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::TrailingInput { offset: 11, ref text } if text == "garbage here"
+        ));
+        assert_eq!(
+            parse("t=file.csv;m.desc=\"a\"b").unwrap_err().to_string(),
+            "Trailing input at byte 21: `b`"
+        );
+        assert_eq!(
+            parse("t=db.postgresql;c.password:hunter2")
+                .unwrap_err()
+                .to_string(),
+            "Trailing input at byte 16: `c.password`"
+        );
+        // Empty sections are skipped rather than ending the input
+        let ucdf = parse("t=file.csv;;m.owner=data;").unwrap();
+        assert_eq!(ucdf.metadata.get("owner").map(String::as_str), Some("data"));
+    }
+
+    #[test]
+    fn test_error_variants() {
+        fn assert_error(s: &str, expected: impl Fn(&Error) -> bool) {
+            for err in [
+                parse(s).unwrap_err(),
+                parse_bytes(s.as_bytes()).unwrap_err(),
+            ] {
+                assert!(expected(err.root_cause()), "{}: {:?}", s, err);
+            }
+        }
+
+        assert_error("c.host=db1", |e| matches!(e, Error::MissingTypeSection));
+        assert_error("", |e| matches!(e, Error::MissingTypeSection));
+        assert_error("t=db.mysql.x", |e| matches!(e, Error::InvalidSourceType(_)));
+        assert_error("t=db.mysql;t=file.csv", |e| {
+            matches!(e, Error::DuplicateTypeSection(_))
+        });
+        assert_error(
+            "t=db.mysql;q.x=1",
+            |e| matches!(e, Error::UnknownSectionPrefix(p) if p == "q"),
+        );
+        assert_error(
+            "t=db.mysql;x=1",
+            |e| matches!(e, Error::UnknownSectionPrefix(p) if p == "x"),
+        );
+        assert_error("t=db.mysql;a=q", |e| {
+            matches!(e, Error::InvalidAccessMode(_))
+        });
+        assert_error("t=db.mysql;s.fields=:int", |e| {
+            matches!(e, Error::InvalidFieldFormat(_))
+        });
+        assert_error("t=db.mysql;c.host", |e| {
+            matches!(e, Error::TrailingInput { offset: 11, .. })
+        });
+        assert_eq!(
+            parse("t=db.mysql;q.x=1").unwrap_err().to_string(),
+            "in section 2 (`q.x`): Unknown section prefix: q"
+        );
+    }
+
+    #[test]
+    fn test_surrounding_whitespace() {
+        let expected = parse("t=file.csv;c.path=/data/users.csv").unwrap();
+        for s in [
+            "\u{feff}t=file.csv;c.path=/data/users.csv\r\n",
+            "  t=file.csv;c.path=/data/users.csv\n\n",
+            "\n\tt=file.csv;c.path=/data/users.csv;",
+        ] {
+            assert_eq!(parse(s).unwrap(), expected);
+            assert_eq!(parse_bytes(s.as_bytes()).unwrap(), expected);
+        }
+        // Quoted whitespace is kept and offsets count the trimmed text
+        let ucdf = parse("t=file.csv;m.desc=\" a \"\r\n").unwrap();
+        assert_eq!(ucdf.get_path("m.desc").as_deref(), Some(" a "));
+        let err = parse_bytes(b"\xef\xbb\xbf t=file.csv;m.desc=\"a\"b").unwrap_err();
+        assert!(matches!(err, Error::TrailingInput { offset: 25, .. }));
+        assert!(matches!(
+            parse("\r\nt=file.csv;m.desc=\"a\"b"),
+            Err(Error::TrailingInput { offset: 23, .. })
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_type_section() {
+        for s in [
+            "t=file.csv;t=db.postgresql",
+            "t=db.mysql;c.host=db1;t=db.mysql",
+        ] {
+            let err = parse(s).unwrap_err();
+            assert!(matches!(err.root_cause(), Error::DuplicateTypeSection(_)));
+            assert_eq!(
+                parse_bytes(s.as_bytes()).unwrap_err().to_string(),
+                err.to_string()
+            );
+        }
+        assert_eq!(
+            parse("t=file.csv;c.path=/a;t=db.postgresql")
+                .unwrap_err()
+                .to_string(),
+            "in section 3 (`t`): Duplicate type section: db.postgresql after file.csv"
+        );
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        for s in [
+            "t=db.mysql;c.host=db1;m.desc=\"a;b\";s.fields=id:int;a=rw",
+            "c.host=db1;t=db.mysql;;m.owner=data",
+        ] {
+            assert_eq!(parse_bytes(s.as_bytes()).unwrap(), parse(s).unwrap());
+        }
+        for s in [
+            "m.owner=data",
+            "t=db.mysql;c.x=\"a\"b;m.owner=data",
+            "t=db.mysql;garbage here",
+        ] {
+            assert_eq!(
+                parse_bytes(s.as_bytes()).unwrap_err().to_string(),
+                parse(s).unwrap_err().to_string()
+            );
+        }
+        assert!(parse_bytes(b"t=db.mysql;a=q").is_err());
+
+        let err = parse_bytes(b"t=db.mysql;;\xff").unwrap_err();
+        assert_eq!(err.contexts().collect::<Vec<_>>(), ["in section 3"]);
+        let err = parse_bytes(b"t=db.mysql;c.host=db\xff1").unwrap_err();
+        assert_eq!(err.contexts().collect::<Vec<_>>(), ["in section 2"]);
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let inputs: Vec<String> = (0..100)
+            .map(|i| format!("t=db.mysql;c.host=db{};m.x-many-key={}", i, i))
+            .collect();
+        let results = Parser::new().parse_many(&inputs);
+        assert_eq!(results.len(), inputs.len());
+        for (result, input) in results.iter().zip(&inputs) {
+            assert_eq!(result.as_ref().unwrap(), &parse(input).unwrap());
+        }
+        assert!(parse_many(&["t=db.mysql;a=q"])[0].is_err());
+    }
+
+    #[test]
+    fn test_scan_section() {
+        let nom_split = |input| {
+            separated_pair(
+                key_parser,
+                char('='),
+                alt((quoted_value_parser, simple_value_parser)),
+            )(input)
+            .ok()
+        };
+        for input in ["c.host=db1;a=r", "m.x=", "m.x=a=b;", "=x", "no_eq", ""] {
+            assert_eq!(scan_section(input).unwrap().ok(), nom_split(input));
+        }
+        assert!(scan_section("m.desc=\"a;b\";a=r").is_none());
+
+        let s = r#"t=file.csv;m.a="x\";y";;m.b=z"#;
+        let sections: Vec<&str> = section_spans(s).into_iter().map(|span| &s[span]).collect();
+        assert_eq!(sections, ["t=file.csv", r#"m.a="x\";y""#, "m.b=z"]);
+    }
+
+    #[test]
+    fn test_lazy_structure() {
+        let s = "t=api.rest;c.url=\"https://api.example.com\";s.fields=id:int,name:str;s.endpoints=/users:GET";
+        let parser = Parser::new().lazy_structure(true);
+        let ucdf = parser.parse(s).unwrap();
+        let fields = &ucdf.structure["fields"];
+        assert!(matches!(fields, StructureData::Lazy(lazy) if !lazy.is_parsed()));
+        assert_eq!(ucdf.to_string(), s);
+        #[cfg(feature = "with-json")]
+        assert_eq!(
+            serde_json::to_value(&ucdf).unwrap(),
+            serde_json::to_value(parse(s).unwrap()).unwrap()
+        );
+
+        assert_eq!(fields.as_fields().unwrap()[1].name, "name");
+        assert!(matches!(fields, StructureData::Lazy(lazy) if lazy.is_parsed()));
+        assert_eq!(ucdf, parse(s).unwrap());
+
+        // The canonical form does not depend on the parser mode
+        let s = "t=file.csv;s.fields=id:int, name:str;s.endpoints=/users:GET";
+        let lazy = parser.parse(s).unwrap();
+        assert_eq!(lazy.to_string(), s);
+        assert_eq!(
+            lazy.to_canonical_string(),
+            parse(s).unwrap().to_canonical_string()
+        );
+
+        let ucdf = parser.parse("t=file.csv;s.fields=id").unwrap();
+        let err = ucdf.structure["fields"].parsed().unwrap_err();
+        assert!(matches!(err, Error::InvalidFieldFormat(_)));
+    }
+
+    #[test]
+    fn test_section_access() {
+        let ucdf = parse("t=db.postgresql;s.fields=id:int;a.fields=r;a.endpoints=rw").unwrap();
+        assert_eq!(ucdf.access_mode, None);
+        assert_eq!(ucdf.access_for("fields"), Some(AccessMode::READ));
+        assert_eq!(ucdf.get_path("a.endpoints").as_deref(), Some("rw"));
+        assert_eq!(ucdf.access_for("format"), None);
+        assert!(parse("t=db.postgresql;a.fields=q").is_err());
+
+        let mut sections = Vec::new();
+        parse_sections(
+            "t=db.mysql;a.fields=r",
+            &ParseOptions::default(),
+            &mut sections,
+        )
+        .unwrap();
+        assert_eq!(sections[0].prefix(), "a");
+        assert_eq!(sections[0].key(), Some("fields"));
+    }
+
+    #[test]
+    fn test_keep_sections() {
+        let s = "t=file.csv;c.path=/a.csv;t=file.json;x=1;m.owner=";
+        assert!(parse(s).is_err());
+
+        let mut parser = Parser::new().keep_sections(true).allow_duplicate_type(true);
+        let ucdf = parser.parse(s).unwrap();
+        assert_eq!(ucdf.to_string(), "t=file.csv;c.path=/a.csv;m.owner=");
+        let sections: Vec<_> = ucdf.sections().cloned().collect();
+        assert_eq!(
+            sections,
+            [
+                Section::Type(SourceType::from_str("file.csv").unwrap()),
+                Section::connection("path", "/a.csv"),
+                Section::Type(SourceType::from_str("file.json").unwrap()),
+                Section::Unknown("x".to_string(), "1".to_string()),
+                Section::Cleared("m.owner".to_string()),
+            ]
+        );
+        let bytes = parser.parse_bytes(s.as_bytes()).unwrap();
+        assert!(bytes.sections().eq(&sections));
+
+        let mut into = parse("t=db.mysql").unwrap();
+        parser.parse_into(&mut into, "t=db.mysql").unwrap();
+        assert_eq!(into.sections().count(), 1);
+        let plain = parse("t=file.csv;c.path=/a.csv").unwrap();
+        assert_eq!(plain.sections().count(), 0);
+
+        // Kept sections do not take part in comparisons
+        let parser = Parser::new().keep_sections(true);
+        let kept = parser.parse("t=file.csv;c.path=/a.csv").unwrap();
+        assert_eq!(kept, plain);
+        let a = parser.parse("t=db.mysql;c.host=db1;c.port=3306").unwrap();
+        let b = parser.parse("t=db.mysql;c.port=3306;c.host=db1").unwrap();
+        assert!(!a.sections().eq(b.sections()));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_hash_in_values() {
+        // `#` after a space stays in the value when sections follow it or
+        // the descriptor is on one line
+        let ucdf = parse("t=db.mysql;m.desc=Issue #42;c.host=db1").unwrap();
+        assert_eq!(
+            ucdf.to_string(),
+            "t=db.mysql;c.host=db1;m.desc=\"Issue #42\""
+        );
+        let ucdf = parse("t=db.mysql;m.desc=Issue #42").unwrap();
+        assert_eq!(ucdf.metadata.get("desc").unwrap(), "Issue #42");
+        let ucdf = parse("t=db.mysql\nm.desc=Issue #42;c.host=db1\n").unwrap();
+        assert_eq!(ucdf.metadata.get("desc").unwrap(), "Issue #42");
+        assert_eq!(ucdf.connection.get("host").unwrap(), "db1");
+
+        // Comments begin a line or end the last section of a line
+        let ucdf = parse("# note; with a semicolon\nt=db.mysql;c.host=db1 # primary\n").unwrap();
+        assert_eq!(ucdf.to_string(), "t=db.mysql;c.host=db1");
+        // A `#` section with sections after it on its line is rejected
+        assert!(parse("t=db.mysql;#c.host=db1;c.port=3306").is_err());
+    }
+
+    #[test]
+    fn test_strict_types() {
+        let s = "t=db.postgresql;s.fields=id:int,tags:array<strr>";
+        assert!(parse(s).is_ok());
+
+        let parser = Parser::new().strict_types(true);
+        let err = parser.parse(s).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Invalid field format: `tags:array<strr>`: unknown type `array<strr>`"
+        );
+        assert!(parser
+            .clone()
+            .lazy_structure(true)
+            .parse("t=db.postgresql;s.fields=id:strr")
+            .is_err());
+
+        let varchar = "t=db.mysql;s.fields=name:varchar(255)";
+        assert!(parser.parse(varchar).is_err());
+
+        let parser = parser
+            .allow_type("strr")
+            .allow_type("map")
+            .allow_type("varchar");
+        assert!(parser.parse(s).is_ok());
+        assert!(parser.parse(varchar).is_ok());
+        assert!(parser
+            .parse("t=db.postgresql;s.fields=m:map<str,int>")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_key_policy() {
+        let s = "t=db.mysql;c.h\u{f6}st=db1;m.owner=data";
+        assert!(Parser::new()
+            .key_policy(KeyPolicy::Alphanumeric)
+            .parse(s)
+            .is_ok());
+        let err = Parser::new()
+            .key_policy(KeyPolicy::Ascii)
+            .parse(s)
+            .unwrap_err();
+        assert!(matches!(err.root_cause(), Error::InvalidKey(_)));
+        assert!(Parser::new()
+            .key_policy(KeyPolicy::Alphanumeric)
+            .parse("t=db.mysql;m.a b=c")
+            .is_err());
+        // Only the keys of two-level sections are checked
+        assert!(Parser::new()
+            .key_policy(KeyPolicy::Ascii)
+            .parse("t=db.mysql;m.x=\u{f6}")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_dotted_keys() {
+        for s in [
+            "t=db.mysql;c..host=db1",
+            "t=db.mysql;m.owner.=data",
+            "t=db.mysql;s.=x",
+        ] {
+            let err = parse(s).unwrap_err();
+            assert!(matches!(err.root_cause(), Error::InvalidKey(_)), "{}", s);
+        }
+        assert!(parse("t=db.mysql;c.pool.max.idle=10").is_ok());
+        let parser = Parser::new().max_key_depth(1);
+        assert!(parser.parse("t=db.mysql;c.host=db1;a.orders=r").is_ok());
+        assert_eq!(
+            parser
+                .parse("t=db.mysql;m.team.owner=data")
+                .unwrap_err()
+                .to_string(),
+            "in section 2 (`m.team.owner`): Invalid key: \
+             `m.team.owner` has 2 segments, at most 1 allowed"
+        );
+    }
+
+    #[cfg(feature = "with-unicode")]
+    #[test]
+    fn test_nfc_keys() {
+        let s = "t=db.mysql;c.cafe\u{301}=db1;s.caf\u{e9}=x:int";
+        let ucdf = Parser::new().nfc_keys(true).parse(s).unwrap();
+        assert_eq!(ucdf.get_path("c.caf\u{e9}").as_deref(), Some("db1"));
+        assert!(ucdf.structure.contains_key("caf\u{e9}"));
+        assert!(parse(s).unwrap().get_path("c.caf\u{e9}").is_none());
+    }
+
+    #[test]
+    fn test_structure_parser_hooks() {
+        let parser = Parser::new().structure_parser("procedures", |value: &str| {
+            let endpoints = value
+                .split(',')
+                .map(|name| {
+                    if name.is_empty() {
+                        return Err(Error::InvalidSectionFormat("empty procedure".to_string()));
+                    }
+                    Ok(Endpoint::builder().path(name).method("CALL").build())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(StructureData::Endpoints(endpoints))
+        });
+
+        let ucdf = parser
+            .parse("t=db.postgresql;c.host=db1;s.procedures=refresh,archive")
+            .unwrap();
+        match ucdf.structure.get("procedures") {
+            Some(StructureData::Endpoints(endpoints)) => {
+                assert_eq!(endpoints.len(), 2);
+                assert_eq!(endpoints[1].path, "archive");
+            }
+            other => panic!("Expected endpoints, got {:?}", other),
+        }
+
+        // Without the hook the section stays custom
+        let ucdf = parse("t=db.postgresql;s.procedures=refresh").unwrap();
+        assert!(matches!(
+            ucdf.structure.get("procedures"),
+            Some(StructureData::Custom(..))
+        ));
+
+        let err = parser
+            .parse("t=db.postgresql;s.procedures=a,,b")
+            .unwrap_err();
+        assert!(err.to_string().contains("in section 2"));
+    }
+
+    #[test]
+    fn test_typed_structure() {
+        let mut ucdf = parse("t=api.rest;c.url=https://api.example.com").unwrap();
+        ucdf.structure
+            .insert("version".to_string(), StructureData::typed(3u32));
+        assert_eq!(ucdf.structure["version"].as_typed::<u32>(), Some(&3));
+        assert_eq!(ucdf.structure["version"].as_typed::<String>(), None);
+
+        // Typed values fall back to their Display form
+        let reparsed = parse(&ucdf.to_string()).unwrap();
+        assert_eq!(reparsed.get_path("s.version").as_deref(), Some("3"));
+        assert_eq!(
+            ucdf.clone().structure["version"],
+            StructureData::typed("3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_source_type_predicates() {
+        let ucdf = parse("t=db.postgresql").unwrap();
+        assert!(ucdf.source_type.is_db());
+        assert!(!ucdf.source_type.is_file());
+        assert_eq!(ucdf.source_type.kind(), Category::Db);
+        assert!(ucdf.source_type.matches("db.*"));
+        assert!(ucdf.source_type.matches("*.postgre?ql"));
+        assert!(!ucdf.source_type.matches("stream.*"));
+
+        let ucdf = parse("t=ml.model").unwrap();
+        assert_eq!(ucdf.source_type.kind(), Category::Custom("ml".to_string()));
+        assert!(ucdf.source_type.matches("*"));
+    }
+
+    #[test]
+    fn test_extended_field_literals() {
+        let ucdf = parse("t=db.postgresql;s.fields=id:int:pk,tags:array<str>,attrs:map<str,int>").unwrap();
+        if let Some(StructureData::Fields(fields)) = ucdf.structure.get("fields") {
+            assert_eq!(fields.len(), 3);
+            assert!(fields[0].is_primary_key());
+            assert_eq!(fields[1].dtype, "array<str>");
+            assert_eq!(
+                DataType::from_str(&fields[1].dtype).unwrap(),
+                DataType::Array(Box::new(DataType::String))
+            );
+            assert_eq!(fields[2].dtype, "map<str,int>");
+        } else {
+            panic!("Expected fields structure");
+        }
+        assert_eq!(
+            UCDF::parse_fields("id:int:pk,tags:array<str>").unwrap(),
+            parse_fields("id:int:pk,tags:array<str>").unwrap().1
+        );
+
+        let err = parse("t=db.postgresql;s.fields=id:int,name").unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Invalid field format: `name`: expected `name:type`"
+        );
+        let err = parse("t=db.postgresql;s.fields=id:int:pkk").unwrap_err();
+        assert!(err.to_string().contains("unknown modifier `pkk`"));
+        let err = Field::from_str("tags:array<int").unwrap_err();
+        assert!(err.to_string().contains("unclosed `<`"));
+
+        // Other type tokens are custom types
+        let s = "t=db.mysql;s.fields=name:varchar(255),loc:geo-point,ts:pg.timestamptz";
+        let ucdf = parse(s).unwrap();
+        let dtypes: Vec<_> = ucdf
+            .structure
+            .fields()
+            .unwrap()
+            .iter()
+            .map(|f| DataType::from_str(&f.dtype).unwrap())
+            .collect();
+        assert_eq!(
+            dtypes,
+            [
+                DataType::Custom("varchar(255)".to_string()),
+                DataType::Custom("geo-point".to_string()),
+                DataType::Custom("pg.timestamptz".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_context() {
+        let err = parse("t=db.postgresql;c.host=localhost;s.fields=id;int").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "in section 3 (`s.fields`): Invalid field format: `id`: expected `name:type`"
+        );
+        assert_eq!(
+            err.contexts().collect::<Vec<_>>(),
+            vec!["in section 3 (`s.fields`)"]
+        );
+        assert!(matches!(err.root_cause(), Error::InvalidFieldFormat(_)));
+        assert!(std::error::Error::source(&err).is_some());
+
+        let err = parse("t=file.csv;a=rwq").unwrap_err();
+        assert!(matches!(err.root_cause(), Error::InvalidAccessMode(_)));
+
+        // Values may be secrets and stay out of the context
+        let err = parse("t=db.postgresql;c..password=hunter2").unwrap_err();
+        assert_eq!(
+            err.contexts().collect::<Vec<_>>(),
+            vec!["in section 2 (`c..password`)"]
+        );
+        assert!(!err.to_string().contains("hunter2"));
+    }
+
     #[test]
     fn test_malformed_input() {
         // Test invalid access mode (should be caught by AccessMode::from_str)
@@ -402,4 +2127,105 @@ mod tests {
         // Test completely invalid format
         assert!(parse("not a valid ucdf string").is_err());
     }
+
+    mod round_trip {
+        use super::*;
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+
+        // Values mixing delimiters, quotes, escapes and non-ASCII text
+        fn value() -> impl Strategy<Value = String> {
+            "[a-zA-Z0-9 ;=,:\"\\\\\n/.é-]{0,12}"
+        }
+
+        fn key() -> impl Strategy<Value = String> {
+            "[a-z][a-z0-9_-]{0,4}(\\.[a-z0-9_-]{1,3})?"
+        }
+
+        fn field() -> impl Strategy<Value = Field> {
+            let dtype = prop::sample::select(vec!["int", "str", "float", "bool", "array<int>"]);
+            let modifiers = prop::sample::subsequence(vec!["pk", "unique", "nullable"], 0..=2);
+            ("[a-z_:\"][a-z0-9_:,\"]{0,8}", dtype, modifiers).prop_map(
+                |(name, dtype, modifiers)| {
+                    modifiers
+                        .into_iter()
+                        .fold(Field::new(name, dtype, None), Field::with_modifier)
+                },
+            )
+        }
+
+        fn endpoint() -> impl Strategy<Value = Endpoint> {
+            let method = prop::sample::select(vec!["GET", "POST", "DELETE"]);
+            ("(https://x)?/[a-z{}:,]{1,6}(/[a-z]{1,6})?", method)
+                .prop_map(|(path, method)| Endpoint::new(path, method))
+        }
+
+        prop_compose! {
+            fn descriptor()(
+                category in "[a-z]{1,8}",
+                subtype in proptest::option::of("[a-z0-9_]{1,8}"),
+                connection in vec((key(), value()), 0..5),
+                cleared in vec(key(), 0..2),
+                fields in proptest::option::of(vec(field(), 1..4)),
+                endpoints in proptest::option::of(vec(endpoint(), 1..3)),
+                format in proptest::option::of(value()),
+                custom in vec(("[a-z]{1,6}x", value()), 0..2),
+                access_mode in proptest::option::of(1u8..32),
+                section_access in vec(("[a-z]{1,6}", 1u8..32), 0..3),
+                metadata in vec((key(), value()), 0..5),
+                includes in vec(value(), 0..2),
+                encrypted in proptest::option::of("[A-Za-z0-9+/]{4,16}={0,2}"),
+            ) -> UCDF {
+                let mut ucdf = UCDF::with_source_type(SourceType::new(category, subtype));
+                for (key, value) in connection {
+                    ucdf.add_connection(key, value);
+                }
+                if let Some(fields) = fields {
+                    ucdf.add_fields(fields);
+                }
+                if let Some(endpoints) = endpoints {
+                    ucdf.add_endpoints(endpoints);
+                }
+                if let Some(format) = format {
+                    ucdf.add_format(format);
+                }
+                for (key, value) in custom {
+                    ucdf.add_custom_structure(key, value);
+                }
+                ucdf.access_mode = access_mode.and_then(AccessMode::from_bits);
+                for (key, bits) in section_access {
+                    ucdf.set_section_access(key, AccessMode::from_bits(bits).unwrap());
+                }
+                for (key, value) in metadata {
+                    ucdf.add_metadata(key, value);
+                }
+                for key in cleared {
+                    ucdf.clear_path(&format!("m.{}", key)).unwrap();
+                }
+                ucdf.includes = includes;
+                ucdf.encrypted_connection = encrypted;
+                ucdf
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn test_display_round_trip(ucdf in descriptor()) {
+                prop_assert_eq!(&parse(&ucdf.to_string()).unwrap(), &ucdf);
+                prop_assert_eq!(&parse(&ucdf.to_canonical_string()).unwrap(), &ucdf);
+            }
+
+            #[test]
+            fn test_normalize(ucdf in descriptor()) {
+                let canonical = ucdf.to_canonical_string();
+                prop_assert_eq!(&normalize(&ucdf.to_string()).unwrap(), &canonical);
+                prop_assert_eq!(&normalize(&canonical).unwrap(), &canonical);
+
+                let mut normalized = ucdf.clone();
+                normalized.normalize();
+                prop_assert_eq!(&normalized, &ucdf);
+                prop_assert_eq!(normalized.to_string(), canonical);
+            }
+        }
+    }
 }
\ No newline at end of file
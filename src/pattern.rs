@@ -0,0 +1,206 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::parser::{split_sections, unquote};
+use crate::sections::{glob_match, quote_structure, quote_value, KeyPath, UCDF};
+
+/// Pattern for selecting UCDF descriptors
+///
+/// A pattern is written like a UCDF string whose values may contain `*` and
+/// `?` wildcards, e.g. `t=stream.kafka;a=r;m.env=prod` or `t=db.*;c.host=*.internal`.
+/// Every section in the pattern must match; sections not mentioned are ignored.
+/// Keys must be present on the descriptor for a value pattern to match.
+/// Values are quoted as in UCDF strings, e.g. `m.desc="a;b"`.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{parse, UcdfPattern};
+///
+/// let pattern: UcdfPattern = "t=db.*;m.env=prod".parse().unwrap();
+/// let ucdf = parse("t=db.postgresql;c.host=db1;m.env=prod").unwrap();
+/// assert!(ucdf.matches(&pattern));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UcdfPattern {
+    pub source_type: Option<String>,
+    pub connection: Vec<(String, String)>,
+    pub structure: Vec<(String, String)>,
+    pub access_mode: Option<String>,
+    pub metadata: Vec<(String, String)>,
+}
+
+impl UcdfPattern {
+    /// Create an empty pattern matching every descriptor
+    pub fn new() -> Self {
+        UcdfPattern::default()
+    }
+
+    /// Fluent API for matching the source type
+    pub fn with_source_type(mut self, pattern: impl Into<String>) -> Self {
+        self.source_type = Some(pattern.into());
+        self
+    }
+
+    /// Fluent API for matching a connection parameter
+    pub fn with_connection(mut self, key: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.connection.push((key.into(), pattern.into()));
+        self
+    }
+
+    /// Fluent API for matching a structure section
+    pub fn with_structure(mut self, key: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.structure.push((key.into(), pattern.into()));
+        self
+    }
+
+    /// Fluent API for matching the access mode
+    pub fn with_access_mode(mut self, pattern: impl Into<String>) -> Self {
+        self.access_mode = Some(pattern.into());
+        self
+    }
+
+    /// Fluent API for matching a metadata entry
+    pub fn with_metadata(mut self, key: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), pattern.into()));
+        self
+    }
+
+    /// Check if a descriptor matches this pattern
+    pub fn matches(&self, ucdf: &UCDF) -> bool {
+        let value_matches = |pattern: &str, value: Option<&String>| {
+            value.is_some_and(|value| glob_match(pattern, value))
+        };
+
+        self.source_type
+            .as_ref()
+            .is_none_or(|pattern| ucdf.source_type.matches(pattern))
+            && self
+                .connection
+                .iter()
+                .all(|(key, pattern)| value_matches(pattern, ucdf.connection.get(key)))
+            && self.structure.iter().all(|(key, pattern)| {
+                ucdf.structure
+                    .get(key)
+                    .is_some_and(|data| glob_match(pattern, &data.to_string()))
+            })
+            && self.access_mode.as_ref().is_none_or(|pattern| {
+                ucdf.access_mode
+                    .is_some_and(|mode| glob_match(pattern, &mode.to_string()))
+            })
+            && self
+                .metadata
+                .iter()
+                .all(|(key, pattern)| value_matches(pattern, ucdf.metadata.get(key)))
+    }
+}
+
+impl FromStr for UcdfPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut pattern = UcdfPattern::new();
+        for section in split_sections(s) {
+            let (key, value) = section
+                .split_once('=')
+                .ok_or_else(|| Error::InvalidSectionFormat(section.to_string()))?;
+            let value = unquote(value);
+
+            match KeyPath::parse(key) {
+                Some(KeyPath::Type) => pattern.source_type = Some(value.to_string()),
+                Some(KeyPath::Access) => pattern.access_mode = Some(value.to_string()),
+                Some(KeyPath::Connection(conn_key)) => pattern
+                    .connection
+                    .push((conn_key.to_string(), value.to_string())),
+                Some(KeyPath::Structure(struct_key)) => pattern
+                    .structure
+                    .push((struct_key.to_string(), value.to_string())),
+                Some(KeyPath::Meta(meta_key)) => pattern
+                    .metadata
+                    .push((meta_key.to_string(), value.to_string())),
+                _ => return Err(Error::UnknownSectionPrefix(key.to_string())),
+            }
+        }
+        Ok(pattern)
+    }
+}
+
+impl fmt::Display for UcdfPattern {
+    /// Format the pattern with values quoted as in UCDF strings
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(source_type) = &self.source_type {
+            parts.push(format!("t={}", quote_value(source_type)));
+        }
+        for (key, value) in &self.connection {
+            parts.push(format!("c.{}={}", key, quote_value(value)));
+        }
+        for (key, value) in &self.structure {
+            parts.push(format!("s.{}={}", key, quote_structure(value)));
+        }
+        if let Some(access_mode) = &self.access_mode {
+            parts.push(format!("a={}", quote_value(access_mode)));
+        }
+        for (key, value) in &self.metadata {
+            parts.push(format!("m.{}={}", key, quote_value(value)));
+        }
+        write!(f, "{}", parts.join(";"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_select_read_only_kafka_in_prod() {
+        let pattern: UcdfPattern = "t=stream.kafka;a=r;m.env=prod".parse().unwrap();
+
+        let prod = parse("t=stream.kafka;c.topic=events;a=r;m.env=prod").unwrap();
+        let staging = parse("t=stream.kafka;c.topic=events;a=r;m.env=staging").unwrap();
+        let writable = parse("t=stream.kafka;c.topic=events;a=rw;m.env=prod").unwrap();
+        let untagged = parse("t=stream.kafka;c.topic=events;a=r").unwrap();
+
+        assert!(prod.matches(&pattern));
+        assert!(!staging.matches(&pattern));
+        assert!(!writable.matches(&pattern));
+        assert!(!untagged.matches(&pattern));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let ucdf = parse("t=db.postgresql;c.host=pg1.internal;s.fields=id:int,name:str").unwrap();
+
+        assert!(ucdf.matches(&"t=db.*;c.host=*.internal".parse().unwrap()));
+        assert!(ucdf.matches(&"s.fields=*name:str*".parse().unwrap()));
+        assert!(ucdf.matches(&UcdfPattern::new()));
+        assert!(!ucdf.matches(&"c.port=*".parse().unwrap()));
+        assert!(!ucdf.matches(&"a=*".parse().unwrap()));
+        assert!("x.key=value".parse::<UcdfPattern>().is_err());
+    }
+
+    #[test]
+    fn test_quoted_values() {
+        let pattern: UcdfPattern = r#"t=db.*;m.desc="a;b""#.parse().unwrap();
+        assert_eq!(pattern.metadata, [("desc".to_string(), "a;b".to_string())]);
+        assert!(parse(r#"t=db.mysql;m.desc="a;b""#)
+            .unwrap()
+            .matches(&pattern));
+
+        let pattern = UcdfPattern::new()
+            .with_source_type("db.*")
+            .with_connection("url", "postgres://*:5432/*")
+            .with_structure("fields", "*;*")
+            .with_access_mode("r*")
+            .with_metadata("desc", "say \"hi\"; k=v")
+            .with_metadata("owner", "data");
+        let s = pattern.to_string();
+        assert_eq!(
+            s,
+            r#"t=db.*;c.url="postgres://*:5432/*";s.fields="*;*";a=r*;m.desc="say \"hi\"; k=v";m.owner=data"#
+        );
+        assert_eq!(s.parse::<UcdfPattern>().unwrap(), pattern);
+    }
+}
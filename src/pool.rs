@@ -0,0 +1,181 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// Connection pool settings kept in a descriptor's `c.pool.*` keys
+///
+/// | Key                      | Setting                               |
+/// |--------------------------|---------------------------------------|
+/// | `c.pool.max_size`        | most connections open at once         |
+/// | `c.pool.min_idle`        | idle connections kept open            |
+/// | `c.pool.acquire_timeout` | wait for a free connection, seconds   |
+/// | `c.pool.idle_timeout`    | close idle connections after, seconds |
+/// | `c.pool.max_lifetime`    | close connections after, seconds      |
+///
+/// Timeouts may have a fraction (`c.pool.acquire_timeout=0.5`). Unset keys
+/// leave the pool library's defaults in place.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use ucdf::parse;
+///
+/// let ucdf = parse("t=db.postgresql;c.host=db1;c.pool.max_size=16;c.pool.acquire_timeout=2.5").unwrap();
+/// let pool = ucdf.pool_config().unwrap();
+/// assert_eq!(pool.max_size, Some(16));
+/// assert_eq!(pool.acquire_timeout, Some(Duration::from_millis(2500)));
+/// assert_eq!(pool.min_idle, None);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_size: Option<u32>,
+    pub min_idle: Option<u32>,
+    pub acquire_timeout: Option<Duration>,
+    pub idle_timeout: Option<Duration>,
+    pub max_lifetime: Option<Duration>,
+}
+
+impl UCDF {
+    /// Read the connection pool settings from the `c.pool.*` keys
+    pub fn pool_config(&self) -> Result<PoolConfig> {
+        Ok(PoolConfig {
+            max_size: self.pool_value("max_size")?,
+            min_idle: self.pool_value("min_idle")?,
+            acquire_timeout: self.pool_timeout("acquire_timeout")?,
+            idle_timeout: self.pool_timeout("idle_timeout")?,
+            max_lifetime: self.pool_timeout("max_lifetime")?,
+        })
+    }
+
+    fn pool_value<T: FromStr>(&self, name: &str) -> Result<Option<T>> {
+        let key = format!("pool.{}", name);
+        let Some(value) = self.connection.get(&key) else {
+            return Ok(None);
+        };
+        let value = value.parse().map_err(|_| {
+            Error::InvalidFormat(format!("c.{}: expected a number, got `{}`", key, value))
+        })?;
+        Ok(Some(value))
+    }
+
+    fn pool_timeout(&self, name: &str) -> Result<Option<Duration>> {
+        let Some(secs) = self.pool_value::<f64>(name)? else {
+            return Ok(None);
+        };
+        Duration::try_from_secs_f64(secs).map(Some).map_err(|_| {
+            Error::InvalidFormat(format!("c.pool.{}: invalid duration `{}`", name, secs))
+        })
+    }
+}
+
+impl PoolConfig {
+    /// Convert to a deadpool pool configuration
+    ///
+    /// The acquire timeout becomes the wait timeout; deadpool has no idle
+    /// connection or lifetime settings.
+    #[cfg(feature = "with-deadpool")]
+    pub fn to_deadpool(&self) -> deadpool::managed::PoolConfig {
+        let mut config = deadpool::managed::PoolConfig::default();
+        if let Some(max_size) = self.max_size {
+            config.max_size = max_size as usize;
+        }
+        config.timeouts.wait = self.acquire_timeout;
+        config
+    }
+
+    /// Start a bb8 pool builder with these settings
+    ///
+    /// The acquire timeout becomes the connection timeout.
+    #[cfg(feature = "with-bb8")]
+    pub fn to_bb8<M: bb8::ManageConnection>(&self) -> bb8::Builder<M> {
+        let mut builder = bb8::Builder::new()
+            .min_idle(self.min_idle)
+            .idle_timeout(self.idle_timeout)
+            .max_lifetime(self.max_lifetime);
+        if let Some(max_size) = self.max_size {
+            builder = builder.max_size(max_size);
+        }
+        if let Some(timeout) = self.acquire_timeout {
+            builder = builder.connection_timeout(timeout);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_pool_config() {
+        let ucdf = parse(
+            "t=db.mysql;c.host=db1;c.pool.max_size=8;c.pool.min_idle=2;\
+             c.pool.idle_timeout=600;c.pool.max_lifetime=0.25",
+        )
+        .unwrap();
+        let pool = ucdf.pool_config().unwrap();
+        assert_eq!(
+            pool,
+            PoolConfig {
+                max_size: Some(8),
+                min_idle: Some(2),
+                acquire_timeout: None,
+                idle_timeout: Some(Duration::from_secs(600)),
+                max_lifetime: Some(Duration::from_millis(250)),
+            }
+        );
+        assert_eq!(
+            parse("t=db.mysql").unwrap().pool_config().unwrap(),
+            PoolConfig::default()
+        );
+        for invalid in [
+            "c.pool.max_size=-1",
+            "c.pool.min_idle=many",
+            "c.pool.acquire_timeout=-2",
+        ] {
+            let ucdf = parse(&format!("t=db.mysql;{}", invalid)).unwrap();
+            assert!(ucdf.pool_config().is_err(), "{}", invalid);
+        }
+
+        #[cfg(feature = "with-deadpool")]
+        {
+            let config = pool.to_deadpool();
+            assert_eq!(config.max_size, 8);
+            assert_eq!(config.timeouts.wait, None);
+        }
+    }
+
+    #[cfg(feature = "with-bb8")]
+    #[test]
+    fn test_bb8_builder() {
+        #[derive(Debug)]
+        struct Manager;
+
+        impl bb8::ManageConnection for Manager {
+            type Connection = ();
+            type Error = std::convert::Infallible;
+
+            async fn connect(&self) -> std::result::Result<(), Self::Error> {
+                Ok(())
+            }
+
+            async fn is_valid(&self, _: &mut ()) -> std::result::Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn has_broken(&self, _: &mut ()) -> bool {
+                false
+            }
+        }
+
+        let ucdf = parse("t=db.postgresql;c.pool.max_size=4;c.pool.acquire_timeout=3").unwrap();
+        let builder = ucdf.pool_config().unwrap().to_bb8::<Manager>();
+        let debug = format!("{:?}", builder);
+        assert!(debug.contains("max_size: 4"), "{}", debug);
+        assert!(debug.contains("connection_timeout: 3s"), "{}", debug);
+    }
+}
@@ -0,0 +1,461 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use encoding_rs::{CoderResult, Decoder, Encoding};
+use serde::de::{Deserializer as _, SeqAccess, Visitor};
+
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::UCDF;
+use crate::types::{DataValue, Field};
+
+/// A record read by [`preview`], with values in column order
+///
+/// Values are `None` for empty cells of non-string columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub values: Vec<(String, Option<DataValue>)>,
+}
+
+impl Record {
+    /// Get a value by column name
+    pub fn get(&self, name: &str) -> Option<&DataValue> {
+        self.values
+            .iter()
+            .find(|(column, _)| column == name)
+            .and_then(|(_, value)| value.as_ref())
+    }
+
+    /// Iterate over column names
+    pub fn columns(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().map(|(column, _)| column.as_str())
+    }
+}
+
+/// Read the first `n` records of a local file source
+///
+/// Supported source types are `file.csv`, `file.json` (an array of objects or
+/// one object per line) and `file.txt` (one `line` column per line). The file
+/// at `c.path` is decoded with `c.encoding` (UTF-8 by default), and CSV files
+/// honour `c.delimiter`, `c.quote`, `c.comment` and `c.header` (`true` by
+/// default). Values are typed according to `s.fields` when it is declared;
+/// columns are matched by name when the file has a header and by position
+/// otherwise.
+///
+/// The file is decoded as it is read, and reading stops after `n` records,
+/// so previewing a large file only reads its beginning.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ucdf::{parse, preview};
+///
+/// let ucdf = parse("t=file.csv;c.path=/data/users.csv;s.fields=id:int,name:str").unwrap();
+/// for record in preview(&ucdf, 5).unwrap() {
+///     println!("{:?}", record.get("name"));
+/// }
+/// ```
+pub fn preview(ucdf: &UCDF, n: usize) -> Result<Vec<Record>> {
+    if !ucdf.source_type.is_file() {
+        return Err(Error::UnsupportedSource(ucdf.source_type.to_string()));
+    }
+    let path = ucdf
+        .connection
+        .get("path")
+        .ok_or_else(|| Error::InvalidFormat("file source without c.path".to_string()))?;
+    let encoding = match ucdf.connection.get("encoding") {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| Error::InvalidFormat(format!("unknown encoding: {}", label)))?,
+        None => encoding_rs::UTF_8,
+    };
+    let io_error = |e: io::Error| Error::from(e).context(format!("reading {}", path));
+    let file = File::open(path).map_err(io_error)?;
+    let mut reader = BufReader::new(Decoded::new(file, encoding.new_decoder()));
+    let fields = ucdf
+        .structure
+        .get("fields")
+        .and_then(|data| data.as_fields())
+        .unwrap_or_default();
+
+    match ucdf.source_type.subtype.as_deref() {
+        Some("csv") => preview_csv(ucdf, reader, fields, n),
+        Some("json") => preview_json(reader, fields, n),
+        Some("txt") => {
+            let mut records = Vec::new();
+            let mut line = String::new();
+            while records.len() < n && read_line(&mut reader, &mut line).map_err(io_error)? {
+                records.push(Record {
+                    values: vec![("line".to_string(), Some(DataValue::String(line.clone())))],
+                });
+            }
+            Ok(records)
+        }
+        _ => Err(Error::UnsupportedSource(ucdf.source_type.to_string())),
+    }
+}
+
+// Reader decoding the bytes of `inner` to UTF-8, honouring a byte order mark
+//
+// Reads must ask for at least 4 bytes, so that any character fits; a
+// `BufReader` around it takes care of that.
+struct Decoded<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Box<[u8]>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: Read> Decoded<R> {
+    fn new(inner: R, decoder: Decoder) -> Self {
+        Decoded {
+            inner,
+            decoder,
+            input: vec![0; 8 * 1024].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            eof: false,
+            finished: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Decoded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.finished && !buf.is_empty() {
+            if self.start == self.end && !self.eof {
+                self.start = 0;
+                self.end = self.inner.read(&mut self.input)?;
+                self.eof = self.end == 0;
+            }
+            let input = &self.input[self.start..self.end];
+            let (result, read, written, _) = self.decoder.decode_to_utf8(input, buf, self.eof);
+            self.start += read;
+            self.finished = self.eof && result == CoderResult::InputEmpty;
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+        Ok(0)
+    }
+}
+
+// Read the next line without its line break into `line`, returning whether
+// there was one
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<bool> {
+    line.clear();
+    if reader.read_line(line)? == 0 {
+        return Ok(false);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(true)
+}
+
+fn preview_csv(ucdf: &UCDF, reader: impl Read, fields: &[Field], n: usize) -> Result<Vec<Record>> {
+    let option = |key: &str| ucdf.connection.get(key).map(String::as_str);
+    let byte_option = |key: &str| -> Result<Option<u8>> {
+        match option(key) {
+            None => Ok(None),
+            Some("tab" | "\\t") => Ok(Some(b'\t')),
+            Some(value) if value.len() == 1 && value.is_ascii() => Ok(Some(value.as_bytes()[0])),
+            Some(value) => Err(Error::InvalidFormat(format!(
+                "c.{} must be a single ASCII character: {}",
+                key, value
+            ))),
+        }
+    };
+    let has_header = match option("header") {
+        None => true,
+        Some(value) => value
+            .parse::<bool>()
+            .map_err(|_| Error::InvalidFormat(format!("c.header must be a boolean: {}", value)))?,
+    };
+
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .has_headers(has_header)
+        .comment(byte_option("comment")?);
+    if let Some(delimiter) = byte_option("delimiter")? {
+        builder.delimiter(delimiter);
+    }
+    if let Some(quote) = byte_option("quote")? {
+        builder.quote(quote);
+    }
+    let mut reader = builder.from_reader(reader);
+
+    let columns: Vec<String> = if has_header {
+        reader
+            .headers()
+            .map_err(csv_error)?
+            .iter()
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut records = Vec::new();
+    for (row, result) in reader.records().take(n).enumerate() {
+        let row = row + 1;
+        let cells = result.map_err(csv_error)?;
+        let mut values = Vec::new();
+        for (i, cell) in cells.iter().enumerate() {
+            let name = match columns.get(i) {
+                Some(name) => name.clone(),
+                None => fields
+                    .get(i)
+                    .map(|field| field.name.clone())
+                    .unwrap_or_else(|| format!("column{}", i + 1)),
+            };
+            let dtype = fields
+                .iter()
+                .find(|field| field.name == name)
+                .map_or("str", |field| field.dtype.as_str());
+            let value =
+                typed(cell, dtype).with_context(|| format!("row {}, column `{}`", row, name))?;
+            values.push((name, value));
+        }
+        records.push(Record { values });
+    }
+    Ok(records)
+}
+
+// Visitor collecting up to `n` elements of a JSON array, leaving the rest
+// unread
+struct FirstElements<'a> {
+    elements: &'a mut Vec<serde_json::Value>,
+    n: usize,
+}
+
+impl<'de> Visitor<'de> for FirstElements<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an array of objects")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while self.elements.len() < self.n {
+            match seq.next_element()? {
+                Some(element) => self.elements.push(element),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn preview_json(mut reader: impl BufRead, fields: &[Field], n: usize) -> Result<Vec<Record>> {
+    let json_error = |e: serde_json::Error| Error::ParseError(e.to_string());
+    let mut objects = Vec::new();
+    if first_byte(&mut reader)? == Some(b'[') {
+        let visitor = FirstElements {
+            elements: &mut objects,
+            n,
+        };
+        // Stopping early leaves the array unterminated, which is reported
+        // once the visitor returns
+        match serde_json::Deserializer::from_reader(reader).deserialize_seq(visitor) {
+            Err(_) if objects.len() == n => {}
+            result => result.map_err(json_error)?,
+        }
+    } else {
+        let mut line = String::new();
+        while objects.len() < n && read_line(&mut reader, &mut line)? {
+            if !line.trim().is_empty() {
+                objects.push(serde_json::from_str(&line).map_err(json_error)?);
+            }
+        }
+    }
+
+    objects
+        .into_iter()
+        .enumerate()
+        .map(|(row, object)| {
+            let serde_json::Value::Object(map) = object else {
+                return Err(Error::ParseError(format!(
+                    "record {} is not an object",
+                    row + 1
+                )));
+            };
+            let values = map
+                .into_iter()
+                .map(|(name, value)| {
+                    let dtype = fields
+                        .iter()
+                        .find(|field| field.name == name)
+                        .map(|field| field.dtype.as_str());
+                    let value = json_value(value, dtype)
+                        .with_context(|| format!("record {}, key `{}`", row + 1, name))?;
+                    Ok((name, value))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Record { values })
+        })
+        .collect()
+}
+
+// Skip leading whitespace and peek at the first byte after it
+fn first_byte(reader: &mut impl BufRead) -> io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&byte) = buf.first() else {
+            return Ok(None);
+        };
+        if !byte.is_ascii_whitespace() {
+            return Ok(Some(byte));
+        }
+        reader.consume(1);
+    }
+}
+
+// Type a JSON value by the declared type, or by its JSON type if undeclared
+fn json_value(value: serde_json::Value, dtype: Option<&str>) -> Result<Option<DataValue>> {
+    use serde_json::Value;
+
+    match (value, dtype) {
+        (Value::Null, _) => Ok(None),
+        (value @ (Value::Array(_) | Value::Object(_)), _) => {
+            Ok(Some(DataValue::Json(value.to_string())))
+        }
+        (Value::String(s), Some(dtype)) => typed(&s, dtype),
+        (value, Some(dtype)) => typed(&value.to_string(), dtype),
+        (Value::String(s), None) => Ok(Some(DataValue::String(s))),
+        (Value::Bool(b), None) => Ok(Some(DataValue::Boolean(b))),
+        (Value::Number(number), None) => Ok(Some(match number.as_i64() {
+            Some(i) => DataValue::Integer(i),
+            None => DataValue::Float(number.as_f64().unwrap_or(f64::NAN)),
+        })),
+    }
+}
+
+fn typed(cell: &str, dtype: &str) -> Result<Option<DataValue>> {
+    if cell.is_empty() && dtype != "str" {
+        return Ok(None);
+    }
+    DataValue::parse(cell, dtype).map(Some)
+}
+
+fn csv_error(e: csv::Error) -> Error {
+    Error::ParseError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::parser::parse;
+
+    fn write_sample(name: &str, contents: &[u8]) -> String {
+        let path =
+            std::env::temp_dir().join(format!("ucdf-preview-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_preview_csv() {
+        let path = write_sample(
+            "users.csv",
+            b"id;name;score\n1;Ann;\n2;\"B;ob\";3.5\n3;Eve;1\n",
+        );
+        let ucdf = parse(&format!(
+            "t=file.csv;c.path={};c.delimiter=\";\";s.fields=id:int,name:str,score:float",
+            path
+        ))
+        .unwrap();
+        let records = preview(&ucdf, 2).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get("id"), Some(&DataValue::Integer(1)));
+        assert_eq!(records[0].get("score"), None);
+        assert_eq!(
+            records[1].get("name"),
+            Some(&DataValue::String("B;ob".to_string()))
+        );
+        assert_eq!(records[1].get("score"), Some(&DataValue::Float(3.5)));
+
+        fs::remove_file(&path).unwrap();
+
+        let path = write_sample("latin1.csv", b"Z\xfcrich,1\n");
+        let ucdf = parse(&format!(
+            "t=file.csv;c.path={};c.encoding=latin1;c.header=false;s.fields=city:str,n:int",
+            path
+        ))
+        .unwrap();
+        let records = preview(&ucdf, 10).unwrap();
+        assert_eq!(records[0].columns().collect::<Vec<_>>(), vec!["city", "n"]);
+        assert_eq!(records[0].get("city").unwrap().as_str(), Some("Zürich"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_preview_txt() {
+        // Larger than the read buffers, with characters growing when decoded
+        let path = write_sample("latin1.txt", &b"Z\xfcrich\r\n".repeat(5000));
+        let ucdf = parse(&format!("t=file.txt;c.path={};c.encoding=latin1", path)).unwrap();
+        let records = preview(&ucdf, 6000).unwrap();
+        assert_eq!(records.len(), 5000);
+        assert_eq!(records[4999].get("line").unwrap().as_str(), Some("Zürich"));
+        fs::remove_file(&path).unwrap();
+
+        let path = write_sample("utf16.txt", b"\xff\xfea\0\n\0b\0");
+        let ucdf = parse(&format!("t=file.txt;c.path={}", path)).unwrap();
+        let lines: Vec<_> = preview(&ucdf, 5)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.get("line").unwrap().clone())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                DataValue::String("a".to_string()),
+                DataValue::String("b".to_string())
+            ]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_preview_json() {
+        let path = write_sample(
+            "events.json",
+            b"{\"id\": 1, \"tags\": [\"a\"]}\n{\"id\": \"2\", \"ok\": true}\n",
+        );
+        let ucdf = parse(&format!("t=file.json;c.path={};s.fields=id:int", path)).unwrap();
+        let records = preview(&ucdf, 5).unwrap();
+        assert_eq!(
+            records[0].get("tags"),
+            Some(&DataValue::Json("[\"a\"]".to_string()))
+        );
+        assert_eq!(records[1].get("id"), Some(&DataValue::Integer(2)));
+        assert_eq!(records[1].get("ok"), Some(&DataValue::Boolean(true)));
+        fs::remove_file(&path).unwrap();
+
+        // Reading stops after `n` records, before the malformed ones
+        let path = write_sample("array.json", b" [{\"id\": 1}, {\"id\": 2}, {oops");
+        let ucdf = parse(&format!("t=file.json;c.path={}", path)).unwrap();
+        assert_eq!(preview(&ucdf, 2).unwrap().len(), 2);
+        assert!(matches!(preview(&ucdf, 3), Err(Error::ParseError(_))));
+        fs::remove_file(&path).unwrap();
+
+        let path = write_sample("lines.json", b"{\"id\": 1}\n\n{oops\n");
+        let ucdf = parse(&format!("t=file.json;c.path={}", path)).unwrap();
+        assert_eq!(preview(&ucdf, 1).unwrap().len(), 1);
+        assert!(preview(&ucdf, 2).is_err());
+        fs::remove_file(&path).unwrap();
+
+        let ucdf = parse("t=db.postgresql;c.host=localhost").unwrap();
+        assert!(matches!(
+            preview(&ucdf, 1),
+            Err(Error::UnsupportedSource(_))
+        ));
+    }
+}
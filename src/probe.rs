@@ -0,0 +1,194 @@
+//! Connectivity checks for data sources
+//!
+//! [`probe`] checks that the server of a network source accepts TCP
+//! connections and that the file of a file source exists, measuring how long
+//! the check takes. It doesn't authenticate or speak the source's protocol.
+
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::UCDF;
+
+/// Default ports of network source subtypes and URL schemes
+const DEFAULT_PORTS: &[(&str, u16)] = &[
+    ("postgresql", 5432),
+    ("postgres", 5432),
+    ("mysql", 3306),
+    ("mariadb", 3306),
+    ("mssql", 1433),
+    ("oracle", 1521),
+    ("mongodb", 27017),
+    ("redis", 6379),
+    ("cassandra", 9042),
+    ("clickhouse", 8123),
+    ("elasticsearch", 9200),
+    ("kafka", 9092),
+    ("rabbitmq", 5672),
+    ("amqp", 5672),
+    ("pulsar", 6650),
+    ("nats", 4222),
+    ("mqtt", 1883),
+    ("http", 80),
+    ("https", 443),
+];
+
+/// Outcome of a successful [`probe`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeReport {
+    /// `host:port` connected to, or the path of a file source
+    pub target: String,
+    pub latency: Duration,
+}
+
+/// Check that a source is reachable within `timeout`
+///
+/// Database, API and stream sources are reached through `c.host`/`c.port`,
+/// `c.url`/`c.uri` or the first of `c.brokers`; the port defaults to the
+/// usual one of the subtype or URL scheme. File and SQLite sources are
+/// reachable when `c.path` exists. Credentials in URLs are left out of the
+/// reported target and errors.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use ucdf::{parse, probe};
+///
+/// let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432").unwrap();
+/// let report = probe(&ucdf, Duration::from_secs(5)).unwrap();
+/// println!("{} answered in {:?}", report.target, report.latency);
+/// ```
+pub fn probe(ucdf: &UCDF, timeout: Duration) -> Result<ProbeReport> {
+    let start = Instant::now();
+    if ucdf.source_type.is_file() || ucdf.source_type.matches("db.sqlite") {
+        let path = ucdf
+            .connection
+            .get("path")
+            .ok_or_else(|| Error::InvalidFormat("file source without c.path".to_string()))?;
+        fs::metadata(path)
+            .map_err(Error::from)
+            .with_context(|| format!("checking {}", path))?;
+        return Ok(ProbeReport {
+            target: path.clone(),
+            latency: start.elapsed(),
+        });
+    }
+
+    let target = target(ucdf)?;
+    let addresses = target
+        .to_socket_addrs()
+        .map_err(Error::from)
+        .with_context(|| format!("resolving {}", target))?;
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(_) => {
+                return Ok(ProbeReport {
+                    target,
+                    latency: start.elapsed(),
+                })
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    let error = last_error
+        .map(Error::from)
+        .unwrap_or_else(|| Error::InvalidFormat("no address found".to_string()));
+    Err(error).with_context(|| format!("connecting to {}", target))
+}
+
+/// Get the `host:port` a network source is reached at
+fn target(ucdf: &UCDF) -> Result<String> {
+    let get = |key: &str| ucdf.connection.get(key).map(String::as_str);
+    let subtype_port = || ucdf.source_type.subtype.as_deref().and_then(default_port);
+
+    let (host, port) = if let Some(host) = get("host") {
+        (host, get("port").map(str::to_string))
+    } else if let Some(url) = get("url").or_else(|| get("uri")) {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| Error::InvalidFormat("URL without scheme".to_string()))?;
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        // Drop credentials, and all hosts but the first of a list
+        let authority = authority.rsplit('@').next().unwrap_or_default();
+        let authority = authority.split(',').next().unwrap_or_default();
+        let host_port = split_port(authority);
+        let port = host_port.1.map(str::to_string).or_else(|| {
+            default_port(scheme.split('+').next().unwrap_or(scheme))
+                .or_else(subtype_port)
+                .map(|port| port.to_string())
+        });
+        (host_port.0, port)
+    } else if let Some(brokers) = get("brokers") {
+        let (host, port) = split_port(brokers.split(',').next().unwrap_or_default().trim());
+        (host, port.map(str::to_string))
+    } else {
+        return Err(Error::UnsupportedSource(format!(
+            "{} (no c.host, c.url, c.uri or c.brokers to probe)",
+            ucdf.source_type
+        )));
+    };
+
+    if host.is_empty() {
+        return Err(Error::InvalidFormat("empty host".to_string()));
+    }
+    let port = port
+        .or_else(|| subtype_port().map(|port| port.to_string()))
+        .ok_or_else(|| Error::InvalidFormat(format!("no port for {}", ucdf.source_type)))?;
+    Ok(format!("{}:{}", host, port))
+}
+
+/// Split `host[:port]`, keeping bracketed IPv6 addresses intact
+fn split_port(authority: &str) -> (&str, Option<&str>) {
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, Some(port)),
+        _ => (authority, None),
+    }
+}
+
+fn default_port(name: &str) -> Option<u16> {
+    DEFAULT_PORTS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, port)| *port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_target() {
+        let target_of = |s: &str| target(&parse(s).unwrap());
+        assert_eq!(target_of("t=db.postgresql;c.host=db1").unwrap(), "db1:5432");
+        assert_eq!(
+            target_of("t=db.mongodb;c.uri=\"mongodb+srv://app:secret@m1,m2/sales\"").unwrap(),
+            "m1:27017"
+        );
+        assert_eq!(
+            target_of("t=api.rest;c.url=\"https://api.example.com/v1\"").unwrap(),
+            "api.example.com:443"
+        );
+        assert_eq!(
+            target_of("t=stream.kafka;c.brokers=\"k1:9093,k2:9093\"").unwrap(),
+            "k1:9093"
+        );
+        assert!(target_of("t=ref;c.target=sales").is_err());
+    }
+
+    #[test]
+    fn test_probe() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let ucdf = parse(&format!("t=db.redis;c.host=127.0.0.1;c.port={}", port)).unwrap();
+        let report = probe(&ucdf, Duration::from_secs(1)).unwrap();
+        assert_eq!(report.target, format!("127.0.0.1:{}", port));
+
+        let ucdf = parse("t=file.csv;c.path=/nonexistent/users.csv").unwrap();
+        assert!(probe(&ucdf, Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::catalog::Catalog;
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::{parse, split_sections, unquote};
+use crate::sections::{quote_structure, quote_value, UCDF};
+
+/// Base descriptor with named per-environment overlays
+///
+/// Each profile (`dev`, `staging`, `prod`, ...) overrides values of the base
+/// descriptor by key path. In the text form, overrides are written as
+/// `key@profile=value` next to the base sections.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::Profiles;
+///
+/// let profiles: Profiles = "t=db.postgresql;c.host=localhost;c.host@prod=db.prod;a@prod=r"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(profiles.base().connection.get("host").unwrap(), "localhost");
+///
+/// let prod = profiles.resolve("prod").unwrap();
+/// assert_eq!(prod.connection.get("host").unwrap(), "db.prod");
+/// assert!(prod.access_mode.unwrap().can_read());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profiles {
+    base: UCDF,
+    overlays: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl Profiles {
+    /// Create profiles from a base descriptor without overlays
+    pub fn new(base: UCDF) -> Self {
+        Profiles {
+            base,
+            overlays: Vec::new(),
+        }
+    }
+
+    /// Get the base descriptor
+    pub fn base(&self) -> &UCDF {
+        &self.base
+    }
+
+    /// Override a key path (`c.host`, `a`, `m.env`, ...) in a profile
+    pub fn set(
+        &mut self,
+        profile: impl Into<String>,
+        path: impl Into<String>,
+        value: impl Into<String>,
+    ) -> &mut Self {
+        let profile = profile.into();
+        let path = path.into();
+        let value = value.into();
+        let overrides = match self.overlays.iter().position(|(name, _)| *name == profile) {
+            Some(i) => &mut self.overlays[i].1,
+            None => {
+                self.overlays.push((profile, Vec::new()));
+                &mut self.overlays.last_mut().unwrap().1
+            }
+        };
+        match overrides.iter_mut().find(|(key, _)| *key == path) {
+            Some(entry) => entry.1 = value,
+            None => overrides.push((path, value)),
+        }
+        self
+    }
+
+    /// Fluent API for overriding a key path in a profile
+    pub fn with(
+        mut self,
+        profile: impl Into<String>,
+        path: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.set(profile, path, value);
+        self
+    }
+
+    /// Iterate over the profile names in order of declaration
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.overlays.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Check if a profile is declared
+    pub fn contains(&self, profile: &str) -> bool {
+        self.overlays.iter().any(|(name, _)| name == profile)
+    }
+
+    /// Resolve the descriptor for a profile by applying its overrides to the base
+    pub fn resolve(&self, profile: &str) -> Result<UCDF> {
+        let (_, overrides) = self
+            .overlays
+            .iter()
+            .find(|(name, _)| name == profile)
+            .ok_or_else(|| Error::UnknownProfile(profile.to_string()))?;
+        let mut ucdf = self.base.clone();
+        for (path, value) in overrides {
+            ucdf.set_path(path, value)
+                .with_context(|| format!("in profile `{}`", profile))?;
+        }
+        Ok(ucdf)
+    }
+
+    /// Resolve every profile into a catalog keyed by profile name
+    pub fn resolve_all(&self) -> Result<Catalog> {
+        self.names()
+            .map(|name| Ok((name.to_string(), self.resolve(name)?)))
+            .collect()
+    }
+}
+
+impl FromStr for Profiles {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut base = Vec::new();
+        let mut overrides = Vec::new();
+        for section in split_sections(s) {
+            match section.split_once('=') {
+                Some((key, value)) if key.contains('@') => {
+                    let (path, profile) = key.rsplit_once('@').unwrap();
+                    if path.is_empty() || profile.is_empty() {
+                        return Err(Error::InvalidSectionFormat(section.to_string()));
+                    }
+                    let value = unquote(value);
+                    overrides.push((profile, path, value));
+                }
+                _ => base.push(section),
+            }
+        }
+
+        let mut profiles = Profiles::new(parse(&base.join(";"))?);
+        for (profile, path, value) in overrides {
+            profiles.set(profile, path, value);
+        }
+        // Surface invalid overrides when parsing rather than when resolving
+        for name in profiles.names() {
+            profiles.resolve(name)?;
+        }
+        Ok(profiles)
+    }
+}
+
+impl fmt::Display for Profiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.base)?;
+        for (profile, overrides) in &self.overlays {
+            for (path, value) in overrides {
+                // Quote values as the base sections are quoted
+                if path.starts_with("s.") {
+                    write!(f, ";{}@{}={}", path, profile, quote_structure(value))?;
+                } else {
+                    write!(f, ";{}@{}={}", path, profile, quote_value(value))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_profiles() {
+        let profiles: Profiles = "t=db.postgresql;c.host=localhost;c.port=5432;a=rw;\
+                                  c.host@staging=db.staging;\
+                                  c.host@prod=db.prod;a@prod=r;m.dsn@prod=\"postgres://db.prod:5432\""
+            .parse()
+            .unwrap();
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            vec!["staging", "prod"]
+        );
+
+        let staging = profiles.resolve("staging").unwrap();
+        assert_eq!(staging.connection.get("host").unwrap(), "db.staging");
+        assert!(staging.access_mode.unwrap().can_write());
+
+        let prod = profiles.resolve("prod").unwrap();
+        assert_eq!(prod.connection.get("port").unwrap(), "5432");
+        assert!(!prod.access_mode.unwrap().can_write());
+        assert_eq!(prod.metadata.get("dsn").unwrap(), "postgres://db.prod:5432");
+
+        let reparsed: Profiles = profiles.to_string().parse().unwrap();
+        assert_eq!(reparsed, profiles);
+        assert_eq!(profiles.resolve_all().unwrap().len(), 2);
+        assert!(matches!(
+            profiles.resolve("dev"),
+            Err(Error::UnknownProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_override() {
+        let err = "t=db.postgresql;a@prod=z".parse::<Profiles>().unwrap_err();
+        assert_eq!(
+            err.contexts().collect::<Vec<_>>(),
+            vec!["in profile `prod`"]
+        );
+        assert!("t=db.postgresql;c.host@=db".parse::<Profiles>().is_err());
+    }
+
+    #[test]
+    fn test_quoted_overrides() {
+        let mut profiles: Profiles = "t=file.csv;c.path=/data/a.csv".parse().unwrap();
+        profiles
+            .set("prod", "s.format", "x;y=z")
+            .set("prod", "s.fields", "id:int,name:str")
+            .set("prod", "m.desc", "orders; \"daily\"");
+        let s = profiles.to_string();
+        assert_eq!(
+            s,
+            r#"t=file.csv;c.path=/data/a.csv;s.format@prod="x;y=z";s.fields@prod=id:int,name:str;m.desc@prod="orders; \"daily\"""#
+        );
+        let reparsed: Profiles = s.parse().unwrap();
+        assert_eq!(reparsed, profiles);
+        let prod = reparsed.resolve("prod").unwrap();
+        assert_eq!(prod.structure["format"].as_format(), Some("x;y=z"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Java properties export
+//!
+//! [`UCDF::to_spring_properties`] writes a database descriptor as the
+//! `spring.datasource.*` settings of a Spring Boot application, and
+//! [`UCDF::to_properties`] writes any descriptor as flat `key=value` lines
+//! (`type`, `connection.<key>`, `structure.<key>`, `access_mode` and
+//! `metadata.<key>`). Keys and values are escaped as
+//! `java.util.Properties` reads them, non-ASCII characters as `\uXXXX`.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432;c.db=sales;c.user=app;c.password=${PG_PASSWORD}").unwrap();
+//! assert_eq!(
+//!     ucdf.to_spring_properties().unwrap(),
+//!     "spring.datasource.url=jdbc:postgresql://db1:5432/sales
+//! spring.datasource.username=app
+//! spring.datasource.password=${PG_PASSWORD}
+//! spring.datasource.driver-class-name=org.postgresql.Driver
+//! "
+//! );
+//! ```
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+// Subtypes and JDBC driver class
+const DRIVERS: &[(&[&str], &str)] = &[
+    (&["postgresql", "postgres"], "org.postgresql.Driver"),
+    (&["mysql"], "com.mysql.cj.jdbc.Driver"),
+    (&["mariadb"], "org.mariadb.jdbc.Driver"),
+    (
+        &["mssql", "sqlserver"],
+        "com.microsoft.sqlserver.jdbc.SQLServerDriver",
+    ),
+    (&["oracle"], "oracle.jdbc.OracleDriver"),
+    (&["clickhouse"], "com.clickhouse.jdbc.ClickHouseDriver"),
+    (&["h2"], "org.h2.Driver"),
+    (&["sqlite"], "org.sqlite.JDBC"),
+];
+
+impl UCDF {
+    /// Write the descriptor as flat Java properties
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=file.csv;c.path=\"/data/users 2024.csv\";s.fields=id:int;a=r").unwrap();
+    /// assert_eq!(
+    ///     ucdf.to_properties(),
+    ///     "type=file.csv\nconnection.path=/data/users 2024.csv\nstructure.fields=id:int\naccess_mode=r\n"
+    /// );
+    /// ```
+    pub fn to_properties(&self) -> String {
+        let mut out = String::new();
+        let mut property = |key: &str, value: &str| {
+            out.push_str(&format!("{}={}\n", escape(key, true), escape(value, false)));
+        };
+        property("type", &self.source_type.to_string());
+        for (key, value) in self.connection.iter() {
+            property(&format!("connection.{}", key), value);
+        }
+        for (key, data) in self.structure.iter() {
+            property(&format!("structure.{}", key), &data.to_string());
+        }
+        if let Some(mode) = self.access_mode {
+            property("access_mode", &mode.to_string());
+        }
+        for (key, value) in self.metadata.iter() {
+            property(&format!("metadata.{}", key), value);
+        }
+        out
+    }
+
+    /// Write a database descriptor as Spring Boot `spring.datasource.*` properties
+    ///
+    /// The URL is the descriptor's JDBC URL without credentials, which go to
+    /// `username` and `password`. `${VAR}` references are kept, as Spring
+    /// resolves them itself, and `env:VAR` references are written as
+    /// `${VAR}`. `driver-class-name` is set for engines with a well-known
+    /// driver.
+    pub fn to_spring_properties(&self) -> Result<String> {
+        if !self.source_type.is_db() {
+            return Err(Error::UnsupportedSource(format!(
+                "{} (Spring datasources require a db.* source)",
+                self.source_type
+            )));
+        }
+        let mut without_credentials = self.clone();
+        let user = without_credentials.connection.remove("user");
+        let password = without_credentials.connection.remove("password");
+
+        let mut out = String::new();
+        let mut property = |name: &str, value: &str| {
+            out.push_str(&format!(
+                "spring.datasource.{}={}\n",
+                name,
+                escape(value, false)
+            ));
+        };
+        property("url", &without_credentials.to_jdbc()?);
+        if let Some(user) = user {
+            property("username", &placeholder(&user));
+        }
+        if let Some(password) = password {
+            property("password", &placeholder(&password));
+        }
+        let subtype = self.source_type.subtype.as_deref().unwrap_or_default();
+        if let Some((_, driver)) = DRIVERS
+            .iter()
+            .find(|(subtypes, _)| subtypes.contains(&subtype))
+        {
+            property("driver-class-name", driver);
+        }
+        Ok(out)
+    }
+}
+
+// Spring placeholder for `env:VAR` references, other values as written
+fn placeholder(value: &str) -> String {
+    match value.strip_prefix("env:") {
+        Some(var) => format!("${{{}}}", var),
+        None => value.to_string(),
+    }
+}
+
+// Escape a properties key or value; keys also escape separators, comment
+// characters and spaces, values only a leading space
+fn escape(s: &str, key: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\x0c' => escaped.push_str("\\f"),
+            '=' | ':' | '#' | '!' if key => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' ' if key || i == 0 => escaped.push_str("\\ "),
+            c if !c.is_ascii() || c.is_ascii_control() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_properties() {
+        let ucdf = parse(
+            "t=db.mysql;c.host=db1;c.db=shop;c.user=env:DB_USER;c.params.useSSL=true;m.owner=\"Zoë # ops\"",
+        )
+        .unwrap();
+        assert_eq!(
+            ucdf.to_spring_properties().unwrap(),
+            "spring.datasource.url=jdbc:mysql://db1/shop?useSSL=true
+spring.datasource.username=${DB_USER}
+spring.datasource.driver-class-name=com.mysql.cj.jdbc.Driver
+"
+        );
+        assert_eq!(
+            ucdf.to_properties(),
+            "type=db.mysql
+connection.host=db1
+connection.db=shop
+connection.user=env:DB_USER
+connection.params.useSSL=true
+metadata.owner=Zo\\u00EB # ops
+"
+        );
+        assert!(parse("t=file.csv;c.path=/a.csv")
+            .unwrap()
+            .to_spring_properties()
+            .is_err());
+    }
+}
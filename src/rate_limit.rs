@@ -0,0 +1,270 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// Metadata key declaring the rate limit, e.g. `m.rate_limit=100/minute`
+pub const RATE_LIMIT_KEY: &str = "rate_limit";
+
+// Window unit names and their length in seconds
+const UNITS: &[(&[&str], u64)] = &[
+    (&["second", "sec", "s"], 1),
+    (&["minute", "min", "m"], 60),
+    (&["hour", "h"], 3600),
+    (&["day", "d"], 86400),
+];
+
+/// Request rate limit of a source, for throttling clients
+///
+/// The limit is read from `m.rate_limit=<requests>/<window>`, and from the
+/// `c.rate.*` keys, which override it:
+///
+/// | Key               | Setting                           |
+/// |-------------------|-----------------------------------|
+/// | `c.rate.requests` | requests allowed per window       |
+/// | `c.rate.window`   | window length                     |
+/// | `c.rate.burst`    | requests allowed at once, at most |
+///
+/// Windows are a unit (`second`, `minute`, `hour`, `day`, their plurals,
+/// or `s`, `m`, `h`, `d`), optionally preceded by a count (`15m`), or a
+/// number of seconds (`90`, `0.5`).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use ucdf::parse;
+///
+/// let ucdf = parse("t=api.rest;c.url=\"https://api.example.com\";m.rate_limit=100/minute;c.rate.burst=10").unwrap();
+/// let limit = ucdf.rate_limit().unwrap().unwrap();
+/// assert_eq!(limit.requests, 100);
+/// assert_eq!(limit.window, Duration::from_secs(60));
+/// assert_eq!(limit.burst, Some(10));
+/// assert_eq!(limit.interval(), Duration::from_millis(600));
+/// assert_eq!(limit.to_string(), "100/minute");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window: Duration,
+    pub burst: Option<u32>,
+}
+
+impl RateLimit {
+    /// Create a rate limit of `requests` per `window`, without burst
+    pub fn new(requests: u32, window: Duration) -> Self {
+        RateLimit {
+            requests,
+            window,
+            burst: None,
+        }
+    }
+
+    /// Set the burst size
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = Some(burst);
+        self
+    }
+
+    /// Average number of requests allowed per second
+    pub fn per_second(&self) -> f64 {
+        f64::from(self.requests) / self.window.as_secs_f64()
+    }
+
+    /// Time between requests sent at the steady rate
+    pub fn interval(&self) -> Duration {
+        self.window / self.requests.max(1)
+    }
+
+    /// Requests allowed at once: the burst size, or a whole window's worth
+    pub fn capacity(&self) -> u32 {
+        self.burst.unwrap_or(self.requests)
+    }
+
+    /// Check that requests, window and burst are all positive
+    pub fn validate(&self) -> Result<()> {
+        if self.requests == 0 {
+            return Err(Error::InvalidFormat(
+                "rate limit must allow at least one request".to_string(),
+            ));
+        }
+        if self.window.is_zero() {
+            return Err(Error::InvalidFormat(
+                "rate limit window must not be empty".to_string(),
+            ));
+        }
+        if self.burst == Some(0) {
+            return Err(Error::InvalidFormat(
+                "rate limit burst must be at least one request".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = Error;
+
+    /// Parse `<requests>/<window>`, e.g. `100/minute` or `5/10s`
+    fn from_str(s: &str) -> Result<Self> {
+        let (requests, window) = s.split_once('/').ok_or_else(|| {
+            Error::InvalidFormat(format!("expected `<requests>/<window>`: {}", s))
+        })?;
+        let requests = requests
+            .trim()
+            .parse()
+            .map_err(|_| Error::InvalidFormat(format!("invalid request count: {}", requests)))?;
+        let limit = RateLimit::new(requests, parse_window(window)?);
+        limit.validate()?;
+        Ok(limit)
+    }
+}
+
+impl fmt::Display for RateLimit {
+    /// Write `<requests>/<window>`, naming the window's unit where it is one
+    /// unit long
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/", self.requests)?;
+        if self.window.subsec_nanos() != 0 {
+            return write!(f, "{}s", self.window.as_secs_f64());
+        }
+        let secs = self.window.as_secs();
+        let (names, len) = UNITS
+            .iter()
+            .rev()
+            .find(|(_, len)| secs.is_multiple_of(*len))
+            .unwrap_or(&UNITS[0]);
+        match secs / len {
+            1 => write!(f, "{}", names[0]),
+            count => write!(f, "{}{}", count, names[names.len() - 1]),
+        }
+    }
+}
+
+// Parse a window: a unit with an optional count, or a number of seconds
+fn parse_window(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let invalid = || Error::InvalidFormat(format!("invalid rate limit window: {}", s));
+    let split = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (count, unit) = s.split_at(split);
+    let count: f64 = match count.trim() {
+        "" if !unit.is_empty() => 1.0,
+        count => count.parse().map_err(|_| invalid())?,
+    };
+    let unit_secs = match unit {
+        "" => 1,
+        unit => {
+            let unit = unit.to_ascii_lowercase();
+            // Plural names, leaving `s` itself alone
+            let singular = match unit.strip_suffix('s') {
+                Some(singular) if singular.len() > 1 => singular,
+                _ => &unit,
+            };
+            UNITS
+                .iter()
+                .find(|(names, _)| names.contains(&unit.as_str()) || names.contains(&singular))
+                .map(|(_, len)| *len)
+                .ok_or_else(invalid)?
+        }
+    };
+    Duration::try_from_secs_f64(count * unit_secs as f64).map_err(|_| invalid())
+}
+
+impl UCDF {
+    /// Read the rate limit from `m.rate_limit` and the `c.rate.*` keys
+    ///
+    /// Returns `None` if neither is set, and an error if the limit is
+    /// incomplete or invalid.
+    pub fn rate_limit(&self) -> Result<Option<RateLimit>> {
+        let declared = self
+            .metadata
+            .get(RATE_LIMIT_KEY)
+            .map(|value| value.parse::<RateLimit>())
+            .transpose()
+            .map_err(|e| e.context(format!("m.{}", RATE_LIMIT_KEY)))?;
+        let get = |name: &str| self.connection.get(&format!("rate.{}", name));
+        let number = |name: &str| -> Result<Option<u32>> {
+            get(name)
+                .map(|value| {
+                    value.parse().map_err(|_| {
+                        Error::InvalidFormat(format!(
+                            "c.rate.{}: expected a number, got `{}`",
+                            name, value
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        let requests = number("requests")?;
+        let window = get("window")
+            .map(|window| parse_window(window).map_err(|e| e.context("c.rate.window")))
+            .transpose()?;
+        let burst = number("burst")?;
+
+        let requests = requests.or(declared.map(|limit| limit.requests));
+        let window = window.or(declared.map(|limit| limit.window));
+        let limit = match (requests, window) {
+            (Some(requests), Some(window)) => RateLimit {
+                requests,
+                window,
+                burst,
+            },
+            (None, None) if burst.is_none() => return Ok(None),
+            _ => {
+                return Err(Error::InvalidFormat(format!(
+                    "rate limit needs a request count and a window, set m.{} or c.rate.requests and c.rate.window",
+                    RATE_LIMIT_KEY
+                )))
+            }
+        };
+        limit.validate()?;
+        Ok(Some(limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_rate_limit() {
+        let limit = |s: &str| parse(&format!("t=api.rest;{}", s)).unwrap().rate_limit();
+        assert_eq!(limit("c.url=x").unwrap(), None);
+        assert_eq!(
+            limit("c.rate.requests=5;c.rate.window=0.5;c.rate.burst=2").unwrap(),
+            Some(RateLimit::new(5, Duration::from_millis(500)).with_burst(2))
+        );
+        assert_eq!(
+            limit("m.rate_limit=1000/hour;c.rate.window=15m").unwrap(),
+            Some(RateLimit::new(1000, Duration::from_secs(900)))
+        );
+        for invalid in [
+            "m.rate_limit=100",
+            "m.rate_limit=0/s",
+            "m.rate_limit=10/fortnight",
+            "c.rate.requests=10",
+            "c.rate.burst=5",
+            "m.rate_limit=10/s;c.rate.burst=0",
+            "c.rate.requests=ten;c.rate.window=s",
+        ] {
+            assert!(limit(invalid).is_err(), "{}", invalid);
+        }
+
+        for (text, written) in [
+            ("10/s", "10/second"),
+            ("10/60", "10/minute"),
+            ("3/ 2 Hours", "3/2h"),
+            ("3/2hour", "3/2h"),
+            ("50/90s", "50/90s"),
+            ("7/1.5", "7/1.5s"),
+            ("1/day", "1/day"),
+        ] {
+            let parsed: RateLimit = text.parse().unwrap_or_else(|e| panic!("{}: {}", text, e));
+            assert_eq!(parsed.to_string(), written);
+            assert_eq!(written.parse::<RateLimit>().unwrap(), parsed);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::catalog::Catalog;
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::{KeyPath, UCDF};
+
+/// Source type category of reference descriptors (`t=ref;c.target=<name>`)
+pub const REF_CATEGORY: &str = "ref";
+
+impl UCDF {
+    /// Check if this is a reference to another catalog entry (`t=ref`)
+    pub fn is_ref(&self) -> bool {
+        self.source_type.category == REF_CATEGORY
+    }
+
+    /// Get the catalog entry a reference points at (`c.target`)
+    pub fn ref_target(&self) -> Option<&str> {
+        if self.is_ref() {
+            self.connection.get("target").map(String::as_str)
+        } else {
+            None
+        }
+    }
+
+    /// Override sections with those of `other`
+    ///
+    /// Connection, structure, metadata and custom section entries of `other`
+    /// replace entries with the same key, as do its section access modes, and
+    /// its [cleared](UCDF::cleared) connection and metadata keys are removed;
+    /// its access mode and encrypted connection replace
+    /// these sections if present. The source type is kept.
+    pub fn merge(&mut self, other: &UCDF) -> &mut Self {
+        for (key, value) in other.connection.iter() {
+            let path = KeyPath::Connection(key);
+            self.cleared
+                .retain(|cleared| KeyPath::parse(cleared) != Some(path));
+            if value.is_empty() && other.is_cleared(path) {
+                self.connection.remove(key);
+            } else {
+                self.connection.insert(key, value);
+            }
+        }
+        for (key, data) in &other.structure {
+            self.structure.insert(key.clone(), data.clone());
+        }
+        if other.access_mode.is_some() {
+            self.access_mode = other.access_mode;
+        }
+        for (key, mode) in &other.section_access {
+            self.section_access.insert(key.clone(), *mode);
+        }
+        for (key, value) in other.metadata.iter() {
+            let path = KeyPath::Meta(key);
+            self.cleared
+                .retain(|cleared| KeyPath::parse(cleared) != Some(path));
+            if value.is_empty() && other.is_cleared(path) {
+                self.metadata.remove(key);
+            } else {
+                self.metadata.insert(key, value);
+            }
+        }
+        if other.encrypted_connection.is_some() {
+            self.encrypted_connection = other.encrypted_connection.clone();
+        }
+        for (key, value) in other.extensions.iter() {
+            self.extensions
+                .insert_boxed(key.to_string(), value.clone_box());
+        }
+        self
+    }
+}
+
+impl Catalog {
+    /// Expand reference descriptors into the descriptors they point at
+    ///
+    /// A reference `t=ref;c.target=<name>` resolves to a copy of the target
+    /// entry, overridden by the reference's own sections other than `t` and
+    /// `c.target`. References may point at other references. Fails on
+    /// unknown targets and reference cycles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::Catalog;
+    ///
+    /// let catalog: Catalog = "warehouse = t=db.postgresql;c.host=dw.internal;c.db=dw\n\
+    ///                         orders = t=ref;c.target=warehouse;c.table=orders;a=r"
+    ///     .parse()
+    ///     .unwrap();
+    /// let resolved = catalog.resolve_refs().unwrap();
+    /// let orders = resolved.get("orders").unwrap();
+    /// assert_eq!(orders.source_type.to_string(), "db.postgresql");
+    /// assert_eq!(orders.connection.get("host").unwrap(), "dw.internal");
+    /// assert_eq!(orders.connection.get("table").unwrap(), "orders");
+    /// ```
+    pub fn resolve_refs(&self) -> Result<Catalog> {
+        let mut resolved = self.clone();
+        for (name, _) in self.iter() {
+            let ucdf = self
+                .resolve_ref(name, &mut Vec::new())
+                .with_context(|| format!("resolving `{}`", name))?;
+            if let Some(entry) = resolved.get_mut(name) {
+                *entry = ucdf;
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn resolve_ref<'a>(&'a self, name: &'a str, chain: &mut Vec<&'a str>) -> Result<UCDF> {
+        if chain.contains(&name) {
+            chain.push(name);
+            return Err(Error::ReferenceCycle(chain.join(" -> ")));
+        }
+        let ucdf = self
+            .get(name)
+            .ok_or_else(|| Error::UnresolvedReference(name.to_string()))?;
+        if !ucdf.is_ref() {
+            return Ok(ucdf.clone());
+        }
+        let target = ucdf.ref_target().ok_or_else(|| {
+            Error::InvalidFormat(format!("reference `{}` without c.target", name))
+        })?;
+
+        chain.push(name);
+        let mut resolved = self.resolve_ref(target, chain)?;
+        let mut overrides = ucdf.clone();
+        overrides.connection.remove("target");
+        resolved.merge(&overrides);
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_resolve_ref_chain() {
+        let catalog: Catalog = "db = t=db.postgresql;c.host=db1;c.db=app;a=rw\n\
+                                orders = t=ref;c.target=db;c.table=orders\n\
+                                orders_ro = t=ref;c.target=orders;a=r;m.desc=read only"
+            .parse()
+            .unwrap();
+        let resolved = catalog.resolve_refs().unwrap();
+        let orders_ro = resolved.get("orders_ro").unwrap();
+        assert_eq!(
+            orders_ro.to_canonical_string(),
+            "t=db.postgresql;c.db=app;c.host=db1;c.table=orders;a=r;m.desc=read only"
+        );
+        assert!(!resolved.iter().any(|(_, ucdf)| ucdf.is_ref()));
+    }
+
+    #[test]
+    fn test_merge_cleared() {
+        let mut base =
+            parse("t=db.mysql;c.host=db1;c.password=pw;c.user=app;m.owner=data").unwrap();
+        let overlay = parse("t=db.mysql;c.password=;c.user=\"\";m.team=").unwrap();
+        base.merge(&overlay);
+        assert_eq!(
+            base.to_string(),
+            "t=db.mysql;c.host=db1;c.user=\"\";m.owner=data"
+        );
+        assert!(base.cleared.is_empty());
+    }
+
+    #[test]
+    fn test_ref_errors() {
+        let catalog: Catalog = "a = t=ref;c.target=b\nb = t=ref;c.target=a"
+            .parse()
+            .unwrap();
+        let err = catalog.resolve_refs().unwrap_err();
+        assert!(matches!(err.root_cause(), Error::ReferenceCycle(chain) if chain == "a -> b -> a"));
+
+        let catalog: Catalog = "a = t=ref;c.target=missing".parse().unwrap();
+        let err = catalog.resolve_refs().unwrap_err();
+        assert!(matches!(err.root_cause(), Error::UnresolvedReference(_)));
+    }
+}
@@ -0,0 +1,295 @@
+//! Client for a central UCDF registry over HTTP
+//!
+//! # Protocol
+//!
+//! A registry serves JSON over HTTP below a base URL. Descriptors are
+//! identified by names made of ASCII letters, digits, `.`, `_` and `-`, and
+//! carry the SHA-256 hex digest of their canonical form
+//! ([`UCDF::to_canonical_string`]) so clients can detect corruption.
+//!
+//! | Request | Response |
+//! |---------|----------|
+//! | `GET {base}/descriptors/{name}` | `200` with an entry, `404` if unknown |
+//! | `PUT {base}/descriptors/{name}` with an entry (`name` optional) | `200` or `201` |
+//! | `GET {base}/descriptors?filter={pattern}` | `200` with `{"descriptors": [entry, ...]}` |
+//!
+//! An entry is `{"name": "sales", "ucdf": "t=db.postgresql;...", "hash": "9f86d0..."}`.
+//! The optional `filter` is a [`UcdfPattern`] in its string form; servers
+//! may ignore it, since the client applies it to the returned entries as well.
+//! Any other status is reported as [`Error::Registry`].
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::catalog::Catalog;
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::parse;
+use crate::pattern::UcdfPattern;
+use crate::sections::UCDF;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    ucdf: String,
+    hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntryList {
+    descriptors: Vec<Entry>,
+}
+
+/// Blocking client for a UCDF registry
+///
+/// # Examples
+///
+/// ```no_run
+/// use ucdf::registry::Registry;
+///
+/// let registry = Registry::new("https://ucdf.example.com/api");
+/// if let Some(sales) = registry.fetch("sales").unwrap() {
+///     println!("{}", sales);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Registry {
+    base: String,
+    agent: ureq::Agent,
+}
+
+impl Registry {
+    /// Create a client for the registry at `base`
+    pub fn new(base: impl Into<String>) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Registry {
+            base: base.into().trim_end_matches('/').to_string(),
+            agent,
+        }
+    }
+
+    /// Fetch a descriptor by name, returning `None` if the registry does not know it
+    pub fn fetch(&self, name: &str) -> Result<Option<UCDF>> {
+        let url = self.url(name)?;
+        let mut response = self.agent.get(&url).call().map_err(http_error)?;
+        match response.status().as_u16() {
+            200 => {
+                let body = response.body_mut().read_to_string().map_err(http_error)?;
+                let entry: Entry = serde_json::from_str(&body).map_err(json_error)?;
+                entry
+                    .into_ucdf()
+                    .map(Some)
+                    .with_context(|| format!("fetching `{}`", name))
+            }
+            404 => Ok(None),
+            status => Err(Error::Registry(format!("GET {} returned {}", url, status))),
+        }
+    }
+
+    /// Publish a descriptor under a name, replacing any previous version
+    pub fn publish(&self, name: &str, ucdf: &UCDF) -> Result<()> {
+        let url = self.url(name)?;
+        let entry = Entry {
+            name: Some(name.to_string()),
+            ucdf: ucdf.to_canonical_string(),
+            hash: canonical_hash(ucdf),
+        };
+        let body = serde_json::to_string(&entry).map_err(json_error)?;
+        let response = self
+            .agent
+            .put(&url)
+            .header("Content-Type", "application/json")
+            .send(body)
+            .map_err(http_error)?;
+        match response.status().as_u16() {
+            200 | 201 => Ok(()),
+            status => Err(Error::Registry(format!("PUT {} returned {}", url, status))),
+        }
+    }
+
+    /// List the descriptors matching a pattern
+    pub fn list(&self, filter: &UcdfPattern) -> Result<Catalog> {
+        let url = format!("{}/descriptors", self.base);
+        let mut request = self.agent.get(&url);
+        if *filter != UcdfPattern::new() {
+            request = request.query("filter", filter.to_string());
+        }
+        let mut response = request.call().map_err(http_error)?;
+        if response.status() != 200 {
+            return Err(Error::Registry(format!(
+                "GET {} returned {}",
+                url,
+                response.status().as_u16()
+            )));
+        }
+        let body = response.body_mut().read_to_string().map_err(http_error)?;
+        let list: EntryList = serde_json::from_str(&body).map_err(json_error)?;
+
+        let mut catalog = Catalog::new();
+        for entry in list.descriptors {
+            let name = entry
+                .name
+                .clone()
+                .ok_or_else(|| Error::Registry("listed entry without a name".to_string()))?;
+            let ucdf = entry
+                .into_ucdf()
+                .with_context(|| format!("listing `{}`", name))?;
+            if filter.matches(&ucdf) {
+                catalog.add(name, ucdf);
+            }
+        }
+        Ok(catalog)
+    }
+
+    fn url(&self, name: &str) -> Result<String> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+        if !valid {
+            return Err(Error::Registry(format!(
+                "invalid descriptor name: `{}`",
+                name
+            )));
+        }
+        Ok(format!("{}/descriptors/{}", self.base, name))
+    }
+}
+
+impl Entry {
+    // Parse the descriptor and check it against the transmitted hash
+    fn into_ucdf(self) -> Result<UCDF> {
+        let ucdf = parse(&self.ucdf)?;
+        if canonical_hash(&ucdf) != self.hash {
+            return Err(Error::Registry(
+                "hash does not match descriptor".to_string(),
+            ));
+        }
+        Ok(ucdf)
+    }
+}
+
+/// SHA-256 hex digest of a descriptor's canonical form, as used by the registry protocol
+pub fn canonical_hash(ucdf: &UCDF) -> String {
+    Sha256::digest(ucdf.to_canonical_string().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn http_error(e: ureq::Error) -> Error {
+    Error::Registry(e.to_string())
+}
+
+fn json_error(e: serde_json::Error) -> Error {
+    Error::Registry(format!("invalid response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Serve one canned response per request, returning the received request lines
+    fn serve(responses: Vec<(u16, String)>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((key, value)) = header.split_once(':') {
+                        if key.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut content = vec![0; length];
+                reader.read_exact(&mut content).unwrap();
+                requests.push(format!(
+                    "{}{}",
+                    line.trim(),
+                    String::from_utf8(content).unwrap()
+                ));
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (base, handle)
+    }
+
+    #[test]
+    fn test_registry_protocol() {
+        let sales = parse("t=db.postgresql;c.host=db1;m.env=prod").unwrap();
+        let users = parse("t=file.csv;c.path=/data/users.csv;m.env=dev").unwrap();
+        let entry = |name: &str, ucdf: &UCDF| {
+            format!(
+                "{{\"name\":\"{}\",\"ucdf\":\"{}\",\"hash\":\"{}\"}}",
+                name,
+                ucdf.to_canonical_string(),
+                canonical_hash(ucdf)
+            )
+        };
+        let (base, server) = serve(vec![
+            (200, entry("sales", &sales)),
+            (404, String::new()),
+            (201, String::new()),
+            (
+                200,
+                format!(
+                    "{{\"descriptors\":[{},{}]}}",
+                    entry("sales", &sales),
+                    entry("users", &users)
+                ),
+            ),
+            (200, entry("sales", &users)),
+        ]);
+
+        let registry = Registry::new(base);
+        assert_eq!(registry.fetch("sales").unwrap(), Some(sales.clone()));
+        assert_eq!(registry.fetch("missing").unwrap(), None);
+        registry.publish("users", &users).unwrap();
+        let listed = registry.list(&"m.env=prod".parse().unwrap()).unwrap();
+        assert_eq!(listed.names().collect::<Vec<_>>(), vec!["sales"]);
+        assert!(registry.fetch("bad name").is_err());
+        assert_eq!(registry.fetch("sales").unwrap(), Some(users.clone()));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0], "GET /descriptors/sales HTTP/1.1");
+        assert!(requests[2].starts_with("PUT /descriptors/users HTTP/1.1{\"name\":\"users\""));
+        assert_eq!(requests[3], "GET /descriptors?filter=m.env%3Dprod HTTP/1.1");
+    }
+
+    #[test]
+    fn test_hash_mismatch() {
+        let ucdf = parse("t=db.postgresql;c.host=db1").unwrap();
+        let entry = Entry {
+            name: None,
+            ucdf: ucdf.to_canonical_string(),
+            hash: canonical_hash(&parse("t=db.postgresql;c.host=evil").unwrap()),
+        };
+        assert!(matches!(entry.into_ucdf(), Err(Error::Registry(_))));
+    }
+}
@@ -0,0 +1,280 @@
+//! Dispatch from descriptors to live source handles
+//!
+//! This crate does not connect to anything itself. Connector crates implement
+//! [`SourceResolver`] for the sources they support and register it in a
+//! [`ResolverRegistry`], which validates descriptors and dispatches them by
+//! source type.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::resolve::{ResolverRegistry, SourceResolver};
+//! use ucdf::{parse, Result, UCDF};
+//!
+//! struct CsvPath;
+//!
+//! impl SourceResolver for CsvPath {
+//!     type Handle = String;
+//!
+//!     fn resolve(&self, ucdf: &UCDF) -> Result<String> {
+//!         Ok(ucdf.connection.get("path").cloned().unwrap_or_default())
+//!     }
+//! }
+//!
+//! let registry = ResolverRegistry::new().register("file", CsvPath);
+//! let handle = registry.resolve(&parse("t=file.csv;c.path=/data/users.csv").unwrap());
+//! assert_eq!(handle.unwrap(), "/data/users.csv");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::UCDF;
+use crate::validate::Validator;
+
+/// Turns a descriptor into a live handle, such as a connection or a reader
+pub trait SourceResolver {
+    /// Handle produced for a resolved descriptor
+    type Handle;
+
+    /// Resolve a descriptor into a handle
+    fn resolve(&self, ucdf: &UCDF) -> Result<Self::Handle>;
+}
+
+/// Resolvers keyed by source category (`db`) or full source type (`db.postgresql`)
+///
+/// All resolvers in a registry produce the same handle type; use an enum or a
+/// boxed trait object to combine different connectors.
+pub struct ResolverRegistry<H> {
+    resolvers: HashMap<String, Box<dyn SourceResolver<Handle = H>>>,
+    validator: Option<Validator>,
+}
+
+impl<H> ResolverRegistry<H> {
+    /// Create an empty registry without validation
+    pub fn new() -> Self {
+        ResolverRegistry {
+            resolvers: HashMap::new(),
+            validator: None,
+        }
+    }
+
+    /// Register a resolver for a category (`db`) or a full source type (`db.postgresql`)
+    ///
+    /// A resolver registered for the full source type takes precedence over
+    /// one registered for its category.
+    pub fn register(
+        mut self,
+        source_type: impl Into<String>,
+        resolver: impl SourceResolver<Handle = H> + 'static,
+    ) -> Self {
+        self.resolvers
+            .insert(source_type.into(), Box::new(resolver));
+        self
+    }
+
+    /// Validate descriptors before resolving them
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Check if a resolver is registered for the descriptor's source type
+    pub fn supports(&self, ucdf: &UCDF) -> bool {
+        self.find(ucdf).is_some()
+    }
+
+    /// Validate a descriptor and resolve it with the matching resolver
+    pub fn resolve(&self, ucdf: &UCDF) -> Result<H> {
+        let resolver = self
+            .find(ucdf)
+            .ok_or_else(|| Error::NoResolver(ucdf.source_type.to_string()))?;
+        if let Some(validator) = &self.validator {
+            let report = validator.validate(ucdf);
+            if !report.is_valid() {
+                return Err(Error::Validation(report.errors().cloned().collect()));
+            }
+        }
+        resolver
+            .resolve(ucdf)
+            .with_context(|| format!("resolving {}", ucdf.source_type))
+    }
+
+    fn find(&self, ucdf: &UCDF) -> Option<&dyn SourceResolver<Handle = H>> {
+        self.resolvers
+            .get(&ucdf.source_type.to_string())
+            .or_else(|| self.resolvers.get(&ucdf.source_type.category))
+            .map(Box::as_ref)
+    }
+}
+
+impl<H> Default for ResolverRegistry<H> {
+    fn default() -> Self {
+        ResolverRegistry::new()
+    }
+}
+
+impl<H> fmt::Debug for ResolverRegistry<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&String> = self.resolvers.keys().collect();
+        keys.sort();
+        f.debug_struct("ResolverRegistry")
+            .field("resolvers", &keys)
+            .field("validator", &self.validator)
+            .finish()
+    }
+}
+
+#[cfg(feature = "with-async")]
+pub use self::asynchronous::{AsyncResolverRegistry, AsyncSourceResolver};
+
+#[cfg(feature = "with-async")]
+mod asynchronous {
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    use crate::error::{Error, Result, ResultExt};
+    use crate::sections::UCDF;
+    use crate::validate::Validator;
+
+    type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+    /// Asynchronous counterpart of [`SourceResolver`](super::SourceResolver)
+    pub trait AsyncSourceResolver: Send + Sync {
+        /// Handle produced for a resolved descriptor
+        type Handle;
+
+        /// Resolve a descriptor into a handle
+        fn resolve(&self, ucdf: &UCDF) -> impl Future<Output = Result<Self::Handle>> + Send;
+    }
+
+    // Object-safe form of `AsyncSourceResolver` used for storage in the registry
+    trait DynAsyncSourceResolver<H>: Send + Sync {
+        fn resolve_boxed<'a>(&'a self, ucdf: &'a UCDF) -> BoxFuture<'a, Result<H>>;
+    }
+
+    impl<R: AsyncSourceResolver> DynAsyncSourceResolver<R::Handle> for R {
+        fn resolve_boxed<'a>(&'a self, ucdf: &'a UCDF) -> BoxFuture<'a, Result<R::Handle>> {
+            Box::pin(self.resolve(ucdf))
+        }
+    }
+
+    /// Asynchronous counterpart of [`ResolverRegistry`](super::ResolverRegistry)
+    pub struct AsyncResolverRegistry<H> {
+        resolvers: HashMap<String, Box<dyn DynAsyncSourceResolver<H>>>,
+        validator: Option<Validator>,
+    }
+
+    impl<H> AsyncResolverRegistry<H> {
+        /// Create an empty registry without validation
+        pub fn new() -> Self {
+            AsyncResolverRegistry {
+                resolvers: HashMap::new(),
+                validator: None,
+            }
+        }
+
+        /// Register a resolver for a category (`db`) or a full source type (`db.postgresql`)
+        pub fn register(
+            mut self,
+            source_type: impl Into<String>,
+            resolver: impl AsyncSourceResolver<Handle = H> + 'static,
+        ) -> Self {
+            self.resolvers
+                .insert(source_type.into(), Box::new(resolver));
+            self
+        }
+
+        /// Validate descriptors before resolving them
+        pub fn validator(mut self, validator: Validator) -> Self {
+            self.validator = Some(validator);
+            self
+        }
+
+        /// Validate a descriptor and resolve it with the matching resolver
+        pub async fn resolve(&self, ucdf: &UCDF) -> Result<H> {
+            let resolver = self
+                .resolvers
+                .get(&ucdf.source_type.to_string())
+                .or_else(|| self.resolvers.get(&ucdf.source_type.category))
+                .ok_or_else(|| Error::NoResolver(ucdf.source_type.to_string()))?;
+            if let Some(validator) = &self.validator {
+                let report = validator.validate(ucdf);
+                if !report.is_valid() {
+                    return Err(Error::Validation(report.errors().cloned().collect()));
+                }
+            }
+            resolver
+                .resolve_boxed(ucdf)
+                .await
+                .with_context(|| format!("resolving {}", ucdf.source_type))
+        }
+    }
+
+    impl<H> Default for AsyncResolverRegistry<H> {
+        fn default() -> Self {
+            AsyncResolverRegistry::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    struct Named(&'static str);
+
+    impl SourceResolver for Named {
+        type Handle = String;
+
+        fn resolve(&self, ucdf: &UCDF) -> Result<String> {
+            Ok(format!("{} {}", self.0, ucdf.source_type))
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let registry = ResolverRegistry::new()
+            .register("db", Named("generic"))
+            .register("db.postgresql", Named("postgres"))
+            .validator(Validator::with_defaults());
+
+        let ucdf = parse("t=db.postgresql;c.host=db1").unwrap();
+        assert_eq!(registry.resolve(&ucdf).unwrap(), "postgres db.postgresql");
+        let ucdf = parse("t=db.mysql;c.host=db1").unwrap();
+        assert_eq!(registry.resolve(&ucdf).unwrap(), "generic db.mysql");
+
+        let ucdf = parse("t=db.mysql").unwrap();
+        assert!(matches!(registry.resolve(&ucdf), Err(Error::Validation(_))));
+        let ucdf = parse("t=file.csv;c.path=/data/users.csv").unwrap();
+        assert!(!registry.supports(&ucdf));
+        assert!(matches!(registry.resolve(&ucdf), Err(Error::NoResolver(_))));
+    }
+
+    #[cfg(feature = "with-async")]
+    impl AsyncSourceResolver for Named {
+        type Handle = String;
+
+        async fn resolve(&self, ucdf: &UCDF) -> Result<String> {
+            SourceResolver::resolve(self, ucdf)
+        }
+    }
+
+    #[cfg(feature = "with-async")]
+    #[test]
+    fn test_async_dispatch() {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        let registry = AsyncResolverRegistry::new().register("db", Named("async"));
+        let ucdf = parse("t=db.postgresql;c.host=db1").unwrap();
+        let mut future = std::pin::pin!(registry.resolve(&ucdf));
+        let poll = future
+            .as_mut()
+            .poll(&mut Context::from_waker(Waker::noop()));
+        assert!(matches!(poll, Poll::Ready(Ok(handle)) if handle == "async db.postgresql"));
+    }
+}
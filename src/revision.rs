@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// Metadata key holding the revision number
+pub const REVISION_KEY: &str = "rev";
+/// Metadata key holding the author of the last change
+pub const MODIFIED_BY_KEY: &str = "modified_by";
+/// Metadata key holding the RFC 3339 timestamp of the last change
+pub const MODIFIED_AT_KEY: &str = "modified_at";
+
+/// Revision information stored in `m.rev`, `m.modified_by` and `m.modified_at`
+///
+/// Revisions are ordered by number, then by modification time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Revision {
+    pub number: u64,
+    pub modified_by: Option<String>,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl PartialOrd for Revision {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Revision {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.number
+            .cmp(&other.number)
+            .then_with(|| self.modified_at.cmp(&other.modified_at))
+    }
+}
+
+impl UCDF {
+    /// Get the revision information, with number 0 for unrevised descriptors
+    pub fn revision(&self) -> Result<Revision> {
+        let number = match self.metadata.get(REVISION_KEY) {
+            Some(rev) => rev
+                .parse()
+                .map_err(|_| Error::InvalidFormat(format!("m.rev is not a number: {}", rev)))?,
+            None => 0,
+        };
+        let modified_at = match self.metadata.get(MODIFIED_AT_KEY) {
+            Some(at) => Some(
+                DateTime::parse_from_rfc3339(at)
+                    .map_err(|e| {
+                        Error::InvalidFormat(format!("m.modified_at is not RFC 3339: {}", e))
+                    })?
+                    .with_timezone(&Utc),
+            ),
+            None => None,
+        };
+        Ok(Revision {
+            number,
+            modified_by: self.metadata.get(MODIFIED_BY_KEY).cloned(),
+            modified_at,
+        })
+    }
+
+    /// Record a change by `author`, bumping `m.rev` and setting `m.modified_at` to now
+    ///
+    /// Returns the new revision number. Touching a signed descriptor
+    /// invalidates its signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let mut ucdf = parse("t=db.postgresql;c.host=db1;m.rev=3").unwrap();
+    /// assert_eq!(ucdf.touch("alice").unwrap(), 4);
+    /// assert_eq!(ucdf.metadata.get("modified_by").unwrap(), "alice");
+    /// ```
+    pub fn touch(&mut self, author: impl Into<String>) -> Result<u64> {
+        self.touch_at(author, Utc::now())
+    }
+
+    /// Record a change by `author` at the given time
+    pub fn touch_at(&mut self, author: impl Into<String>, at: DateTime<Utc>) -> Result<u64> {
+        let number = self.revision()?.number + 1;
+        self.metadata.insert(REVISION_KEY, number.to_string());
+        self.metadata.insert(MODIFIED_BY_KEY, author);
+        self.metadata.insert(
+            MODIFIED_AT_KEY,
+            at.to_rfc3339_opts(SecondsFormat::Secs, true),
+        );
+        Ok(number)
+    }
+
+    /// Check if this descriptor has a later revision than `other`
+    pub fn is_newer_than(&self, other: &UCDF) -> Result<bool> {
+        Ok(self.revision()? > other.revision()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_touch_and_compare() {
+        let original = parse("t=file.csv;c.path=/data/users.csv").unwrap();
+        assert_eq!(original.revision().unwrap(), Revision::default());
+
+        let mut updated = original.clone();
+        let at = DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(updated.touch_at("bob", at).unwrap(), 1);
+        assert_eq!(
+            updated.to_canonical_string(),
+            "t=file.csv;c.path=/data/users.csv;m.modified_at=\"2024-05-01T12:00:00Z\";m.modified_by=bob;m.rev=1"
+        );
+
+        let reparsed = parse(&updated.to_string()).unwrap();
+        let revision = reparsed.revision().unwrap();
+        assert_eq!(revision.modified_by.as_deref(), Some("bob"));
+        assert_eq!(revision.modified_at, Some(at));
+        assert!(reparsed.is_newer_than(&original).unwrap());
+        assert!(!original.is_newer_than(&reparsed).unwrap());
+
+        let invalid = parse("t=file.csv;m.rev=latest").unwrap();
+        assert!(invalid.revision().is_err());
+    }
+}
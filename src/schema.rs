@@ -0,0 +1,187 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::sections::{DataType, StructureData};
+use crate::types::Field;
+
+/// Field schema of a data source, as declared in `s.fields`
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use ucdf::Schema;
+///
+/// let schema = Schema::infer_from_json(&[
+///     json!({"id": 1, "user": {"name": "Ann"}, "score": 1}),
+///     json!({"id": 2, "user": {"name": "Bob"}, "score": 2.5, "tags": ["a"]}),
+/// ]);
+/// assert_eq!(schema.to_string(), "id:int,user:json,score:float,tags:array<str>:nullable");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Create a schema from fields
+    pub fn new(fields: Vec<Field>) -> Self {
+        Schema { fields }
+    }
+
+    /// Infer a schema from sample JSON records
+    ///
+    /// Fields appear in order of first occurrence. Nested objects map to
+    /// `json`, arrays to `array<T>` when their elements share a type, strings
+    /// shaped like ISO 8601 dates to `date` or `datetime` and hyphenated UUIDs
+    /// to `uuid`, and mixed integer
+    /// and float values to `float`. Fields that are `null` or missing in some
+    /// records are marked `nullable`. Samples that are not objects are ignored.
+    pub fn infer_from_json<'a>(values: impl IntoIterator<Item = &'a Value>) -> Self {
+        let mut columns: Vec<(String, Option<DataType>, bool)> = Vec::new();
+        let mut records = 0;
+
+        for object in values.into_iter().filter_map(Value::as_object) {
+            records += 1;
+            for (name, value) in object {
+                let inferred = infer_type(value);
+                match columns.iter_mut().find(|(column, _, _)| column == name) {
+                    Some((_, dtype, nullable)) => {
+                        *nullable |= inferred.is_none();
+                        *dtype = merge(dtype.take(), inferred);
+                    }
+                    None => {
+                        let nullable = inferred.is_none() || records > 1;
+                        columns.push((name.clone(), inferred, nullable));
+                    }
+                }
+            }
+            for (name, _, nullable) in &mut columns {
+                *nullable |= !object.contains_key(name.as_str());
+            }
+        }
+
+        let fields = columns
+            .into_iter()
+            .map(|(name, dtype, nullable)| {
+                let dtype = dtype.unwrap_or(DataType::String).to_string();
+                let field = Field::new(name, dtype, None);
+                if nullable {
+                    field.with_modifier("nullable")
+                } else {
+                    field
+                }
+            })
+            .collect();
+        Schema { fields }
+    }
+
+    /// Convert the schema into a fields structure for `s.fields`
+    pub fn to_structure(&self) -> StructureData {
+        StructureData::from_fields(self.fields.clone())
+    }
+}
+
+impl From<Schema> for StructureData {
+    fn from(schema: Schema) -> Self {
+        StructureData::from_fields(schema.fields)
+    }
+}
+
+impl fmt::Display for Schema {
+    /// Format the schema as written after `s.fields=`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_structure())
+    }
+}
+
+// Infer the type of a JSON value; `None` for null
+fn infer_type(value: &Value) -> Option<DataType> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some(DataType::Boolean),
+        Value::Number(number) if number.is_f64() => Some(DataType::Float),
+        Value::Number(_) => Some(DataType::Integer),
+        Value::String(s) => Some(infer_string_type(s)),
+        Value::Object(_) => Some(DataType::Json),
+        Value::Array(items) => {
+            let element = items
+                .iter()
+                .map(infer_type)
+                .fold(None, merge)
+                .unwrap_or(DataType::String);
+            Some(match element {
+                DataType::Json => DataType::Json,
+                element => DataType::array(element),
+            })
+        }
+    }
+}
+
+fn infer_string_type(s: &str) -> DataType {
+    let is_date = |s: &str| {
+        let bytes = s.as_bytes();
+        bytes.len() == 10
+            && bytes.iter().enumerate().all(|(i, b)| {
+                if i == 4 || i == 7 {
+                    *b == b'-'
+                } else {
+                    b.is_ascii_digit()
+                }
+            })
+    };
+    let is_uuid = |s: &str| {
+        let groups: Vec<&str> = s.split('-').collect();
+        groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+            && groups
+                .iter()
+                .all(|group| group.bytes().all(|b| b.is_ascii_hexdigit()))
+    };
+    match s.split_once(['T', ' ']) {
+        Some((date, time)) if is_date(date) && time.len() >= 5 && time.as_bytes()[2] == b':' => {
+            DataType::DateTime
+        }
+        _ if is_date(s) => DataType::Date,
+        _ if is_uuid(s) => DataType::Uuid,
+        _ => DataType::String,
+    }
+}
+
+// Combine the types seen for one field across records
+fn merge(a: Option<DataType>, b: Option<DataType>) -> Option<DataType> {
+    match (a, b) {
+        (None, other) | (other, None) => other,
+        (Some(a), Some(b)) if a == b => Some(a),
+        (Some(DataType::Integer), Some(DataType::Float))
+        | (Some(DataType::Float), Some(DataType::Integer)) => Some(DataType::Float),
+        (Some(DataType::Date), Some(DataType::DateTime))
+        | (Some(DataType::DateTime), Some(DataType::Date)) => Some(DataType::DateTime),
+        (Some(DataType::Json), _) | (_, Some(DataType::Json)) => Some(DataType::Json),
+        (Some(DataType::Array(_)), _) | (_, Some(DataType::Array(_))) => Some(DataType::Json),
+        _ => Some(DataType::String),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_infer_from_json() {
+        let samples = [
+            json!({"id": 1, "at": "2024-01-02T10:00:00Z", "day": "2024-01-02", "meta": {"a": 1}}),
+            json!({"id": 2, "at": "2024-01-03T11:00:00Z", "day": null, "meta": [1, 2], "code": 7}),
+            json!({"id": 3, "at": "2024-01-04", "code": "X7", "ratio": [0.5, 1],
+                "key": "67e55044-10b1-426f-9247-bb680e5fe0c8"}),
+            json!("not an object"),
+        ];
+        let schema = Schema::infer_from_json(&samples);
+        assert_eq!(
+            schema.to_string(),
+            "id:int,at:datetime,day:date:nullable,meta:json:nullable,code:str:nullable,ratio:array<float>:nullable,key:uuid:nullable"
+        );
+        assert!(Schema::infer_from_json(&[]).fields.is_empty());
+    }
+}
@@ -1,15 +1,24 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 use bon::bon;
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::error::{Error, Result};
-use crate::types::{Endpoint, Field};
+use crate::extensions::Extensions;
+use crate::intern::Key;
+use crate::pattern::UcdfPattern;
+use crate::small_map::{self, SmallMap};
+use crate::types::{split_list, Endpoint, Field};
 
 /// Represents a source type in UCDF
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
 pub struct SourceType {
     pub category: String,
     pub subtype: Option<String>,
@@ -17,13 +26,52 @@ pub struct SourceType {
 
 #[bon]
 impl SourceType {
-    #[builder]
+    #[builder(on(String, into))]
     pub fn builder(category: String, subtype: Option<String>) -> Self {
         Self { category, subtype }
     }
 
-    pub fn new(category: String, subtype: Option<String>) -> Self {
-        Self { category, subtype }
+    /// Create a source type, e.g. `SourceType::new("db", Some("postgresql".into()))`
+    /// or `SourceType::new("db", None)` for a category without subtype
+    pub fn new(category: impl Into<String>, subtype: Option<String>) -> Self {
+        Self {
+            category: category.into(),
+            subtype,
+        }
+    }
+}
+
+impl SourceType {
+    /// Get the category as a [`Category`]
+    pub fn kind(&self) -> Category {
+        Category::from(self.category.as_str())
+    }
+
+    /// Check if this is a database source (`db`)
+    pub fn is_db(&self) -> bool {
+        self.kind() == Category::Db
+    }
+
+    /// Check if this is a file source (`file`)
+    pub fn is_file(&self) -> bool {
+        self.kind() == Category::File
+    }
+
+    /// Check if this is an API source (`api`)
+    pub fn is_api(&self) -> bool {
+        self.kind() == Category::Api
+    }
+
+    /// Check if this is a stream source (`stream`)
+    pub fn is_stream(&self) -> bool {
+        self.kind() == Category::Stream
+    }
+
+    /// Match the source type against a glob pattern such as `db.*` or `*.kafka`
+    ///
+    /// `*` matches any sequence of characters and `?` matches a single character.
+    pub fn matches(&self, pattern: &str) -> bool {
+        glob_match(pattern, &self.to_string())
     }
 }
 
@@ -55,39 +103,280 @@ impl fmt::Display for SourceType {
     }
 }
 
+/// Standard source type categories
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Category {
+    Db,
+    File,
+    Api,
+    Stream,
+    Custom(String),
+}
+
+impl From<&str> for Category {
+    fn from(s: &str) -> Self {
+        match s {
+            "db" => Category::Db,
+            "file" => Category::File,
+            "api" => Category::Api,
+            "stream" => Category::Stream,
+            _ => Category::Custom(s.to_string()),
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Category::from(s))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Db => write!(f, "db"),
+            Category::File => write!(f, "file"),
+            Category::Api => write!(f, "api"),
+            Category::Stream => write!(f, "stream"),
+            Category::Custom(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Match text against a glob pattern where `*` matches any sequence and `?` any character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 /// Access mode for UCDF sources
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum AccessMode {
-    Read,
-    Write,
-    ReadWrite,
+///
+/// Access modes are flags that can be combined: `r` (read), `w` (write),
+/// `a` (append), `x` (execute, for APIs and procedures) and `admin`.
+/// Flags are written as a single token (`a=ra`), with `admin` joined by `+`
+/// when combined with other flags (`a=rw+admin`). A mode without flags is
+/// written `none`.
+///
+/// Serialized as its string form; the `Read`, `Write` and `ReadWrite` names
+/// of earlier versions are read as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AccessMode(u8);
+
+impl AccessMode {
+    /// Read access (`r`)
+    pub const READ: AccessMode = AccessMode(0b0_0001);
+    /// Write access (`w`)
+    pub const WRITE: AccessMode = AccessMode(0b0_0010);
+    /// Append-only access (`a`)
+    pub const APPEND: AccessMode = AccessMode(0b0_0100);
+    /// Execute access (`x`), for APIs and procedures
+    pub const EXECUTE: AccessMode = AccessMode(0b0_1000);
+    /// Administrative access (`admin`)
+    pub const ADMIN: AccessMode = AccessMode(0b1_0000);
+    /// Read and write access (`rw`)
+    pub const READ_WRITE: AccessMode = AccessMode(0b0_0011);
+
+    const ALL_BITS: u8 = 0b1_1111;
+
+    /// Flags written as single letters, in display order
+    const LETTERS: [(char, AccessMode); 4] = [
+        ('r', AccessMode::READ),
+        ('w', AccessMode::WRITE),
+        ('a', AccessMode::APPEND),
+        ('x', AccessMode::EXECUTE),
+    ];
+
+    /// Create an empty access mode with no flags set
+    pub const fn empty() -> Self {
+        AccessMode(0)
+    }
+
+    /// Create an access mode from raw bits, returning `None` for unknown bits
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL_BITS == 0 {
+            Some(AccessMode(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Get the raw bits of this access mode
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Check if no flags are set
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check if all flags of `other` are set
+    pub const fn contains(&self, other: AccessMode) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Combine with the flags of `other`, like `|` in const context
+    pub const fn union(self, other: AccessMode) -> AccessMode {
+        AccessMode(self.0 | other.0)
+    }
+
+    /// Set all flags of `other`
+    pub fn insert(&mut self, other: AccessMode) {
+        self.0 |= other.0;
+    }
+
+    /// Clear all flags of `other`
+    pub fn remove(&mut self, other: AccessMode) {
+        self.0 &= !other.0;
+    }
+
+    /// Check if the read flag is set
+    pub const fn can_read(&self) -> bool {
+        self.contains(AccessMode::READ)
+    }
+
+    /// Check if the write flag is set
+    pub const fn can_write(&self) -> bool {
+        self.contains(AccessMode::WRITE)
+    }
+
+    /// Check if the append flag is set
+    pub const fn can_append(&self) -> bool {
+        self.contains(AccessMode::APPEND)
+    }
+
+    /// Check if the execute flag is set
+    pub const fn can_execute(&self) -> bool {
+        self.contains(AccessMode::EXECUTE)
+    }
+
+    /// Check if the admin flag is set
+    pub const fn is_admin(&self) -> bool {
+        self.contains(AccessMode::ADMIN)
+    }
+}
+
+impl std::ops::BitOr for AccessMode {
+    type Output = AccessMode;
+
+    fn bitor(self, rhs: AccessMode) -> AccessMode {
+        AccessMode(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for AccessMode {
+    fn bitor_assign(&mut self, rhs: AccessMode) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for AccessMode {
+    type Output = AccessMode;
+
+    fn bitand(self, rhs: AccessMode) -> AccessMode {
+        AccessMode(self.0 & rhs.0)
+    }
 }
 
 impl FromStr for AccessMode {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s {
-            "r" => Ok(AccessMode::Read),
-            "w" => Ok(AccessMode::Write),
-            "rw" | "wr" => Ok(AccessMode::ReadWrite),
-            _ => Err(Error::InvalidAccessMode(s.to_string())),
+        let mut mode = AccessMode::empty();
+        if s == "none" {
+            return Ok(mode);
+        }
+        for token in s.split('+') {
+            if token == "admin" {
+                mode.insert(AccessMode::ADMIN);
+                continue;
+            }
+            if token.is_empty() {
+                return Err(Error::InvalidAccessMode(s.to_string()));
+            }
+            for c in token.chars() {
+                let flag = AccessMode::LETTERS
+                    .iter()
+                    .find(|(letter, _)| *letter == c)
+                    .map(|(_, flag)| *flag)
+                    .ok_or_else(|| Error::InvalidAccessMode(s.to_string()))?;
+                if mode.contains(flag) {
+                    return Err(Error::InvalidAccessMode(s.to_string()));
+                }
+                mode.insert(flag);
+            }
         }
+        Ok(mode)
     }
 }
 
 impl fmt::Display for AccessMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            AccessMode::Read => write!(f, "r"),
-            AccessMode::Write => write!(f, "w"),
-            AccessMode::ReadWrite => write!(f, "rw"),
+        let letters: String = AccessMode::LETTERS
+            .iter()
+            .filter(|(_, flag)| self.contains(*flag))
+            .map(|(letter, _)| *letter)
+            .collect();
+        match (letters.is_empty(), self.is_admin()) {
+            (true, false) => write!(f, "none"),
+            (true, true) => write!(f, "admin"),
+            (false, true) => write!(f, "{}+admin", letters),
+            _ => write!(f, "{}", letters),
         }
     }
 }
 
+impl TryFrom<String> for AccessMode {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        // Names of the variants of the former access mode enum
+        match s.as_str() {
+            "Read" => Ok(AccessMode::READ),
+            "Write" => Ok(AccessMode::WRITE),
+            "ReadWrite" => Ok(AccessMode::READ_WRITE),
+            _ => AccessMode::from_str(&s),
+        }
+    }
+}
+
+impl From<AccessMode> for String {
+    fn from(mode: AccessMode) -> Self {
+        mode.to_string()
+    }
+}
+
 /// Represents the data type for fields
+///
+/// New data types may be added in minor releases; use [`DataType::from_str`]
+/// and [`DataType::custom`] to construct values and keep a wildcard arm when matching.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub enum DataType {
     String,
     Integer,
@@ -96,9 +385,46 @@ pub enum DataType {
     Date,
     DateTime,
     Json,
+    Decimal,
+    Uuid,
+    Duration,
+    Array(Box<DataType>),
     Custom(String),
 }
 
+impl DataType {
+    /// Create a custom data type
+    pub fn custom(name: impl Into<String>) -> Self {
+        DataType::Custom(name.into())
+    }
+
+    /// Create an array data type
+    pub fn array(inner: DataType) -> Self {
+        DataType::Array(Box::new(inner))
+    }
+
+    /// Check if this is a custom (non-standard) data type
+    pub fn is_custom(&self) -> bool {
+        matches!(self, DataType::Custom(_))
+    }
+
+    /// Check if this is a numeric data type
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            DataType::Integer | DataType::Float | DataType::Decimal
+        )
+    }
+
+    /// Get the element type of an array data type
+    pub fn element_type(&self) -> Option<&DataType> {
+        match self {
+            DataType::Array(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
 impl FromStr for DataType {
     type Err = Error;
 
@@ -111,7 +437,13 @@ impl FromStr for DataType {
             "date" => Ok(DataType::Date),
             "datetime" => Ok(DataType::DateTime),
             "json" => Ok(DataType::Json),
-            _ => Ok(DataType::Custom(s.to_string())),
+            "decimal" => Ok(DataType::Decimal),
+            "uuid" => Ok(DataType::Uuid),
+            "duration" => Ok(DataType::Duration),
+            _ => match s.strip_prefix("array<").and_then(|s| s.strip_suffix('>')) {
+                Some(inner) => Ok(DataType::Array(Box::new(DataType::from_str(inner)?))),
+                None => Ok(DataType::Custom(s.to_string())),
+            },
         }
     }
 }
@@ -126,94 +458,847 @@ impl fmt::Display for DataType {
             DataType::Date => write!(f, "date"),
             DataType::DateTime => write!(f, "datetime"),
             DataType::Json => write!(f, "json"),
+            DataType::Decimal => write!(f, "decimal"),
+            DataType::Uuid => write!(f, "uuid"),
+            DataType::Duration => write!(f, "duration"),
+            DataType::Array(inner) => write!(f, "array<{}>", inner),
             DataType::Custom(s) => write!(f, "{}", s),
         }
     }
 }
 
+/// Typed value of a structure section, serialized through its `Display`
+///
+/// Implemented for every `Clone + Debug + Display` type, so integrations can
+/// attach rich structures (Arrow schemas, OpenAPI fragments, ...) with
+/// [`StructureData::typed`].
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::{Parser, StructureData};
+///
+/// #[derive(Debug, Clone)]
+/// struct Topics(Vec<String>);
+///
+/// impl std::fmt::Display for Topics {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", self.0.join(","))
+///     }
+/// }
+///
+/// let parser = Parser::new().structure_parser("topics", |value: &str| {
+///     Ok(StructureData::typed(Topics(value.split(',').map(String::from).collect())))
+/// });
+/// let ucdf = parser.parse("t=stream.kafka;s.topics=orders,payments").unwrap();
+/// let topics = ucdf.structure["topics"].as_typed::<Topics>().unwrap();
+/// assert_eq!(topics.0, ["orders", "payments"]);
+/// assert_eq!(ucdf.to_string(), "t=stream.kafka;s.topics=orders,payments");
+/// ```
+pub trait StructureValue: Any + fmt::Debug + fmt::Display + Send + Sync {
+    /// Clone into a new box
+    fn clone_box(&self) -> Box<dyn StructureValue>;
+}
+
+impl<T: Any + Clone + fmt::Debug + fmt::Display + Send + Sync> StructureValue for T {
+    fn clone_box(&self) -> Box<dyn StructureValue> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn StructureValue> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+/// Typed values compare equal when their serialized forms are equal
+impl PartialEq for dyn StructureValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Serialize for dyn StructureValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Typed values deserialize as raw `String` values
+impl<'de> Deserialize<'de> for Box<dyn StructureValue> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        Ok(Box::new(String::deserialize(deserializer)?))
+    }
+}
+
+/// Value of a built-in structure section, parsed on first access
+///
+/// Created by parsers with [`Parser::lazy_structure`](crate::Parser::lazy_structure)
+/// enabled. The value is written back exactly as it was read, except by
+/// [`UCDF::to_canonical_string`], which writes the structure it parses to.
+#[derive(Debug)]
+pub struct LazyStructure {
+    key: String,
+    raw: String,
+    parsed: OnceLock<Result<Box<StructureData>>>,
+}
+
+impl LazyStructure {
+    /// Create a value of section `s.<key>` to be parsed on first access
+    pub fn new(key: impl Into<String>, raw: impl Into<String>) -> Self {
+        LazyStructure {
+            key: key.into(),
+            raw: raw.into(),
+            parsed: OnceLock::new(),
+        }
+    }
+
+    /// Get the value as written
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Check if the value has been parsed
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    /// Parse the value, or get the result of an earlier parse
+    pub fn get(&self) -> std::result::Result<&StructureData, &Error> {
+        self.parsed
+            .get_or_init(|| crate::parser::structure_value(&self.key, &self.raw).map(Box::new))
+            .as_ref()
+            .map(Box::as_ref)
+    }
+}
+
+impl Clone for LazyStructure {
+    /// Parsed data is cloned along, errors are reported again on access
+    fn clone(&self) -> Self {
+        let parsed = OnceLock::new();
+        if let Some(Ok(data)) = self.parsed.get() {
+            let _ = parsed.set(Ok(data.clone()));
+        }
+        LazyStructure {
+            key: self.key.clone(),
+            raw: self.raw.clone(),
+            parsed,
+        }
+    }
+}
+
 /// Structure data section which can contain different schema types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// New structure kinds may be added in minor releases; prefer the
+/// constructor and `as_*` accessor functions over matching on variants.
+/// A [`Lazy`](StructureData::Lazy) value behaves like the structure it
+/// parses to: it compares equal to it, serializes as it, and the `as_*`
+/// accessors see through it.
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
 pub enum StructureData {
     Fields(Vec<Field>),
     Endpoints(Vec<Endpoint>),
     Format(String),
-    Custom(String, String),
+    /// Structure without a built-in parser, kept as written; its key is the
+    /// one it is stored under in [`Structure`]
+    #[serde(deserialize_with = "deserialize_custom")]
+    Custom(String),
+    /// User-defined structure, written as its `Display` output
+    Typed(Box<dyn StructureValue>),
+    /// Built-in structure not parsed yet
+    #[serde(skip)]
+    Lazy(LazyStructure),
+}
+
+// Borrowed `StructureData` without the lazy variant, to derive `Serialize`
+#[derive(Serialize)]
+#[serde(rename = "StructureData")]
+enum StructureDataRef<'a> {
+    Fields(&'a [Field]),
+    Endpoints(&'a [Endpoint]),
+    Format(&'a str),
+    Custom(&'a str),
+    Typed(&'a dyn StructureValue),
+}
+
+// Custom structures were serialized as `[key, value]` before the key was
+// dropped from the variant; read both forms
+fn deserialize_custom<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum CustomRepr {
+        Value(String),
+        KeyValue(serde::de::IgnoredAny, String),
+    }
+
+    Ok(match CustomRepr::deserialize(deserializer)? {
+        CustomRepr::Value(value) | CustomRepr::KeyValue(_, value) => value,
+    })
+}
+
+/// Lazy values serialize as the structure they parse to
+impl Serialize for StructureData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let data = match self.parsed().map_err(serde::ser::Error::custom)? {
+            StructureData::Fields(fields) => StructureDataRef::Fields(fields),
+            StructureData::Endpoints(endpoints) => StructureDataRef::Endpoints(endpoints),
+            StructureData::Format(format) => StructureDataRef::Format(format),
+            StructureData::Custom(value) => StructureDataRef::Custom(value),
+            StructureData::Typed(value) => StructureDataRef::Typed(value.as_ref()),
+            StructureData::Lazy(_) => unreachable!("parsed structures are not lazy"),
+        };
+        data.serialize(serializer)
+    }
+}
+
+impl StructureData {
+    /// Create a fields structure
+    pub fn from_fields(fields: Vec<Field>) -> Self {
+        StructureData::Fields(fields)
+    }
+
+    /// Create an endpoints structure
+    pub fn from_endpoints(endpoints: Vec<Endpoint>) -> Self {
+        StructureData::Endpoints(endpoints)
+    }
+
+    /// Create a format structure
+    pub fn from_format(format: impl Into<String>) -> Self {
+        StructureData::Format(format.into())
+    }
+
+    /// Create a custom structure
+    pub fn custom(value: impl Into<String>) -> Self {
+        StructureData::Custom(value.into())
+    }
+
+    /// Create a user-defined structure
+    pub fn typed(value: impl StructureValue) -> Self {
+        StructureData::Typed(Box::new(value))
+    }
+
+    /// Create a structure of section `s.<key>` parsed on first access
+    pub fn lazy(key: impl Into<String>, raw: impl Into<String>) -> Self {
+        StructureData::Lazy(LazyStructure::new(key, raw))
+    }
+
+    /// Get the parsed structure, parsing a lazy value if needed
+    pub fn parsed(&self) -> std::result::Result<&StructureData, &Error> {
+        match self {
+            StructureData::Lazy(lazy) => lazy.get(),
+            _ => Ok(self),
+        }
+    }
+
+    /// Get the fields if this is a fields structure
+    pub fn as_fields(&self) -> Option<&[Field]> {
+        match self.parsed().ok()? {
+            StructureData::Fields(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Get the endpoints if this is an endpoints structure
+    pub fn as_endpoints(&self) -> Option<&[Endpoint]> {
+        match self.parsed().ok()? {
+            StructureData::Endpoints(endpoints) => Some(endpoints),
+            _ => None,
+        }
+    }
+
+    /// Get the format if this is a format structure
+    pub fn as_format(&self) -> Option<&str> {
+        match self {
+            StructureData::Format(format) => Some(format),
+            _ => None,
+        }
+    }
+
+    /// Get the raw value if this is a custom structure
+    pub fn as_custom(&self) -> Option<&str> {
+        match self {
+            StructureData::Custom(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a concrete type if this is a user-defined structure
+    pub fn as_typed<T: Any>(&self) -> Option<&T> {
+        match self {
+            StructureData::Typed(value) => {
+                let value: &dyn Any = value.as_ref();
+                value.downcast_ref()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StructureData {
+    /// Format the structure value as written after `s.<key>=`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureData::Fields(fields) => {
+                let fields_str = fields
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                write!(f, "{}", fields_str)
+            }
+            StructureData::Endpoints(endpoints) => {
+                let endpoints_str = endpoints
+                    .iter()
+                    .map(|endpoint| endpoint.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                write!(f, "{}", endpoints_str)
+            }
+            StructureData::Format(format) => write!(f, "{}", format),
+            StructureData::Custom(value) => write!(f, "{}", value),
+            StructureData::Typed(value) => write!(f, "{}", value),
+            StructureData::Lazy(lazy) => write!(f, "{}", lazy.raw),
+        }
+    }
+}
+
+impl PartialEq for StructureData {
+    fn eq(&self, other: &Self) -> bool {
+        use StructureData::*;
+
+        match (self.parsed(), other.parsed()) {
+            (Ok(Fields(a)), Ok(Fields(b))) => a == b,
+            (Ok(Endpoints(a)), Ok(Endpoints(b))) => a == b,
+            (Ok(Format(a)), Ok(Format(b))) => a == b,
+            (Ok(Custom(a)), Ok(Custom(b))) => a == b,
+            (Ok(Typed(a)), Ok(Typed(b))) => a == b,
+            // Values failing to parse are compared as written
+            (Err(_), Err(_)) => self.to_string() == other.to_string(),
+            _ => false,
+        }
+    }
+}
+
+/// Structure section: structure values keyed by the name after `s.`
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::parse;
+///
+/// let ucdf = parse("t=file.csv;s.fields=id:int,name:str;s.format=csv;s.partition=day").unwrap();
+/// assert_eq!(ucdf.structure.fields().map(<[_]>::len), Some(2));
+/// assert_eq!(ucdf.structure.format(), Some("csv"));
+/// assert_eq!(ucdf.structure.custom("partition"), Some("day"));
+/// assert_eq!(ucdf.structure.endpoints(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
+pub struct Structure(pub IndexMap<String, StructureData>);
+
+impl Structure {
+    pub fn new() -> Self {
+        Structure(IndexMap::new())
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, data: StructureData) -> Option<StructureData> {
+        self.0.insert(key.into(), data)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&StructureData> {
+        self.0.get(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Remove a key, keeping the order of the remaining ones
+    pub fn remove(&mut self, key: &str) -> Option<StructureData> {
+        self.0.shift_remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over keys and values in insertion order
+    pub fn iter(&self) -> indexmap::map::Iter<'_, String, StructureData> {
+        self.0.iter()
+    }
+
+    /// Iterate over keys in insertion order
+    pub fn keys(&self) -> indexmap::map::Keys<'_, String, StructureData> {
+        self.0.keys()
+    }
+
+    /// Get the fields of `s.fields`
+    pub fn fields(&self) -> Option<&[Field]> {
+        self.get("fields")?.as_fields()
+    }
+
+    /// Get the endpoints of `s.endpoints`
+    pub fn endpoints(&self) -> Option<&[Endpoint]> {
+        self.get("endpoints")?.as_endpoints()
+    }
+
+    /// Get the format of `s.format`
+    pub fn format(&self) -> Option<&str> {
+        self.get("format")?.as_format()
+    }
+
+    /// Get the raw value of custom structure `s.<key>`
+    pub fn custom(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_custom()
+    }
+}
+
+impl std::ops::Index<&str> for Structure {
+    type Output = StructureData;
+
+    /// Get the structure of a key, panicking if it is absent
+    fn index(&self, key: &str) -> &StructureData {
+        &self.0[key]
+    }
+}
+
+impl<'a> IntoIterator for &'a Structure {
+    type Item = (&'a String, &'a StructureData);
+    type IntoIter = indexmap::map::Iter<'a, String, StructureData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<(String, StructureData)> for Structure {
+    fn from_iter<I: IntoIterator<Item = (String, StructureData)>>(iter: I) -> Self {
+        Structure(iter.into_iter().collect())
+    }
+}
+
+impl From<IndexMap<String, StructureData>> for Structure {
+    fn from(map: IndexMap<String, StructureData>) -> Self {
+        Structure(map)
+    }
 }
 
 /// Connection parameters section
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ConnectionParams(pub HashMap<String, String>);
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
+pub struct ConnectionParams(pub SmallMap<String>);
 
 impl ConnectionParams {
-    pub fn new() -> Self {
-        ConnectionParams(HashMap::new())
+    pub const fn new() -> Self {
+        ConnectionParams(SmallMap::new())
     }
 
-    pub fn insert(&mut self, key: &str, value: &str) -> Option<String> {
-        self.0.insert(key.to_string(), value.to_string())
+    pub fn insert(&mut self, key: impl Into<Key>, value: impl Into<String>) -> Option<String> {
+        self.0.insert(key.into(), value.into())
     }
 
     pub fn get(&self, key: &str) -> Option<&String> {
         self.0.get(key)
     }
 
-    pub fn iter(&self) -> std::collections::hash_map::Iter<String, String> {
+    /// Remove a key, keeping the order of the remaining ones
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over keys and values in insertion order
+    pub fn iter(&self) -> small_map::Iter<'_, String> {
         self.0.iter()
     }
 }
 
+impl From<IndexMap<String, String>> for ConnectionParams {
+    fn from(map: IndexMap<String, String>) -> Self {
+        ConnectionParams(map.into())
+    }
+}
+
 impl From<HashMap<String, String>> for ConnectionParams {
     fn from(map: HashMap<String, String>) -> Self {
-        ConnectionParams(map)
+        ConnectionParams(map.into_iter().collect())
     }
 }
 
 /// Metadata section
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Metadata(pub HashMap<String, String>);
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
+pub struct Metadata(pub SmallMap<String>);
 
 impl Metadata {
-    pub fn new() -> Self {
-        Metadata(HashMap::new())
+    pub const fn new() -> Self {
+        Metadata(SmallMap::new())
     }
 
-    pub fn insert(&mut self, key: &str, value: &str) -> Option<String> {
-        self.0.insert(key.to_string(), value.to_string())
+    pub fn insert(&mut self, key: impl Into<Key>, value: impl Into<String>) -> Option<String> {
+        self.0.insert(key.into(), value.into())
     }
 
     pub fn get(&self, key: &str) -> Option<&String> {
         self.0.get(key)
     }
 
-    pub fn iter(&self) -> std::collections::hash_map::Iter<String, String> {
+    /// Remove a key, keeping the order of the remaining ones
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over keys and values in insertion order
+    pub fn iter(&self) -> small_map::Iter<'_, String> {
         self.0.iter()
     }
+
+    /// Get the value of `key` in language `lang`
+    ///
+    /// Localized values are stored under language-suffixed keys, such as
+    /// `m.desc.en` and `m.desc.de`. Language tags compare case-insensitively,
+    /// with `_` matching `-`. The value is looked up under, in order:
+    ///
+    /// 1. `key.<lang>`, e.g. `desc.de-AT`
+    /// 2. `key.<language>` without region or script, e.g. `desc.de`
+    /// 3. `key` itself
+    /// 4. the first localized value of `key`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=file.csv;m.desc.en=Customer orders;m.desc.de=Kundenaufträge").unwrap();
+    /// assert_eq!(ucdf.metadata.get_localized("desc", "de-AT").unwrap(), "Kundenaufträge");
+    /// assert_eq!(ucdf.metadata.get_localized("desc", "fr").unwrap(), "Customer orders");
+    /// assert_eq!(ucdf.metadata.localizations("desc").count(), 2);
+    ///
+    /// let ucdf = parse("t=file.csv;m.desc=Orders;m.desc.de_ch=Bestellungen").unwrap();
+    /// assert_eq!(ucdf.metadata.get_localized("desc", "de-CH").unwrap(), "Bestellungen");
+    /// assert_eq!(ucdf.metadata.get_localized("desc", "en").unwrap(), "Orders");
+    /// ```
+    pub fn get_localized(&self, key: &str, lang: &str) -> Option<&String> {
+        let lang = lang.replace('_', "-");
+        let language = lang.split('-').next().unwrap_or_default();
+        let find = |tag: &str| {
+            self.localizations(key)
+                .find(|(candidate, _)| candidate.replace('_', "-").eq_ignore_ascii_case(tag))
+                .map(|(_, value)| value)
+        };
+        find(&lang)
+            .or_else(|| find(language))
+            .or_else(|| self.get(key))
+            .or_else(|| self.localizations(key).next().map(|(_, value)| value))
+    }
+
+    /// Iterate over the language tags and values of `key`, stored under
+    /// `key.<lang>`, in insertion order
+    pub fn localizations(&self, key: &str) -> impl Iterator<Item = (&str, &String)> {
+        let prefix = format!("{}.", key);
+        self.iter().filter_map(move |(candidate, value)| {
+            let lang = candidate.strip_prefix(prefix.as_str())?;
+            (!lang.is_empty() && !lang.contains('.')).then_some((lang, value))
+        })
+    }
+}
+
+impl From<IndexMap<String, String>> for Metadata {
+    fn from(map: IndexMap<String, String>) -> Self {
+        Metadata(map.into())
+    }
 }
 
 impl From<HashMap<String, String>> for Metadata {
     fn from(map: HashMap<String, String>) -> Self {
-        Metadata(map)
+        Metadata(map.into_iter().collect())
     }
 }
 
 /// UCDF Section enum representing different parts of a UCDF string
+///
+/// New section kinds may be added in minor releases; prefer the
+/// constructor and accessor functions over matching on variants.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Section {
     Type(SourceType),
     Connection(String, String),
     Structure(String, StructureData),
     Access(AccessMode),
+    /// Access mode of a single section (`a.<section>=<mode>`)
+    SectionAccess(String, AccessMode),
     Meta(String, String),
+    /// Encrypted connection section (`c!=<base64 ciphertext>`)
+    EncryptedConnection(String),
+    /// Include section (`i=<name-or-path>`)
+    Include(String),
+    /// Connection or metadata key written without a value (`c.key=`), as
+    /// its key path
+    Cleared(String),
+    /// Section with an unknown prefix, as its key and value; only produced
+    /// by parsers that [keep sections](crate::Parser::keep_sections)
+    Unknown(String, String),
+}
+
+impl Section {
+    /// Create a type section
+    pub fn source_type(source_type: SourceType) -> Self {
+        Section::Type(source_type)
+    }
+
+    /// Create a connection section
+    pub fn connection(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Section::Connection(key.into(), value.into())
+    }
+
+    /// Create a structure section
+    pub fn structure(key: impl Into<String>, data: StructureData) -> Self {
+        Section::Structure(key.into(), data)
+    }
+
+    /// Create an access mode section
+    pub fn access(mode: AccessMode) -> Self {
+        Section::Access(mode)
+    }
+
+    /// Create an access mode section scoped to `section`
+    pub fn section_access(section: impl Into<String>, mode: AccessMode) -> Self {
+        Section::SectionAccess(section.into(), mode)
+    }
+
+    /// Create an encrypted connection section
+    pub fn encrypted_connection(ciphertext: impl Into<String>) -> Self {
+        Section::EncryptedConnection(ciphertext.into())
+    }
+
+    /// Create an include section
+    pub fn include(source: impl Into<String>) -> Self {
+        Section::Include(source.into())
+    }
+
+    /// Create a metadata section
+    pub fn meta(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Section::Meta(key.into(), value.into())
+    }
+
+    /// Get the section prefix as written in a UCDF string (`t`, `c`, `s`, `a`, `m`)
+    pub fn prefix(&self) -> &str {
+        match self {
+            Section::Type(_) => "t",
+            Section::Connection(_, _) => "c",
+            Section::Structure(_, _) => "s",
+            Section::Access(_) | Section::SectionAccess(_, _) => "a",
+            Section::Meta(_, _) => "m",
+            Section::EncryptedConnection(_) => "c!",
+            Section::Include(_) => "i",
+            Section::Cleared(path) => match KeyPath::parse(path) {
+                Some(KeyPath::Meta(_)) => "m",
+                _ => "c",
+            },
+            Section::Unknown(key, _) => key.split_once('.').map_or(key, |(prefix, _)| prefix),
+        }
+    }
+
+    /// Get the key after the prefix, for sections that have one
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Section::Connection(key, _)
+            | Section::Meta(key, _)
+            | Section::Structure(key, _)
+            | Section::SectionAccess(key, _) => Some(key),
+            Section::Cleared(path) => KeyPath::parse(path).and_then(|path| path.key()),
+            Section::Unknown(key, _) => key.split_once('.').map(|(_, key)| key),
+            Section::Type(_)
+            | Section::Access(_)
+            | Section::EncryptedConnection(_)
+            | Section::Include(_) => None,
+        }
+    }
+}
+
+/// Section key split once into its prefix and the key after it
+///
+/// Keys are two-level (`c.host`, `s.fields`, `a.fields`, `m.desc`) or a bare prefix
+/// (`t`, `a`, `i`, `c!`). Parsers and key path accessors dispatch on a
+/// `KeyPath` instead of matching prefixes of the key string repeatedly.
+///
+/// # Examples
+///
+/// ```
+/// use ucdf::KeyPath;
+///
+/// assert_eq!(KeyPath::parse("c.auth.type"), Some(KeyPath::Connection("auth.type")));
+/// assert_eq!(KeyPath::parse("q.timeout"), Some(KeyPath::Custom("q", "timeout")));
+/// assert_eq!(KeyPath::parse("host"), None);
+/// assert_eq!(KeyPath::Meta("desc").to_string(), "m.desc");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPath<'a> {
+    Type,
+    Include,
+    EncryptedConnection,
+    Access,
+    Connection(&'a str),
+    Structure(&'a str),
+    /// Access mode of a single section, keyed by the section name
+    SectionAccess(&'a str),
+    Meta(&'a str),
+    /// Key with a custom prefix, as `(prefix, key)`
+    Custom(&'a str, &'a str),
+}
+
+impl<'a> KeyPath<'a> {
+    /// Split a key path, returning `None` for keys without a known bare prefix or a `.`
+    pub fn parse(path: &'a str) -> Option<KeyPath<'a>> {
+        Some(match path {
+            "t" => KeyPath::Type,
+            "i" => KeyPath::Include,
+            "c!" => KeyPath::EncryptedConnection,
+            "a" => KeyPath::Access,
+            _ => match path.split_once('.')? {
+                ("c", key) => KeyPath::Connection(key),
+                ("s", key) => KeyPath::Structure(key),
+                ("a", key) => KeyPath::SectionAccess(key),
+                ("m", key) => KeyPath::Meta(key),
+                (prefix, key) => KeyPath::Custom(prefix, key),
+            },
+        })
+    }
+
+    /// Get the prefix as written in a UCDF string
+    pub fn prefix(&self) -> &'a str {
+        match self {
+            KeyPath::Type => "t",
+            KeyPath::Include => "i",
+            KeyPath::EncryptedConnection => "c!",
+            KeyPath::Access | KeyPath::SectionAccess(_) => "a",
+            KeyPath::Connection(_) => "c",
+            KeyPath::Structure(_) => "s",
+            KeyPath::Meta(_) => "m",
+            KeyPath::Custom(prefix, _) => prefix,
+        }
+    }
+
+    /// Get the key after the prefix, for two-level keys
+    pub fn key(&self) -> Option<&'a str> {
+        match self {
+            KeyPath::Connection(key)
+            | KeyPath::Structure(key)
+            | KeyPath::SectionAccess(key)
+            | KeyPath::Meta(key)
+            | KeyPath::Custom(_, key) => Some(key),
+            KeyPath::Type | KeyPath::Include | KeyPath::EncryptedConnection | KeyPath::Access => {
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeyPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key() {
+            Some(key) => write!(f, "{}.{}", self.prefix(), key),
+            None => f.write_str(self.prefix()),
+        }
+    }
+}
+
+/// Value of a key path as written, see [`UCDF::get_raw`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawValue<'a> {
+    /// Key written without a value (`c.key=`), which clears the key when
+    /// the descriptor is [merged](UCDF::merge) into another
+    Cleared,
+    /// Key written with a value, which may be an explicit empty string
+    /// (`c.key=""`)
+    Value(Cow<'a, str>),
 }
 
 /// Main UCDF structure that represents a UCDF data source
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// # Serialization
+///
+/// The serde representation is stable: field names are fixed, enums keep
+/// their external tags (`{"Fields": [...]}`) and fields added later are
+/// optional, so serialized descriptors stay readable by later versions of
+/// the crate. The source type is written as `type`; data written as
+/// `source_type` by earlier versions is still read, as are access modes
+/// written as `Read`, `Write` or `ReadWrite`.
+///
+/// Descriptors compare equal when their parsed content is equal; the
+/// [sections](UCDF::sections) kept as written are not compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
 pub struct UCDF {
+    #[serde(rename = "type", alias = "source_type")]
     pub source_type: SourceType,
+    #[serde(default)]
     pub connection: ConnectionParams,
-    pub structure: HashMap<String, StructureData>,
+    #[serde(default)]
+    pub structure: Structure,
+    #[serde(default)]
     pub access_mode: Option<AccessMode>,
+    /// Access modes of single sections (`a.<section>=...`), overriding
+    /// `access_mode` for them
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub section_access: IndexMap<String, AccessMode>,
+    #[serde(default)]
     pub metadata: Metadata,
+    /// Base64 ciphertext of encrypted connection parameters (`c!=...`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_connection: Option<String>,
+    /// Descriptors whose sections are included before this one's (`i=...`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+    /// Sections with custom prefixes, see [`SectionRegistry`](crate::SectionRegistry)
+    #[serde(default, skip_serializing_if = "Extensions::is_empty")]
+    pub extensions: Extensions,
+    /// Key paths of connection and metadata keys written without a value
+    /// (`c.key=`), see [`UCDF::get_raw`]
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub cleared: BTreeSet<String>,
+    /// Sections as written, see [`UCDF::sections`]
+    #[serde(skip)]
+    pub(crate) sections: Vec<Section>,
+}
+
+impl PartialEq for UCDF {
+    fn eq(&self, other: &Self) -> bool {
+        self.source_type == other.source_type
+            && self.connection == other.connection
+            && self.structure == other.structure
+            && self.access_mode == other.access_mode
+            && self.section_access == other.section_access
+            && self.metadata == other.metadata
+            && self.encrypted_connection == other.encrypted_connection
+            && self.includes == other.includes
+            && self.extensions == other.extensions
+            && self.cleared == other.cleared
+    }
 }
 
 #[bon]
@@ -222,16 +1307,27 @@ impl UCDF {
     pub fn builder(
         source_type: SourceType,
         #[builder(default = ConnectionParams::new())] connection: ConnectionParams,
-        #[builder(default = HashMap::new())] structure: HashMap<String, StructureData>,
+        #[builder(default = Structure::new())] structure: Structure,
         access_mode: Option<AccessMode>,
+        #[builder(default)] section_access: IndexMap<String, AccessMode>,
         #[builder(default = Metadata::new())] metadata: Metadata,
+        encrypted_connection: Option<String>,
+        #[builder(default)] includes: Vec<String>,
+        #[builder(default)] extensions: Extensions,
+        #[builder(default)] cleared: BTreeSet<String>,
     ) -> Self {
         Self {
             source_type,
             connection,
             structure,
             access_mode,
+            section_access,
             metadata,
+            encrypted_connection,
+            includes,
+            extensions,
+            cleared,
+            sections: Vec::new(),
         }
     }
     pub fn with_source_type(source_type: SourceType) -> Self {
@@ -240,20 +1336,26 @@ impl UCDF {
             connection: ConnectionParams::new(),
             structure: Default::default(),
             access_mode: None,
+            section_access: IndexMap::new(),
             metadata: Metadata::new(),
+            encrypted_connection: None,
+            includes: Vec::new(),
+            extensions: Extensions::new(),
+            cleared: BTreeSet::new(),
+            sections: Vec::new(),
         }
     }
 }
 
 impl UCDF {
     /// Add a connection parameter
-    pub fn add_connection(&mut self, key: &str, value: &str) -> &mut Self {
+    pub fn add_connection(&mut self, key: impl Into<Key>, value: impl Into<String>) -> &mut Self {
         self.connection.insert(key, value);
         self
     }
 
     /// Fluent API for adding a connection parameter
-    pub fn with_connection(mut self, key: &str, value: &str) -> Self {
+    pub fn with_connection(mut self, key: impl Into<Key>, value: impl Into<String>) -> Self {
         self.connection.insert(key, value);
         self
     }
@@ -285,31 +1387,35 @@ impl UCDF {
     }
 
     /// Add format structure
-    pub fn add_format(&mut self, format: &str) -> &mut Self {
-        self.structure.insert(
-            "format".to_string(),
-            StructureData::Format(format.to_string()),
-        );
+    pub fn add_format(&mut self, format: impl Into<String>) -> &mut Self {
+        self.structure
+            .insert("format".to_string(), StructureData::Format(format.into()));
         self
     }
 
     /// Fluent API for adding format structure
-    pub fn with_format(mut self, format: &str) -> Self {
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
         self.add_format(format);
         self
     }
 
     /// Add custom structure
-    pub fn add_custom_structure(&mut self, key: &str, value: &str) -> &mut Self {
-        self.structure.insert(
-            key.to_string(),
-            StructureData::Custom(key.to_string(), value.to_string()),
-        );
+    pub fn add_custom_structure(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> &mut Self {
+        self.structure
+            .insert(key, StructureData::Custom(value.into()));
         self
     }
 
     /// Fluent API for adding custom structure
-    pub fn with_custom_structure(mut self, key: &str, value: &str) -> Self {
+    pub fn with_custom_structure(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
         self.add_custom_structure(key, value);
         self
     }
@@ -326,104 +1432,438 @@ impl UCDF {
         self
     }
 
+    /// Set the access mode of a single section, such as `fields`
+    pub fn set_section_access(
+        &mut self,
+        section: impl Into<String>,
+        mode: AccessMode,
+    ) -> &mut Self {
+        self.section_access.insert(section.into(), mode);
+        self
+    }
+
+    /// Fluent API for setting the access mode of a single section
+    pub fn with_section_access(mut self, section: impl Into<String>, mode: AccessMode) -> Self {
+        self.set_section_access(section, mode);
+        self
+    }
+
+    /// Get the access mode of a section, falling back to the global one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=api.rest;a=r;a.endpoints=rw").unwrap();
+    /// assert!(ucdf.access_for("endpoints").unwrap().can_write());
+    /// assert!(!ucdf.access_for("fields").unwrap().can_write());
+    /// assert_eq!(ucdf.to_string(), "t=api.rest;a=r;a.endpoints=rw");
+    /// ```
+    pub fn access_for(&self, section: &str) -> Option<AccessMode> {
+        self.section_access
+            .get(section)
+            .copied()
+            .or(self.access_mode)
+    }
+
     /// Add metadata
-    pub fn add_metadata(&mut self, key: &str, value: &str) -> &mut Self {
+    pub fn add_metadata(&mut self, key: impl Into<Key>, value: impl Into<String>) -> &mut Self {
         self.metadata.insert(key, value);
         self
     }
 
     /// Fluent API for adding metadata
-    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+    pub fn with_metadata(mut self, key: impl Into<Key>, value: impl Into<String>) -> Self {
         self.add_metadata(key, value);
         self
     }
 
+    /// Iterate over the sections as written, in document order
+    ///
+    /// Only descriptors parsed with
+    /// [`keep_sections`](crate::Parser::keep_sections) enabled have sections;
+    /// for others this is empty. Kept sections are not serialized and don't
+    /// follow later changes to the descriptor.
+    pub fn sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter()
+    }
+
+    /// Get a value by its UCDF key path, e.g. `t`, `a`, `c.host`, `s.fields` or `m.desc`
+    ///
+    /// Values are returned as they are written in a UCDF string, without quoting.
+    pub fn get_path(&self, path: &str) -> Option<String> {
+        match KeyPath::parse(path)? {
+            KeyPath::Type => Some(self.source_type.to_string()),
+            KeyPath::Access => self.access_mode.map(|mode| mode.to_string()),
+            KeyPath::SectionAccess(key) => {
+                self.section_access.get(key).map(|mode| mode.to_string())
+            }
+            KeyPath::EncryptedConnection => self.encrypted_connection.clone(),
+            KeyPath::Include => (!self.includes.is_empty()).then(|| self.includes.join(",")),
+            KeyPath::Connection(key) => self.connection.get(key).cloned(),
+            KeyPath::Structure(key) => self.structure.get(key).map(|data| data.to_string()),
+            KeyPath::Meta(key) => self.metadata.get(key).cloned(),
+            KeyPath::Custom(_, _) => None,
+        }
+    }
+
+    /// Get a value by its UCDF key path, telling a key written without a
+    /// value (`c.key=`) from one set to an empty string (`c.key=""`)
+    ///
+    /// Returns `None` for absent keys. Only connection and metadata keys can
+    /// be [`Cleared`](RawValue::Cleared); other values are returned as by
+    /// [`get_path`](UCDF::get_path).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::{parse, RawValue};
+    ///
+    /// let ucdf = parse("t=db.mysql;c.password=;c.user=\"\"").unwrap();
+    /// assert_eq!(ucdf.get_raw("c.password"), Some(RawValue::Cleared));
+    /// assert_eq!(ucdf.get_raw("c.user"), Some(RawValue::Value("".into())));
+    /// assert_eq!(ucdf.get_raw("c.host"), None);
+    /// assert_eq!(ucdf.to_string(), "t=db.mysql;c.password=;c.user=\"\"");
+    /// ```
+    pub fn get_raw(&self, path: &str) -> Option<RawValue<'_>> {
+        let key_path = KeyPath::parse(path)?;
+        let value = match key_path {
+            KeyPath::Connection(key) => self.connection.get(key)?,
+            KeyPath::Meta(key) => self.metadata.get(key)?,
+            _ => {
+                return self
+                    .get_path(path)
+                    .map(|value| RawValue::Value(value.into()))
+            }
+        };
+        if value.is_empty() && self.is_cleared(key_path) {
+            Some(RawValue::Cleared)
+        } else {
+            Some(RawValue::Value(value.into()))
+        }
+    }
+
+    /// Clear a connection or metadata key, written as `c.key=`
+    ///
+    /// The key is set to an empty value and recorded in
+    /// [`cleared`](UCDF::cleared).
+    pub fn clear_path(&mut self, path: &str) -> Result<()> {
+        match KeyPath::parse(path) {
+            Some(KeyPath::Connection(key)) if !key.is_empty() => {
+                self.add_connection(key, "");
+            }
+            Some(KeyPath::Meta(key)) if !key.is_empty() => {
+                self.add_metadata(key, "");
+            }
+            _ => return Err(Error::InvalidSectionFormat(path.to_string())),
+        }
+        self.cleared.insert(path.to_string());
+        Ok(())
+    }
+
+    // Check if a key path is recorded as written without a value
+    pub(crate) fn is_cleared(&self, path: KeyPath<'_>) -> bool {
+        self.cleared
+            .iter()
+            .any(|cleared| KeyPath::parse(cleared) == Some(path))
+    }
+
+    // Write a connection or metadata value, quoting an empty one unless its
+    // key is cleared
+    fn write_value<W: fmt::Write>(&self, w: &mut W, path: KeyPath<'_>, value: &str) -> fmt::Result {
+        if value.is_empty() && !self.is_cleared(path) {
+            return w.write_str("\"\"");
+        }
+        write!(w, "{}", quote_value(value))
+    }
+
+    /// Set a value by its UCDF key path, parsing it as the section requires
+    ///
+    /// Structure values are parsed like in a UCDF string, so `s.fields` and
+    /// `s.endpoints` are validated.
+    pub fn set_path(&mut self, path: &str, value: &str) -> Result<()> {
+        let key_path = KeyPath::parse(path)
+            .filter(|key_path| key_path.key() != Some(""))
+            .ok_or_else(|| Error::InvalidSectionFormat(path.to_string()))?;
+        match key_path {
+            KeyPath::Type => self.source_type = SourceType::from_str(value)?,
+            KeyPath::Access => self.access_mode = Some(AccessMode::from_str(value)?),
+            KeyPath::SectionAccess(key) => {
+                self.set_section_access(key, AccessMode::from_str(value)?);
+            }
+            KeyPath::EncryptedConnection => self.encrypted_connection = Some(value.to_string()),
+            KeyPath::Include => self.includes.push(value.to_string()),
+            KeyPath::Connection(key) => {
+                self.add_connection(key, value);
+            }
+            KeyPath::Meta(key) => {
+                self.add_metadata(key, value);
+            }
+            KeyPath::Structure("fields") => {
+                self.add_fields(UCDF::parse_fields(value)?);
+            }
+            KeyPath::Structure("endpoints") => {
+                self.add_endpoints(UCDF::parse_endpoints(value)?);
+            }
+            KeyPath::Structure("format") => {
+                self.add_format(value);
+            }
+            KeyPath::Structure(key) => {
+                self.add_custom_structure(key, value);
+            }
+            KeyPath::Custom(prefix, _) => {
+                return Err(Error::UnknownSectionPrefix(prefix.to_string()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if the descriptor matches a [`UcdfPattern`]
+    pub fn matches(&self, pattern: &UcdfPattern) -> bool {
+        pattern.matches(self)
+    }
+
     /// Parse a string containing fields
     pub fn parse_fields(fields_str: &str) -> Result<Vec<Field>> {
-        let mut fields = Vec::new();
-        for field_str in fields_str.split(',') {
-            fields.push(Field::from_str(field_str)?);
-        }
-        Ok(fields)
+        split_list(fields_str)
+            .into_iter()
+            .map(Field::from_str)
+            .collect()
     }
 
     /// Parse a string containing endpoints
     pub fn parse_endpoints(endpoints_str: &str) -> Result<Vec<Endpoint>> {
-        let mut endpoints = Vec::new();
-        for endpoint_str in endpoints_str.split(',') {
-            endpoints.push(Endpoint::from_str(endpoint_str)?);
+        split_list(endpoints_str)
+            .into_iter()
+            .map(Endpoint::from_str)
+            .collect()
+    }
+}
+
+impl UCDF {
+    /// Convert the UCDF structure to its canonical string form
+    ///
+    /// `to_string()` keeps keys in insertion order, so a parsed descriptor
+    /// prints as written. Here keys within each section are sorted instead,
+    /// so equal descriptors always produce the same string.
+    pub fn to_canonical_string(&self) -> String {
+        let mut s = String::new();
+        self.write_sections(&mut s, true, ";")
+            .expect("writing to a String cannot fail");
+        s
+    }
+
+    /// Convert the UCDF structure to its multi-line form, one section per line
+    ///
+    /// Sections and keys are written as by `to_string()`, with line breaks
+    /// instead of `;`, so the parser reads back the same descriptor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=db.postgresql;c.host=db1;c.port=5432;m.desc=\"orders; daily\"").unwrap();
+    /// let pretty = ucdf.to_pretty_string();
+    /// assert_eq!(pretty, "t=db.postgresql\nc.host=db1\nc.port=5432\nm.desc=\"orders; daily\"");
+    /// assert_eq!(parse(&pretty).unwrap(), ucdf);
+    /// ```
+    pub fn to_pretty_string(&self) -> String {
+        let mut s = String::new();
+        self.write_sections(&mut s, false, "\n")
+            .expect("writing to a String cannot fail");
+        s
+    }
+
+    /// Bring the descriptor into its canonical form
+    ///
+    /// Keys within each section are sorted, so `to_string()` then returns the
+    /// canonical string, and lazy structure values are replaced by the
+    /// structure they parse to. Values that fail to parse stay lazy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let mut ucdf = parse("t=db.mysql;m.owner=data;c.port=3306;c.host=db1").unwrap();
+    /// ucdf.normalize();
+    /// assert_eq!(ucdf.to_string(), "t=db.mysql;c.host=db1;c.port=3306;m.owner=data");
+    /// ```
+    pub fn normalize(&mut self) {
+        self.connection.0.sort_keys();
+        self.structure.0.sort_keys();
+        self.section_access.sort_keys();
+        self.metadata.0.sort_keys();
+        self.extensions.sort_keys();
+        for data in self.structure.0.values_mut() {
+            if let StructureData::Lazy(lazy) = data {
+                if let Ok(parsed) = lazy.get() {
+                    *data = parsed.clone();
+                }
+            }
         }
-        Ok(endpoints)
     }
 
-    /// Convert the UCDF structure to a string
-    pub fn to_string(&self) -> String {
-        let mut parts = Vec::new();
+    /// Write the UCDF string into `w`, as `to_string()` returns it
+    ///
+    /// Sections are written straight into `w`, without building a string per
+    /// section first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=db.postgresql;c.host=db1;m.owner=data").unwrap();
+    /// let mut out = String::from("source: ");
+    /// ucdf.write_to(&mut out).unwrap();
+    /// assert_eq!(out, "source: t=db.postgresql;c.host=db1;m.owner=data");
+    /// ```
+    pub fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        self.write_sections(w, false, ";")
+    }
+
+    // Write the sections as `key=value` separated by `separator`, optionally
+    // sorting keys within sections
+    fn write_sections<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        sorted: bool,
+        separator: &str,
+    ) -> fmt::Result {
+        fn entries<'a, K: Ord + ?Sized + 'a, V: ?Sized + 'a, W>(
+            w: &mut W,
+            mut iter: impl Iterator<Item = (&'a K, &'a V)>,
+            sorted: bool,
+            mut write: impl FnMut(&mut W, &K, &V) -> fmt::Result,
+        ) -> fmt::Result {
+            if !sorted {
+                return iter.try_for_each(|(key, value)| write(w, key, value));
+            }
+            let mut entries: Vec<_> = iter.collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            entries
+                .into_iter()
+                .try_for_each(|(key, value)| write(w, key, value))
+        }
 
         // Type section
-        parts.push(format!("t={}", self.source_type));
+        write!(w, "t={}", self.source_type)?;
+
+        // Includes, in declaration order
+        for include in &self.includes {
+            write!(w, "{separator}i={}", quote_value(include))?;
+        }
 
         // Connection parameters
-        for (key, value) in self.connection.iter() {
-            let formatted_value = if value.contains(';')
-                || value.contains('=')
-                || value.contains(',')
-                || value.contains(':')
-            {
-                format!("\"{}\"", value)
-            } else {
-                value.clone()
+        entries(w, self.connection.iter(), sorted, |w, key, value| {
+            write!(w, "{separator}c.{}=", key)?;
+            self.write_value(w, KeyPath::Connection(key), value)
+        })?;
+        if let Some(ciphertext) = &self.encrypted_connection {
+            write!(w, "{separator}c!={}", ciphertext)?;
+        }
+
+        // Structure sections, formatted into a reused buffer to decide on quoting
+        use fmt::Write as _;
+        let mut buffer = String::new();
+        entries(w, self.structure.iter(), sorted, |w, key, value| {
+            // The canonical form must not depend on whether the parser was
+            // lazy, so write lazy values as they parse, like `normalize()`
+            let value = match value {
+                StructureData::Lazy(lazy) if sorted => lazy.get().unwrap_or(value),
+                _ => value,
             };
-            parts.push(format!("c.{}={}", key, formatted_value));
-        }
-
-        // Structure sections
-        for (key, value) in &self.structure {
-            match value {
-                StructureData::Fields(fields) => {
-                    let fields_str = fields
-                        .iter()
-                        .map(|field| field.to_string())
-                        .collect::<Vec<String>>()
-                        .join(",");
-                    parts.push(format!("s.{}={}", key, fields_str));
-                }
-                StructureData::Endpoints(endpoints) => {
-                    let endpoints_str = endpoints
-                        .iter()
-                        .map(|endpoint| endpoint.to_string())
-                        .collect::<Vec<String>>()
-                        .join(",");
-                    parts.push(format!("s.{}={}", key, endpoints_str));
-                }
-                StructureData::Format(format) => {
-                    parts.push(format!("s.{}={}", key, format));
-                }
-                StructureData::Custom(_, custom_value) => {
-                    parts.push(format!("s.{}={}", key, custom_value));
-                }
+            // Field and endpoint lists quote their own tokens
+            if let StructureData::Fields(_) | StructureData::Endpoints(_) = value {
+                return write!(w, "{separator}s.{}={}", key, value);
             }
-        }
+            buffer.clear();
+            write!(buffer, "{}", value)?;
+            write!(w, "{separator}s.{}={}", key, quote_structure(&buffer))
+        })?;
 
         // Access mode
         if let Some(access_mode) = &self.access_mode {
-            parts.push(format!("a={}", access_mode));
+            write!(w, "{separator}a={}", access_mode)?;
         }
+        entries(w, self.section_access.iter(), sorted, |w, key, mode| {
+            write!(w, "{separator}a.{}={}", key, mode)
+        })?;
 
         // Metadata
-        for (key, value) in self.metadata.iter() {
-            let formatted_value = if value.contains(';')
-                || value.contains('=')
-                || value.contains(',')
-                || value.contains(':')
-            {
-                format!("\"{}\"", value)
-            } else {
-                value.clone()
+        entries(w, self.metadata.iter(), sorted, |w, key, value| {
+            write!(w, "{separator}m.{}=", key)?;
+            self.write_value(w, KeyPath::Meta(key), value)
+        })?;
+
+        // Custom sections
+        entries(w, self.extensions.iter(), sorted, |w, key, value| {
+            buffer.clear();
+            write!(buffer, "{}", value)?;
+            write!(w, "{separator}{}={}", key, quote_value(&buffer))
+        })
+    }
+}
+
+/// Value displayed in quotes if it contains one of `delimiters` or a quote,
+/// or starts or ends with whitespace, which the parser trims off descriptors
+///
+/// Inside quotes, `"` and `\` are escaped with a backslash, which the parser
+/// decodes.
+pub(crate) struct Quoted<'a> {
+    value: &'a str,
+    delimiters: &'static [char],
+}
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let padded = self.value.starts_with(char::is_whitespace)
+            || self.value.ends_with(char::is_whitespace);
+        // Unquoted, a line break would end the section and ` #` start a comment
+        let layout = self.value.contains(['\n', '\r'])
+            || self.value.contains(" #")
+            || self.value.contains("\t#");
+        if !padded && !layout && !self.value.contains(self.delimiters) && !self.value.contains('"')
+        {
+            return f.write_str(self.value);
+        }
+        f.write_str("\"")?;
+        let mut rest = self.value;
+        while let Some(index) = rest.find(['"', '\\', '\n', '\r']) {
+            let escape = match rest.as_bytes()[index] {
+                b'\n' => 'n',
+                b'\r' => 'r',
+                c => c as char,
             };
-            parts.push(format!("m.{}={}", key, formatted_value));
+            write!(f, "{}\\{}", &rest[..index], escape)?;
+            rest = &rest[index + 1..];
         }
+        write!(f, "{}\"", rest)
+    }
+}
+
+// Quote a value if it contains one of `delimiters`
+pub(crate) fn quote_with<'a>(value: &'a str, delimiters: &'static [char]) -> Quoted<'a> {
+    Quoted { value, delimiters }
+}
 
-        parts.join(";")
+// Quote a connection or metadata value if it contains UCDF delimiters
+pub(crate) fn quote_value(value: &str) -> Quoted<'_> {
+    quote_with(value, &[';', '=', ',', ':'])
+}
+
+// Quote a structure value only if it contains a section separator, so field
+// and endpoint lists stay unquoted
+pub(crate) fn quote_structure(value: &str) -> Quoted<'_> {
+    quote_with(value, &[';'])
+}
+
+impl fmt::Display for UCDF {
+    /// Convert the UCDF structure to a string
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
     }
 }
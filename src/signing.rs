@@ -0,0 +1,94 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Metadata key holding the descriptor signature
+pub const SIGNATURE_KEY: &str = "sig";
+
+impl UCDF {
+    /// Sign the descriptor with HMAC-SHA256, storing the hex digest in `m.sig`
+    ///
+    /// The signature covers the canonical form of every other section, so any
+    /// change to the type, connection, structure, access mode or metadata
+    /// invalidates it. An existing signature is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let mut ucdf = parse("t=db.postgresql;c.host=db.prod;a=r").unwrap();
+    /// ucdf.sign(b"shared key");
+    /// assert!(ucdf.verify(b"shared key").is_ok());
+    ///
+    /// let tampered = parse(&ucdf.to_string().replace("db.prod", "evil.example")).unwrap();
+    /// assert!(tampered.verify(b"shared key").is_err());
+    /// ```
+    pub fn sign(&mut self, key: &[u8]) -> &mut Self {
+        let digest = self.signature_mac(key).finalize().into_bytes();
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.metadata.insert(SIGNATURE_KEY, hex);
+        self
+    }
+
+    /// Verify the `m.sig` signature against the descriptor contents
+    pub fn verify(&self, key: &[u8]) -> Result<()> {
+        let hex = self
+            .metadata
+            .get(SIGNATURE_KEY)
+            .ok_or_else(|| Error::InvalidSignature("descriptor is not signed".to_string()))?;
+        let signature = decode_hex(hex)
+            .ok_or_else(|| Error::InvalidSignature("m.sig is not valid hex".to_string()))?;
+        self.signature_mac(key)
+            .verify_slice(&signature)
+            .map_err(|_| Error::InvalidSignature("signature does not match".to_string()))
+    }
+
+    // HMAC over the canonical form without the signature itself
+    fn signature_mac(&self, key: &[u8]) -> HmacSha256 {
+        let mut unsigned = self.clone();
+        unsigned.metadata.remove(SIGNATURE_KEY);
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(unsigned.to_canonical_string().as_bytes());
+        mac
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut ucdf = parse("t=db.postgresql;c.host=db.prod;c.port=5432;m.env=prod").unwrap();
+        assert!(matches!(ucdf.verify(b"k"), Err(Error::InvalidSignature(_))));
+
+        ucdf.sign(b"k");
+        assert_eq!(ucdf.metadata.get(SIGNATURE_KEY).unwrap().len(), 64);
+        let reparsed = parse(&ucdf.to_string()).unwrap();
+        assert!(reparsed.verify(b"k").is_ok());
+        assert!(reparsed.verify(b"other").is_err());
+
+        let mut tampered = reparsed.clone();
+        tampered.add_connection("host", "attacker.example");
+        assert!(tampered.verify(b"k").is_err());
+
+        tampered.sign(b"k");
+        assert!(tampered.verify(b"k").is_ok());
+    }
+}
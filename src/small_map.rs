@@ -0,0 +1,400 @@
+//! Insertion-ordered map for the few keys of a typical section
+//!
+//! Most descriptors have a handful of connection and metadata keys. For those,
+//! a vector searched linearly is faster to build and query than a hash map.
+//! [`SmallMap`] keeps up to [`INLINE_CAPACITY`] entries that way and moves
+//! them to an [`IndexMap`] when a larger section is parsed. Keys are
+//! [interned](crate::intern).
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::SmallMap;
+//!
+//! let mut map = SmallMap::new();
+//! map.insert("host", "db1".to_string());
+//! map.insert("port", "5432".to_string());
+//! assert_eq!(map.get("host").map(String::as_str), Some("db1"));
+//! assert_eq!(map.keys().collect::<Vec<_>>(), ["host", "port"]);
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Index;
+
+use indexmap::IndexMap;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::intern::Key;
+
+/// Number of entries kept inline before spilling to an [`IndexMap`]
+pub const INLINE_CAPACITY: usize = 16;
+
+/// Map from interned [`Key`]s to values, in insertion order
+///
+/// Maps compare equal when they hold the same entries, in any order.
+#[derive(Clone)]
+pub struct SmallMap<V>(Repr<V>);
+
+#[derive(Clone)]
+enum Repr<V> {
+    Inline(Vec<(Key, V)>),
+    Spilled(IndexMap<Key, V>),
+}
+
+impl<V> SmallMap<V> {
+    /// Create an empty map
+    pub const fn new() -> Self {
+        SmallMap(Repr::Inline(Vec::new()))
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Inline(entries) => entries.len(),
+            Repr::Spilled(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if the entries have moved to an [`IndexMap`]
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.0, Repr::Spilled(_))
+    }
+
+    /// Get the position of a key
+    pub fn get_index_of(&self, key: &str) -> Option<usize> {
+        match &self.0 {
+            Repr::Inline(entries) => entries.iter().position(|(k, _)| k == key),
+            Repr::Spilled(map) => map.get_index_of(key),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        match &self.0 {
+            Repr::Inline(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            Repr::Spilled(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            Repr::Spilled(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Insert a value, returning the one it replaced
+    ///
+    /// A replaced key keeps its position, new keys go last.
+    pub fn insert(&mut self, key: impl Into<Key>, value: V) -> Option<V> {
+        let key = key.into();
+        if let Some(slot) = self.get_mut(&key) {
+            return Some(std::mem::replace(slot, value));
+        }
+        self.push(key, value);
+        None
+    }
+
+    /// Insert a new key at `index`, shifting the following entries
+    ///
+    /// Like [`SmallMap::insert`] when the key is already present.
+    pub fn insert_at(&mut self, index: usize, key: impl Into<Key>, value: V) -> Option<V> {
+        let key = key.into();
+        if let Some(slot) = self.get_mut(&key) {
+            return Some(std::mem::replace(slot, value));
+        }
+        self.spill_if_full();
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.insert(index.min(entries.len()), (key, value)),
+            Repr::Spilled(map) => {
+                map.shift_insert(index.min(map.len()), key, value);
+            }
+        }
+        None
+    }
+
+    /// Remove a key, keeping the order of the remaining ones
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        self.remove_full(key).map(|(_, _, value)| value)
+    }
+
+    /// Remove a key, returning its position, the key and the value
+    pub fn remove_full(&mut self, key: &str) -> Option<(usize, Key, V)> {
+        match &mut self.0 {
+            Repr::Inline(entries) => {
+                let index = entries.iter().position(|(k, _)| k == key)?;
+                let (key, value) = entries.remove(index);
+                Some((index, key, value))
+            }
+            Repr::Spilled(map) => map.shift_remove_full(key),
+        }
+    }
+
+    /// Keep only the entries for which `keep` returns `true`
+    pub fn retain(&mut self, mut keep: impl FnMut(&Key, &mut V) -> bool) {
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.retain_mut(|(k, v)| keep(k, v)),
+            Repr::Spilled(map) => map.retain(keep),
+        }
+    }
+
+    /// Sort the entries by key
+    pub fn sort_keys(&mut self) {
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+            Repr::Spilled(map) => map.sort_keys(),
+        }
+    }
+
+    /// Remove all entries, keeping the allocated capacity
+    pub fn clear(&mut self) {
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.clear(),
+            Repr::Spilled(map) => map.clear(),
+        }
+    }
+
+    /// Iterate over keys and values in insertion order
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter(match &self.0 {
+            Repr::Inline(entries) => IterRepr::Inline(entries.iter()),
+            Repr::Spilled(map) => IterRepr::Spilled(map.iter()),
+        })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    fn push(&mut self, key: Key, value: V) {
+        self.spill_if_full();
+        match &mut self.0 {
+            Repr::Inline(entries) => entries.push((key, value)),
+            Repr::Spilled(map) => {
+                map.insert(key, value);
+            }
+        }
+    }
+
+    fn spill_if_full(&mut self) {
+        if let Repr::Inline(entries) = &mut self.0 {
+            if entries.len() >= INLINE_CAPACITY {
+                let map = std::mem::take(entries).into_iter().collect();
+                self.0 = Repr::Spilled(map);
+            }
+        }
+    }
+}
+
+impl<V> Default for SmallMap<V> {
+    fn default() -> Self {
+        SmallMap::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for SmallMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V: PartialEq> PartialEq for SmallMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<V: Eq> Eq for SmallMap<V> {}
+
+impl<V> Index<&str> for SmallMap<V> {
+    type Output = V;
+
+    /// Panics if the key is missing
+    fn index(&self, key: &str) -> &V {
+        self.get(key).expect("key not found in SmallMap")
+    }
+}
+
+impl<K: Into<Key>, V> FromIterator<(K, V)> for SmallMap<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SmallMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<Key>, V> Extend<(K, V)> for SmallMap<V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<V> From<IndexMap<String, V>> for SmallMap<V> {
+    fn from(map: IndexMap<String, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SmallMap<V> {
+    type Item = (&'a Key, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Iter<'a, V> {
+        self.iter()
+    }
+}
+
+impl<V> IntoIterator for SmallMap<V> {
+    type Item = (Key, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> IntoIter<V> {
+        IntoIter(match self.0 {
+            Repr::Inline(entries) => IntoIterRepr::Inline(entries.into_iter()),
+            Repr::Spilled(map) => IntoIterRepr::Spilled(map.into_iter()),
+        })
+    }
+}
+
+impl<V: Serialize> Serialize for SmallMap<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for SmallMap<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct MapVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for MapVisitor<V> {
+            type Value = SmallMap<V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a map with string keys")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut map = SmallMap::new();
+                while let Some((key, value)) = access.next_entry::<Key, V>()? {
+                    map.insert(key, value);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+/// Iterator over the entries of a [`SmallMap`]
+pub struct Iter<'a, V>(IterRepr<'a, V>);
+
+enum IterRepr<'a, V> {
+    Inline(std::slice::Iter<'a, (Key, V)>),
+    Spilled(indexmap::map::Iter<'a, Key, V>),
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (&'a Key, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterRepr::Inline(iter) => iter.next().map(|(key, value)| (key, value)),
+            IterRepr::Spilled(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IterRepr::Inline(iter) => iter.size_hint(),
+            IterRepr::Spilled(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
+
+/// Owning iterator over the entries of a [`SmallMap`]
+pub struct IntoIter<V>(IntoIterRepr<V>);
+
+enum IntoIterRepr<V> {
+    Inline(std::vec::IntoIter<(Key, V)>),
+    Spilled(indexmap::map::IntoIter<Key, V>),
+}
+
+impl<V> Iterator for IntoIter<V> {
+    type Item = (Key, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterRepr::Inline(iter) => iter.next(),
+            IntoIterRepr::Spilled(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IntoIterRepr::Inline(iter) => iter.size_hint(),
+            IntoIterRepr::Spilled(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<V> ExactSizeIterator for IntoIter<V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill() {
+        let mut map: SmallMap<usize> = (0..INLINE_CAPACITY)
+            .map(|i| (format!("k{}", i), i))
+            .collect();
+        assert!(!map.is_spilled());
+        assert_eq!(map.insert("k3", 30), Some(3));
+        assert!(!map.is_spilled());
+
+        map.insert("extra", 99);
+        assert!(map.is_spilled());
+        assert_eq!(map.get("k3"), Some(&30));
+        assert_eq!(map.remove("k0"), Some(0));
+        assert_eq!(map.keys().next().map(Key::as_str), Some("k1"));
+        assert_eq!(map.keys().last().map(Key::as_str), Some("extra"));
+        assert_eq!(map.iter().len(), INLINE_CAPACITY);
+    }
+
+    #[test]
+    fn test_order_and_equality() {
+        let mut a = SmallMap::new();
+        a.insert("x", 1);
+        a.insert("y", 2);
+        let (index, key, value) = a.remove_full("x").unwrap();
+        a.insert_at(index, key, value);
+        assert_eq!(a.keys().collect::<Vec<_>>(), ["x", "y"]);
+
+        let b: SmallMap<i32> = [("y", 2), ("x", 1)].into_iter().collect();
+        assert_eq!(a, b);
+        assert_eq!(format!("{:?}", b), r#"{"y": 2, "x": 1}"#);
+    }
+}
@@ -0,0 +1,114 @@
+//! Known UCDF source types
+//!
+//! Constants for the well-known `category.subtype` pairs and a registry
+//! used to detect unknown (often misspelled) source types.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{parse, source_types};
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=localhost").unwrap();
+//! assert_eq!(ucdf.source_type.to_string(), source_types::DB_POSTGRESQL);
+//! assert!(source_types::is_known(&ucdf.source_type));
+//! ```
+
+use crate::sections::SourceType;
+
+pub const DB_POSTGRESQL: &str = "db.postgresql";
+pub const DB_MYSQL: &str = "db.mysql";
+pub const DB_MARIADB: &str = "db.mariadb";
+pub const DB_SQLITE: &str = "db.sqlite";
+pub const DB_MSSQL: &str = "db.mssql";
+pub const DB_ORACLE: &str = "db.oracle";
+pub const DB_MONGODB: &str = "db.mongodb";
+pub const DB_REDIS: &str = "db.redis";
+pub const DB_CASSANDRA: &str = "db.cassandra";
+pub const DB_CLICKHOUSE: &str = "db.clickhouse";
+pub const DB_ELASTICSEARCH: &str = "db.elasticsearch";
+
+pub const FILE_CSV: &str = "file.csv";
+pub const FILE_JSON: &str = "file.json";
+pub const FILE_PARQUET: &str = "file.parquet";
+pub const FILE_AVRO: &str = "file.avro";
+pub const FILE_ORC: &str = "file.orc";
+pub const FILE_XML: &str = "file.xml";
+pub const FILE_EXCEL: &str = "file.excel";
+pub const FILE_TXT: &str = "file.txt";
+pub const FILE_S3: &str = "file.s3";
+pub const FILE_GCS: &str = "file.gcs";
+pub const FILE_AZBLOB: &str = "file.azblob";
+pub const FILE_FS: &str = "file.fs";
+
+pub const API_REST: &str = "api.rest";
+pub const API_GRAPHQL: &str = "api.graphql";
+pub const API_GRPC: &str = "api.grpc";
+pub const API_SOAP: &str = "api.soap";
+
+pub const STREAM_KAFKA: &str = "stream.kafka";
+pub const STREAM_RABBITMQ: &str = "stream.rabbitmq";
+pub const STREAM_KINESIS: &str = "stream.kinesis";
+pub const STREAM_PULSAR: &str = "stream.pulsar";
+pub const STREAM_NATS: &str = "stream.nats";
+pub const STREAM_MQTT: &str = "stream.mqtt";
+
+/// Registry of known categories and their subtypes
+pub const REGISTRY: &[(&str, &[&str])] = &[
+    (
+        "db",
+        &[
+            "postgresql",
+            "mysql",
+            "mariadb",
+            "sqlite",
+            "mssql",
+            "oracle",
+            "mongodb",
+            "redis",
+            "cassandra",
+            "clickhouse",
+            "elasticsearch",
+        ],
+    ),
+    (
+        "file",
+        &[
+            "csv", "json", "parquet", "avro", "orc", "xml", "excel", "txt", "s3", "gcs", "azblob",
+            "fs",
+        ],
+    ),
+    ("api", &["rest", "graphql", "grpc", "soap"]),
+    (
+        "stream",
+        &["kafka", "rabbitmq", "kinesis", "pulsar", "nats", "mqtt"],
+    ),
+    // References to other catalog entries (`t=ref`) have no subtypes
+    ("ref", &[]),
+];
+
+/// Iterate over all known source types as `category.subtype` strings
+pub fn known_types() -> impl Iterator<Item = String> {
+    REGISTRY.iter().flat_map(|(category, subtypes)| {
+        subtypes
+            .iter()
+            .map(move |subtype| format!("{}.{}", category, subtype))
+    })
+}
+
+/// Check if a category is known
+pub fn is_known_category(category: &str) -> bool {
+    REGISTRY.iter().any(|(known, _)| *known == category)
+}
+
+/// Check if a source type is known
+///
+/// A source type without a subtype is known when its category is known.
+pub fn is_known(source_type: &SourceType) -> bool {
+    REGISTRY
+        .iter()
+        .find(|(category, _)| *category == source_type.category)
+        .is_some_and(|(_, subtypes)| match &source_type.subtype {
+            Some(subtype) => subtypes.contains(&subtype.as_str()),
+            None => true,
+        })
+}
@@ -0,0 +1,146 @@
+//! Descriptors built in const context
+//!
+//! [`UCDF`] owns its strings and maps, so it can't be built by a `const fn`.
+//! [`StaticUcdf`] holds the same sections as `'static` strings and slices,
+//! so well-known descriptors can be declared as `static` or `const` items and
+//! converted into a [`UCDF`] where one is needed.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{AccessMode, StaticUcdf, UCDF};
+//!
+//! static WAREHOUSE: StaticUcdf = UCDF::const_template("db", Some("postgresql"))
+//!     .connection(&[("host", "warehouse.internal"), ("port", "5432")])
+//!     .structure(&[("fields", "id:int,name:str")])
+//!     .access_mode(AccessMode::READ.union(AccessMode::APPEND))
+//!     .metadata(&[("owner", "data")]);
+//!
+//! let ucdf = WAREHOUSE.to_ucdf().unwrap();
+//! assert_eq!(
+//!     ucdf.to_string(),
+//!     "t=db.postgresql;c.host=warehouse.internal;c.port=5432;s.fields=id:int,name:str;a=ra;m.owner=data"
+//! );
+//! ```
+
+use crate::error::Result;
+use crate::intern::Key;
+use crate::parser::structure_value;
+use crate::sections::{AccessMode, SourceType, UCDF};
+
+/// Key-value entries of a [`StaticUcdf`] section, in order
+pub type StaticEntries = &'static [(&'static str, &'static str)];
+
+/// Descriptor made of `'static` parts, built by `const fn`s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticUcdf {
+    pub category: &'static str,
+    pub subtype: Option<&'static str>,
+    pub connection: StaticEntries,
+    /// Structure sections as written, parsed by [`StaticUcdf::to_ucdf`]
+    pub structure: StaticEntries,
+    pub access_mode: Option<AccessMode>,
+    pub metadata: StaticEntries,
+}
+
+impl StaticUcdf {
+    /// Create a descriptor of a source type without other sections
+    pub const fn new(category: &'static str, subtype: Option<&'static str>) -> Self {
+        StaticUcdf {
+            category,
+            subtype,
+            connection: &[],
+            structure: &[],
+            access_mode: None,
+            metadata: &[],
+        }
+    }
+
+    /// Set the connection parameters
+    pub const fn connection(mut self, connection: StaticEntries) -> Self {
+        self.connection = connection;
+        self
+    }
+
+    /// Set the structure sections
+    pub const fn structure(mut self, structure: StaticEntries) -> Self {
+        self.structure = structure;
+        self
+    }
+
+    /// Set the access mode
+    pub const fn access_mode(mut self, access_mode: AccessMode) -> Self {
+        self.access_mode = Some(access_mode);
+        self
+    }
+
+    /// Set the metadata
+    pub const fn metadata(mut self, metadata: StaticEntries) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Get the source type
+    pub fn source_type(&self) -> SourceType {
+        SourceType::new(self.category, self.subtype.map(Into::into))
+    }
+
+    /// Build the descriptor, parsing its structure sections
+    ///
+    /// Keys are used as `'static` strings without interning.
+    pub fn to_ucdf(&self) -> Result<UCDF> {
+        let mut ucdf = UCDF::with_source_type(self.source_type());
+        for (key, value) in self.connection {
+            ucdf.add_connection(Key::from_static(key), *value);
+        }
+        for (key, value) in self.structure {
+            ucdf.structure
+                .insert(key.to_string(), structure_value(key, value)?);
+        }
+        ucdf.access_mode = self.access_mode;
+        for (key, value) in self.metadata {
+            ucdf.add_metadata(Key::from_static(key), *value);
+        }
+        Ok(ucdf)
+    }
+}
+
+impl UCDF {
+    /// Start a [`StaticUcdf`] in const context
+    pub const fn const_template(
+        category: &'static str,
+        subtype: Option<&'static str>,
+    ) -> StaticUcdf {
+        StaticUcdf::new(category, subtype)
+    }
+}
+
+impl TryFrom<&StaticUcdf> for UCDF {
+    type Error = crate::error::Error;
+
+    fn try_from(ucdf: &StaticUcdf) -> Result<UCDF> {
+        ucdf.to_ucdf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const CSV: StaticUcdf = UCDF::const_template("file", Some("csv"))
+        .connection(&[("path", "/data/users.csv")])
+        .access_mode(AccessMode::READ);
+
+    #[test]
+    fn test_static_ucdf() {
+        let ucdf = UCDF::try_from(&CSV).unwrap();
+        assert_eq!(
+            ucdf,
+            parse("t=file.csv;c.path=/data/users.csv;a=r").unwrap()
+        );
+
+        const BROKEN: StaticUcdf = CSV.structure(&[("fields", ":int")]);
+        assert!(BROKEN.to_ucdf().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! OpenDAL service configuration for object-store descriptors
+//!
+//! `file.s3`, `file.gcs`, `file.azblob` and `file.fs` descriptors convert to
+//! an [`OpendalConfig`]: the OpenDAL scheme and the service options, ready
+//! for `opendal::Operator::via_iter`. Connection keys named like the
+//! service options are copied, and `c.opendal.<option>` passes any other
+//! option through. `c.path` stands in for `root` when it isn't set.
+//!
+//! | Source        | Required key  | Copied keys                                                                 |
+//! |---------------|---------------|-----------------------------------------------------------------------------|
+//! | `file.s3`     | `c.bucket`    | `region`, `endpoint`, `access_key_id`, `secret_access_key`, `session_token` |
+//! | `file.gcs`    | `c.bucket`    | `endpoint`, `credential`, `credential_path`                                 |
+//! | `file.azblob` | `c.container` | `endpoint`, `account_name`, `account_key`, `sas_token`                      |
+//! | `file.fs`     | `c.root`      |                                                                             |
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::parse;
+//!
+//! let ucdf = parse("t=file.s3;c.bucket=lake;c.region=eu-west-1;c.path=/raw/events").unwrap();
+//! let config = ucdf.to_opendal_config().unwrap();
+//! assert_eq!(config.scheme, "s3");
+//! assert_eq!(config.get("root"), Some("/raw/events"));
+//! // let op = opendal::Operator::via_iter(config.scheme.parse()?, config.options)?;
+//! ```
+
+use crate::error::{Error, Result};
+use crate::sections::UCDF;
+
+/// OpenDAL scheme and service options of a descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpendalConfig {
+    /// Scheme name, as `opendal::Scheme` parses it
+    pub scheme: &'static str,
+    /// Service options in the order they were found
+    pub options: Vec<(String, String)>,
+}
+
+impl OpendalConfig {
+    /// Get a service option
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// Scheme, required option and copied options of each supported subtype
+const SERVICES: &[(&str, &str, &[&str])] = &[
+    (
+        "s3",
+        "bucket",
+        &[
+            "region",
+            "endpoint",
+            "access_key_id",
+            "secret_access_key",
+            "session_token",
+        ],
+    ),
+    (
+        "gcs",
+        "bucket",
+        &["endpoint", "credential", "credential_path"],
+    ),
+    (
+        "azblob",
+        "container",
+        &["endpoint", "account_name", "account_key", "sas_token"],
+    ),
+    ("fs", "root", &[]),
+];
+
+impl UCDF {
+    /// Convert an object-store descriptor to OpenDAL service configuration
+    pub fn to_opendal_config(&self) -> Result<OpendalConfig> {
+        let subtype = self.source_type.subtype.as_deref().unwrap_or_default();
+        let Some((scheme, required, copied)) = SERVICES
+            .iter()
+            .find(|(scheme, _, _)| self.source_type.is_file() && *scheme == subtype)
+        else {
+            return Err(Error::UnsupportedSource(format!(
+                "{} (OpenDAL configuration requires a file.s3, file.gcs, file.azblob or file.fs source)",
+                self.source_type
+            )));
+        };
+        let get = |key: &str| self.connection.get(key).cloned();
+
+        let mut options = Vec::new();
+        let root = get("root").or_else(|| get("path"));
+        if *required != "root" {
+            let value = get(required).ok_or_else(|| {
+                Error::InvalidFormat(format!(
+                    "{} descriptors require c.{}",
+                    self.source_type, required
+                ))
+            })?;
+            options.push((required.to_string(), value));
+        } else if root.is_none() {
+            return Err(Error::InvalidFormat(format!(
+                "{} descriptors require c.root or c.path",
+                self.source_type
+            )));
+        }
+        if let Some(root) = root {
+            options.push(("root".to_string(), root));
+        }
+        for key in *copied {
+            if let Some(value) = get(key) {
+                options.push((key.to_string(), value));
+            }
+        }
+        for (key, value) in self.connection.iter() {
+            if let Some(option) = key.strip_prefix("opendal.") {
+                options.push((option.to_string(), value.clone()));
+            }
+        }
+        Ok(OpendalConfig { scheme, options })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::lint;
+    use crate::parser::parse;
+    use crate::secrets::{SecretResolver, REDACTED};
+
+    #[test]
+    fn test_opendal_config() {
+        let ucdf = parse(
+            "t=file.azblob;c.container=raw;c.account_name=lake;c.opendal.batch_max_operations=100",
+        )
+        .unwrap();
+        let config = ucdf.to_opendal_config().unwrap();
+        assert_eq!(config.scheme, "azblob");
+        assert_eq!(
+            config.options,
+            [
+                ("container".to_string(), "raw".to_string()),
+                ("account_name".to_string(), "lake".to_string()),
+                ("batch_max_operations".to_string(), "100".to_string()),
+            ]
+        );
+
+        let fs = parse("t=file.fs;c.path=/data").unwrap();
+        assert_eq!(fs.to_opendal_config().unwrap().get("root"), Some("/data"));
+
+        assert!(parse("t=file.gcs;c.path=/raw")
+            .unwrap()
+            .to_opendal_config()
+            .is_err());
+        assert!(parse("t=file.fs").unwrap().to_opendal_config().is_err());
+        assert!(parse("t=file.csv;c.path=/data/a.csv")
+            .unwrap()
+            .to_opendal_config()
+            .is_err());
+        assert!(parse("t=db.s3").unwrap().to_opendal_config().is_err());
+    }
+
+    #[test]
+    fn test_plaintext_credentials() {
+        let ucdf = parse(
+            "t=file.azblob;c.container=raw;c.account_key=k3y;c.sas_token=sv%3D1;c.endpoint=https://lake",
+        )
+        .unwrap();
+        let keys: Vec<_> = lint(&ucdf)
+            .into_iter()
+            .filter(|lint| lint.code == "plaintext-secret")
+            .filter_map(|lint| lint.key)
+            .collect();
+        assert_eq!(keys, ["c.account_key", "c.sas_token"]);
+
+        let ucdf =
+            parse("t=file.s3;c.bucket=raw;c.secret_access_key=abc;c.session_token=xyz").unwrap();
+        let redacted = SecretResolver::redact(&ucdf);
+        assert_eq!(
+            redacted.connection.get("secret_access_key").unwrap(),
+            REDACTED
+        );
+        assert_eq!(redacted.connection.get("session_token").unwrap(), REDACTED);
+    }
+}
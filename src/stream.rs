@@ -0,0 +1,89 @@
+//! Asynchronous catalog reading
+//!
+//! [`read_catalog`] parses a catalog manifest (`name: ucdf` lines, see
+//! [`Catalog::load`](crate::Catalog::load)) from any [`AsyncBufRead`], such as
+//! a large file or a network stream, yielding entries as they are read.
+//!
+//! # Examples
+//!
+//! ```
+//! use futures_util::StreamExt;
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let manifest = b"# sources\nusers: t=file.csv;c.path=/data/users.csv\n";
+//! let entries: Vec<_> = ucdf::read_catalog(&manifest[..]).collect().await;
+//! let (name, ucdf) = entries[0].as_ref().unwrap();
+//! assert_eq!(name, "users");
+//! assert!(ucdf.source_type.is_file());
+//! # });
+//! ```
+
+use futures_util::stream::{self, Stream};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::catalog::parse_entry;
+use crate::error::{Error, Result, ResultExt};
+use crate::sections::UCDF;
+
+/// Read catalog entries from a manifest stream
+///
+/// Blank lines and `#` comments are skipped. An invalid entry yields an error
+/// and reading continues with the next line; an I/O error ends the stream.
+/// `@include` lines cannot be resolved without a file system and yield an
+/// error.
+pub fn read_catalog<R>(reader: R) -> impl Stream<Item = Result<(String, UCDF)>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    stream::unfold(
+        (reader.lines(), 0, false),
+        |(mut lines, mut number, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                number += 1;
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => return None,
+                    Err(e) => return Some((Err(Error::from(e)), (lines, number, true))),
+                };
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+                let entry = if trimmed.starts_with("@include") {
+                    Err(Error::InvalidFormat(
+                        "`@include` is not supported when streaming".to_string(),
+                    ))
+                } else {
+                    parse_entry(trimmed).map(|(name, ucdf)| (name.to_string(), ucdf))
+                };
+                let entry = entry.with_context(|| format!("line {}", number));
+                return Some((entry, (lines, number, false)));
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_read_catalog() {
+        let manifest = "users: t=file.csv;c.path=/data/users.csv\n\n\
+                        not an entry\n\
+                        @include more.ucdf\n\
+                        events: t=stream.kafka;c.topic=events\n";
+        let entries: Vec<_> = read_catalog(manifest.as_bytes()).collect().await;
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].as_ref().unwrap().0, "users");
+        let err = entries[1].as_ref().unwrap_err();
+        assert_eq!(err.contexts().next(), Some("line 3"));
+        assert!(entries[2].is_err());
+        assert_eq!(entries[3].as_ref().unwrap().0, "events");
+    }
+}
@@ -0,0 +1,111 @@
+use serde::Serialize;
+
+use crate::lint::{is_secret_key, is_secret_reference};
+use crate::sections::UCDF;
+
+/// Digest of a descriptor for catalog dashboards, see [`UCDF::summary`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceSummary {
+    pub source_type: String,
+    /// Prefixes of the sections present, in serialization order (`t`, `i`, `c`, `c!`, `s`, `a`, `m`)
+    pub sections: Vec<&'static str>,
+    pub connection_keys: usize,
+    pub structure_keys: usize,
+    pub metadata_keys: usize,
+    /// Whether `s.fields` is declared
+    pub has_schema: bool,
+    pub field_count: usize,
+    /// Whether any connection key holds a secret, plaintext or referenced
+    pub has_secrets: bool,
+    /// Whether any secret is stored in plaintext
+    pub has_plaintext_secrets: bool,
+    /// Whether the connection section is encrypted (`c!=`)
+    pub encrypted: bool,
+    /// Length of the serialized form in bytes
+    pub serialized_len: usize,
+}
+
+impl UCDF {
+    /// Summarize the descriptor without walking its sections manually
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::parse;
+    ///
+    /// let ucdf = parse("t=db.postgresql;c.host=db1;c.password=${PG_PASSWORD};s.fields=id:int").unwrap();
+    /// let summary = ucdf.summary();
+    /// assert_eq!(summary.sections, vec!["t", "c", "s"]);
+    /// assert!(summary.has_schema && summary.has_secrets && !summary.has_plaintext_secrets);
+    /// ```
+    pub fn summary(&self) -> SourceSummary {
+        let mut sections = vec!["t"];
+        let present = [
+            ("i", !self.includes.is_empty()),
+            ("c", !self.connection.is_empty()),
+            ("c!", self.encrypted_connection.is_some()),
+            ("s", !self.structure.is_empty()),
+            (
+                "a",
+                self.access_mode.is_some() || !self.section_access.is_empty(),
+            ),
+            ("m", !self.metadata.is_empty()),
+        ];
+        sections.extend(
+            present
+                .iter()
+                .filter(|(_, present)| *present)
+                .map(|(prefix, _)| *prefix),
+        );
+
+        let secrets: Vec<&String> = self
+            .connection
+            .iter()
+            .filter(|(key, value)| is_secret_key(key) && !value.is_empty())
+            .map(|(_, value)| value)
+            .collect();
+        let field_count = self
+            .structure
+            .get("fields")
+            .and_then(|data| data.as_fields())
+            .map(<[_]>::len);
+
+        SourceSummary {
+            source_type: self.source_type.to_string(),
+            sections,
+            connection_keys: self.connection.len(),
+            structure_keys: self.structure.len(),
+            metadata_keys: self.metadata.len(),
+            has_schema: field_count.is_some(),
+            field_count: field_count.unwrap_or_default(),
+            has_secrets: !secrets.is_empty(),
+            has_plaintext_secrets: secrets.iter().any(|value| !is_secret_reference(value)),
+            encrypted: self.encrypted_connection.is_some(),
+            serialized_len: self.byte_len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_summary() {
+        let ucdf = parse(
+            "t=api.rest;i=defaults;c.url=\"https://api.example.com\";c.token=abc;s.endpoints=/users:GET;a=r;m.owner=team",
+        )
+        .unwrap();
+        let summary = ucdf.summary();
+        assert_eq!(summary.source_type, "api.rest");
+        assert_eq!(summary.sections, vec!["t", "i", "c", "s", "a", "m"]);
+        assert_eq!(summary.connection_keys, 2);
+        assert_eq!(summary.structure_keys, 1);
+        assert_eq!(summary.metadata_keys, 1);
+        assert!(!summary.has_schema);
+        assert_eq!(summary.field_count, 0);
+        assert!(summary.has_plaintext_secrets);
+        assert!(!summary.encrypted);
+        assert_eq!(summary.serialized_len, ucdf.to_string().len());
+    }
+}
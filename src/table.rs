@@ -0,0 +1,213 @@
+//! DataFusion table registration for file sources
+//!
+//! [`UCDF::to_table_registration`] turns a `file.csv` or `file.parquet`
+//! descriptor into a [`TableRegistration`]: the table name, the path, the
+//! CSV options and an Arrow schema built from `s.fields`. Each part maps to
+//! an argument of DataFusion's `register_csv` and `register_parquet`.
+//!
+//! | Field type    | Arrow type                        |
+//! |---------------|-----------------------------------|
+//! | `str`, `json` | `Utf8`                            |
+//! | `int`         | `Int64`                           |
+//! | `float`       | `Float64`                         |
+//! | `bool`        | `Boolean`                         |
+//! | `date`        | `Date32`                          |
+//! | `datetime`    | `Timestamp(Microsecond, "UTC")`   |
+//! | `decimal`     | `Decimal128(38, 10)`              |
+//! | `uuid`        | `Utf8`                            |
+//! | `duration`    | `Utf8` (ISO 8601, as written)     |
+//! | `array<T>`    | `List(T)`                         |
+//!
+//! Fields are nullable when they have the `nullable` modifier.
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{parse, TableFormat};
+//!
+//! let ucdf = parse("t=file.csv;c.path=/data/users.csv;c.delimiter=\";\";s.fields=id:int,name:str").unwrap();
+//! let table = ucdf.to_table_registration("users").unwrap();
+//! assert_eq!(table.path, "/data/users.csv");
+//! assert!(matches!(table.format, TableFormat::Csv { delimiter: b';', has_header: true, .. }));
+//! assert_eq!(table.schema.unwrap().fields().len(), 2);
+//! ```
+
+use std::sync::Arc;
+
+use arrow_schema::{DataType as ArrowType, Field as ArrowField, Schema, SchemaRef, TimeUnit};
+
+use crate::error::{Error, Result};
+use crate::sections::{DataType, UCDF};
+use crate::types::Field;
+
+/// File format of a [`TableRegistration`] and its read options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// CSV, read with `c.header` (`true` by default), `c.delimiter` and
+    /// `c.quote`
+    Csv {
+        has_header: bool,
+        delimiter: u8,
+        quote: u8,
+    },
+    Parquet,
+}
+
+/// What DataFusion needs to register a file source as a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRegistration {
+    pub name: String,
+    pub path: String,
+    pub format: TableFormat,
+    /// Schema from `s.fields`, `None` to let DataFusion infer it
+    pub schema: Option<SchemaRef>,
+}
+
+impl UCDF {
+    /// Describe how to register this file source as table `name`
+    pub fn to_table_registration(&self, name: impl Into<String>) -> Result<TableRegistration> {
+        let option = |key: &str| self.connection.get(key).map(String::as_str);
+        let format = match (
+            self.source_type.is_file(),
+            self.source_type.subtype.as_deref(),
+        ) {
+            (true, Some("csv")) => TableFormat::Csv {
+                has_header: match option("header") {
+                    None => true,
+                    Some(value) => value.parse().map_err(|_| {
+                        Error::InvalidFormat(format!("c.header must be a boolean: {}", value))
+                    })?,
+                },
+                delimiter: self.csv_byte("delimiter")?.unwrap_or(b','),
+                quote: self.csv_byte("quote")?.unwrap_or(b'"'),
+            },
+            (true, Some("parquet")) => TableFormat::Parquet,
+            _ => {
+                return Err(Error::UnsupportedSource(format!(
+                    "{} (tables require a file.csv or file.parquet source)",
+                    self.source_type
+                )))
+            }
+        };
+        let path = option("path")
+            .ok_or_else(|| Error::InvalidFormat("file source without c.path".to_string()))?;
+
+        Ok(TableRegistration {
+            name: name.into(),
+            path: path.to_string(),
+            format,
+            schema: self.arrow_schema()?.map(Arc::new),
+        })
+    }
+
+    /// Build an Arrow schema from `s.fields`, if declared
+    pub fn arrow_schema(&self) -> Result<Option<Schema>> {
+        let Some(fields) = self
+            .structure
+            .get("fields")
+            .and_then(|data| data.as_fields())
+        else {
+            return Ok(None);
+        };
+        let fields = fields.iter().map(arrow_field).collect::<Result<Vec<_>>>()?;
+        Ok(Some(Schema::new(fields)))
+    }
+
+    fn csv_byte(&self, key: &str) -> Result<Option<u8>> {
+        match self.connection.get(key).map(String::as_str) {
+            None => Ok(None),
+            Some("tab" | "\\t") => Ok(Some(b'\t')),
+            Some(value) if value.len() == 1 && value.is_ascii() => Ok(Some(value.as_bytes()[0])),
+            Some(value) => Err(Error::InvalidFormat(format!(
+                "c.{} must be a single ASCII character: {}",
+                key, value
+            ))),
+        }
+    }
+}
+
+fn arrow_field(field: &Field) -> Result<ArrowField> {
+    let dtype = field.dtype.parse::<DataType>()?;
+    Ok(ArrowField::new(
+        &field.name,
+        arrow_type(&dtype)?,
+        field.has_modifier("nullable"),
+    ))
+}
+
+fn arrow_type(dtype: &DataType) -> Result<ArrowType> {
+    Ok(match dtype {
+        DataType::String | DataType::Json | DataType::Uuid | DataType::Duration => ArrowType::Utf8,
+        DataType::Integer => ArrowType::Int64,
+        DataType::Float => ArrowType::Float64,
+        DataType::Boolean => ArrowType::Boolean,
+        DataType::Date => ArrowType::Date32,
+        DataType::DateTime => ArrowType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        DataType::Decimal => ArrowType::Decimal128(38, 10),
+        DataType::Array(inner) => ArrowType::List(Arc::new(ArrowField::new_list_field(
+            arrow_type(inner)?,
+            true,
+        ))),
+        _ => {
+            return Err(Error::InvalidTypeDeclaration(format!(
+                "{} has no Arrow type",
+                dtype
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_table_registration() {
+        let ucdf = parse(
+            "t=file.parquet;c.path=/data/events.parquet;\
+             s.fields=id:int,at:datetime,tags:array<str>:nullable,amount:decimal",
+        )
+        .unwrap();
+        let table = ucdf.to_table_registration("events").unwrap();
+        assert_eq!(table.name, "events");
+        assert_eq!(table.format, TableFormat::Parquet);
+        let schema = table.schema.unwrap();
+        assert_eq!(schema.field(0).data_type(), &ArrowType::Int64);
+        assert!(!schema.field(0).is_nullable());
+        assert_eq!(
+            schema.field(1).data_type(),
+            &ArrowType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        );
+        assert!(
+            matches!(schema.field(2).data_type(), ArrowType::List(item) if item.data_type() == &ArrowType::Utf8)
+        );
+        assert!(schema.field(2).is_nullable());
+
+        let csv = parse("t=file.csv;c.path=/data/a.tsv;c.delimiter=tab;c.header=false").unwrap();
+        let table = csv.to_table_registration("a").unwrap();
+        assert_eq!(
+            table.format,
+            TableFormat::Csv {
+                has_header: false,
+                delimiter: b'\t',
+                quote: b'"'
+            }
+        );
+        assert_eq!(table.schema, None);
+
+        assert!(parse("t=file.json;c.path=/a.json")
+            .unwrap()
+            .to_table_registration("a")
+            .is_err());
+        assert!(parse("t=file.csv")
+            .unwrap()
+            .to_table_registration("a")
+            .is_err());
+        let custom = parse("t=file.csv;c.path=/a.csv;s.fields=geo:point").unwrap();
+        assert!(matches!(
+            custom.to_table_registration("a"),
+            Err(Error::InvalidTypeDeclaration(_))
+        ));
+    }
+}
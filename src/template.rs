@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result, ResultExt};
+use crate::parser::parse;
+use crate::sections::UCDF;
+
+/// UCDF descriptor with `{{var}}` placeholders
+///
+/// Placeholders may appear anywhere in the descriptor and are replaced
+/// verbatim when rendering, so values must not contain `;`, `=`, `"`, `\`
+/// or line breaks, which would add or end sections or escape a closing
+/// quote. Variable names consist of ASCII letters, digits, `_` and `-`;
+/// whitespace inside the braces is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use ucdf::Template;
+///
+/// let template: Template = "t=db.postgresql;c.host={{tenant}}.db.internal;c.db={{tenant}}"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(template.variables().collect::<Vec<_>>(), vec!["tenant"]);
+///
+/// let vars = HashMap::from([("tenant".to_string(), "acme".to_string())]);
+/// let ucdf = template.render(&vars).unwrap();
+/// assert_eq!(ucdf.connection.get("host").unwrap(), "acme.db.internal");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Variable(String),
+}
+
+impl Template {
+    /// Iterate over the declared variables in order of first appearance
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        let mut seen = Vec::new();
+        self.segments
+            .iter()
+            .filter_map(move |segment| match segment {
+                Segment::Variable(name) if !seen.contains(&name) => {
+                    seen.push(name);
+                    Some(name.as_str())
+                }
+                _ => None,
+            })
+    }
+
+    /// Substitute the variables and parse the result
+    ///
+    /// Fails with [`Error::MissingVariables`] listing every variable without a
+    /// value, and with [`Error::InvalidFormat`] if a value contains UCDF
+    /// delimiters.
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<UCDF> {
+        let missing: Vec<String> = self
+            .variables()
+            .filter(|name| !vars.contains_key(*name))
+            .map(str::to_string)
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::MissingVariables(missing));
+        }
+        if let Some(name) = self
+            .variables()
+            .find(|name| vars[*name].contains([';', '=', '"', '\\', '\n', '\r']))
+        {
+            return Err(Error::InvalidFormat(format!(
+                "value of `{}` must not contain `;`, `=`, `\"`, `\\` or line breaks",
+                name
+            )));
+        }
+
+        let rendered: String = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Variable(name) => vars[name].as_str(),
+            })
+            .collect();
+        parse(&rendered).context("in rendered template")
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = s;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| Error::InvalidFormat(format!("unclosed placeholder: {}", rest)))?;
+            let name = after[..end].trim();
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(Error::InvalidFormat(format!(
+                    "invalid variable name: `{}`",
+                    &after[..end]
+                )));
+            }
+            segments.push(Segment::Variable(name.to_string()));
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(Template {
+            source: s.to_string(),
+            segments,
+        })
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_per_tenant() {
+        let template: Template =
+            "t=db.postgresql;c.host={{ tenant }}.{{region}}.internal;c.db={{tenant}};a=r"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            template.variables().collect::<Vec<_>>(),
+            vec!["tenant", "region"]
+        );
+
+        let ucdf = template
+            .render(&vars(&[("tenant", "acme"), ("region", "eu")]))
+            .unwrap();
+        assert_eq!(ucdf.connection.get("host").unwrap(), "acme.eu.internal");
+        assert_eq!(ucdf.connection.get("db").unwrap(), "acme");
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let template: Template = "t={{kind}};c.host={{host}}".parse().unwrap();
+        match template.render(&vars(&[("host", "db1")])) {
+            Err(Error::MissingVariables(names)) => assert_eq!(names, vec!["kind"]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!("t=db;c.host={{host".parse::<Template>().is_err());
+        assert!("t=db;c.host={{}}".parse::<Template>().is_err());
+        assert!("t=db;c.host={{a b}}".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn test_reject_injected_sections() {
+        let template: Template = "t=db.postgresql;c.host=db1;c.db={{tenant}}"
+            .parse()
+            .unwrap();
+        for value in ["acme;c.host=evil", "acme\nc.host=evil", "a=b", "acme\""] {
+            let err = template.render(&vars(&[("tenant", value)])).unwrap_err();
+            assert!(matches!(err, Error::InvalidFormat(_)), "{:?}", value);
+        }
+        let ucdf = template.render(&vars(&[("tenant", "acme-eu")])).unwrap();
+        assert_eq!(ucdf.connection.get("host").unwrap(), "db1");
+
+        // A trailing backslash would escape the closing quote
+        let template: Template = "t=db.postgresql;c.password=\"{{pw}}\"".parse().unwrap();
+        let err = template.render(&vars(&[("pw", "abc\\")])).unwrap_err();
+        assert!(matches!(err, Error::InvalidFormat(_)));
+    }
+}
@@ -1,13 +1,56 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::time::Duration;
 
 use bon::bon;
+#[cfg(feature = "with-chrono")]
+use chrono::{DateTime, FixedOffset, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
+use crate::parser::split_quoted;
+use crate::sections::quote_with;
 
 /// Represents a field value with type information
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// New value kinds may be added in minor releases; use [`DataValue::parse`]
+/// and the `as_*` accessors instead of exhaustive matches.
+///
+/// # Ordering
+///
+/// Values are totally ordered, so they can be sorted and used as keys of
+/// ordered maps and hash sets. Values of different kinds are ordered by kind,
+/// in the order the variants are declared, so any string sorts before any
+/// integer. Values of one kind are ordered by their natural order:
+///
+/// - floats as by [`f64::total_cmp`], except that `-0.0` equals `0.0` and
+///   all NaNs are equal and sort after positive infinity;
+/// - textual values, including dates and decimals, by their text as written;
+/// - custom values by type name, then by value.
+///
+/// Equality and hashing agree with this order.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use ucdf::DataValue;
+///
+/// let values: BTreeSet<_> = [f64::NAN, 1.5, -0.0, f64::NAN, 0.0]
+///     .into_iter()
+///     .map(DataValue::Float)
+///     .collect();
+/// assert_eq!(values.len(), 3);
+/// assert_eq!(values.first(), Some(&DataValue::Float(0.0)));
+/// assert_eq!(values.last(), Some(&DataValue::Float(f64::NAN)));
+/// assert!(DataValue::String("z".to_string()) < DataValue::Integer(0));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub enum DataValue {
     /// String value
     String(String),
@@ -19,10 +62,16 @@ pub enum DataValue {
     Boolean(bool),
     /// JSON value as a string
     Json(String),
-    /// Date value in ISO 8601 format
+    /// Date value in ISO 8601 format, as written
     Date(String),
-    /// DateTime value in ISO 8601 format
+    /// DateTime value in RFC 3339 format, as written
     DateTime(String),
+    /// Exact decimal number, as written
+    Decimal(String),
+    /// UUID in its hyphenated form, as written
+    Uuid(String),
+    /// Duration in ISO 8601 format, such as `PT1H30M`, as written
+    Duration(String),
     /// Custom data type with value
     Custom(String, String),
 }
@@ -37,12 +86,32 @@ impl fmt::Display for DataValue {
             DataValue::Json(j) => write!(f, "{}", j),
             DataValue::Date(d) => write!(f, "{}", d),
             DataValue::DateTime(dt) => write!(f, "{}", dt),
+            DataValue::Decimal(d) => write!(f, "{}", d),
+            DataValue::Uuid(u) => write!(f, "{}", u),
+            DataValue::Duration(d) => write!(f, "{}", d),
             DataValue::Custom(_, val) => write!(f, "{}", val),
         }
     }
 }
 
 impl DataValue {
+    // Position of the value kind in the order of values of different kinds
+    fn rank(&self) -> u8 {
+        match self {
+            DataValue::String(_) => 0,
+            DataValue::Integer(_) => 1,
+            DataValue::Float(_) => 2,
+            DataValue::Boolean(_) => 3,
+            DataValue::Json(_) => 4,
+            DataValue::Date(_) => 5,
+            DataValue::DateTime(_) => 6,
+            DataValue::Decimal(_) => 7,
+            DataValue::Uuid(_) => 8,
+            DataValue::Duration(_) => 9,
+            DataValue::Custom(_, _) => 10,
+        }
+    }
+
     /// Get the type name of this data value
     pub fn type_name(&self) -> &'static str {
         match self {
@@ -53,17 +122,132 @@ impl DataValue {
             DataValue::Json(_) => "json",
             DataValue::Date(_) => "date",
             DataValue::DateTime(_) => "datetime",
-            DataValue::Custom(custom_type, _) => {
-                // Return a static string based on the custom type
-                match custom_type.as_str() {
-                    // Add cases for known custom types if needed
-                    _ => "custom", // Default fallback for custom types
-                }
-            }
+            DataValue::Decimal(_) => "decimal",
+            DataValue::Uuid(_) => "uuid",
+            DataValue::Duration(_) => "duration",
+            DataValue::Custom(_, _) => "custom",
+        }
+    }
+
+    /// Create a custom data value
+    pub fn custom(dtype: impl Into<String>, value: impl Into<String>) -> Self {
+        DataValue::Custom(dtype.into(), value.into())
+    }
+
+    /// Get the value as a string slice for textual values
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            DataValue::String(s)
+            | DataValue::Json(s)
+            | DataValue::Date(s)
+            | DataValue::DateTime(s)
+            | DataValue::Decimal(s)
+            | DataValue::Uuid(s)
+            | DataValue::Duration(s)
+            | DataValue::Custom(_, s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an integer
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            DataValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a float, converting integers
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            DataValue::Float(f) => Some(*f),
+            DataValue::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            DataValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a date
+    #[cfg(feature = "with-chrono")]
+    pub fn as_date(&self) -> Option<NaiveDate> {
+        match self {
+            DataValue::Date(d) => d.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a date and time with its UTC offset
+    #[cfg(feature = "with-chrono")]
+    pub fn as_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        match self {
+            DataValue::DateTime(dt) => DateTime::parse_from_rfc3339(dt).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an exact decimal number
+    #[cfg(feature = "with-decimal")]
+    pub fn as_decimal(&self) -> Option<rust_decimal::Decimal> {
+        match self {
+            DataValue::Decimal(d) => d.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a UUID
+    #[cfg(feature = "with-uuid")]
+    pub fn as_uuid(&self) -> Option<uuid::Uuid> {
+        match self {
+            DataValue::Uuid(u) => u.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a duration
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            DataValue::Duration(d) => parse_duration(d),
+            _ => None,
         }
     }
 
     /// Parse a string value into a DataValue based on the specified type
+    ///
+    /// With the `with-chrono` feature, `date` values must be ISO 8601 dates
+    /// and `datetime` values RFC 3339 timestamps. They keep the text as
+    /// written and are read back with [`as_date`](DataValue::as_date) and
+    /// [`as_datetime`](DataValue::as_datetime). Likewise, `decimal` and `uuid`
+    /// values are checked with the `with-decimal` and `with-uuid` features.
+    /// `duration` values are always checked and may not use years or months,
+    /// whose length varies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ucdf::DataValue;
+    ///
+    /// let value = DataValue::parse("2024-02-29", "date").unwrap();
+    /// assert_eq!(value.to_string(), "2024-02-29");
+    /// # #[cfg(feature = "with-chrono")] {
+    /// assert_eq!(value.as_date(), chrono::NaiveDate::from_ymd_opt(2024, 2, 29));
+    /// assert!(DataValue::parse("2023-02-29", "date").is_err());
+    ///
+    /// let value = DataValue::parse("2024-05-01T12:30:00+02:00", "datetime").unwrap();
+    /// assert_eq!(value.as_datetime().unwrap().timestamp(), 1714559400);
+    /// assert!(DataValue::parse("yesterday", "datetime").is_err());
+    /// # }
+    ///
+    /// let value = DataValue::parse("P1DT1H30M", "duration").unwrap();
+    /// assert_eq!(value.as_duration().unwrap().as_secs(), 91800);
+    /// assert!(DataValue::parse("P1M", "duration").is_err());
+    /// ```
     pub fn parse(value: &str, dtype: &str) -> Result<Self> {
         match dtype {
             "str" => Ok(DataValue::String(value.to_string())),
@@ -89,30 +273,208 @@ impl DataValue {
                 ))),
             },
             "json" => Ok(DataValue::Json(value.to_string())),
+            #[cfg(feature = "with-chrono")]
+            "date" => match value.parse::<NaiveDate>() {
+                Ok(_) => Ok(DataValue::Date(value.to_string())),
+                Err(e) => Err(Error::ParseError(format!(
+                    "Failed to parse '{}' as date: {}",
+                    value, e
+                ))),
+            },
+            #[cfg(feature = "with-chrono")]
+            "datetime" => match DateTime::parse_from_rfc3339(value) {
+                Ok(_) => Ok(DataValue::DateTime(value.to_string())),
+                Err(e) => Err(Error::ParseError(format!(
+                    "Failed to parse '{}' as datetime: {}",
+                    value, e
+                ))),
+            },
+            #[cfg(not(feature = "with-chrono"))]
             "date" => Ok(DataValue::Date(value.to_string())),
+            #[cfg(not(feature = "with-chrono"))]
             "datetime" => Ok(DataValue::DateTime(value.to_string())),
+            #[cfg(feature = "with-decimal")]
+            "decimal" => match value.parse::<rust_decimal::Decimal>() {
+                Ok(_) => Ok(DataValue::Decimal(value.to_string())),
+                Err(e) => Err(Error::ParseError(format!(
+                    "Failed to parse '{}' as decimal: {}",
+                    value, e
+                ))),
+            },
+            #[cfg(not(feature = "with-decimal"))]
+            "decimal" => Ok(DataValue::Decimal(value.to_string())),
+            #[cfg(feature = "with-uuid")]
+            "uuid" => match uuid::Uuid::parse_str(value) {
+                Ok(_) => Ok(DataValue::Uuid(value.to_string())),
+                Err(e) => Err(Error::ParseError(format!(
+                    "Failed to parse '{}' as uuid: {}",
+                    value, e
+                ))),
+            },
+            #[cfg(not(feature = "with-uuid"))]
+            "uuid" => Ok(DataValue::Uuid(value.to_string())),
+            "duration" => match parse_duration(value) {
+                Some(_) => Ok(DataValue::Duration(value.to_string())),
+                None => Err(Error::ParseError(format!(
+                    "Failed to parse '{}' as duration",
+                    value
+                ))),
+            },
             _ => Ok(DataValue::Custom(dtype.to_string(), value.to_string())),
         }
     }
 }
 
-/// Field definition with name and type
+impl PartialEq for DataValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DataValue {}
+
+impl PartialOrd for DataValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DataValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (DataValue::Integer(a), DataValue::Integer(b)) => a.cmp(b),
+            (DataValue::Float(a), DataValue::Float(b)) => {
+                canonical_float(*a).total_cmp(&canonical_float(*b))
+            }
+            (DataValue::Boolean(a), DataValue::Boolean(b)) => a.cmp(b),
+            (DataValue::Custom(a, x), DataValue::Custom(b, y)) => (a, x).cmp(&(b, y)),
+            _ if self.rank() != other.rank() => self.rank().cmp(&other.rank()),
+            // Textual values of the same kind
+            _ => self.as_str().cmp(&other.as_str()),
+        }
+    }
+}
+
+impl Hash for DataValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            DataValue::Integer(i) => i.hash(state),
+            DataValue::Float(f) => canonical_float(*f).to_bits().hash(state),
+            DataValue::Boolean(b) => b.hash(state),
+            DataValue::Custom(dtype, value) => (dtype, value).hash(state),
+            _ => self.as_str().hash(state),
+        }
+    }
+}
+
+// Float with `-0.0` replaced by `0.0` and all NaNs by one positive NaN
+fn canonical_float(f: f64) -> f64 {
+    if f.is_nan() {
+        f64::NAN
+    } else if f == 0.0 {
+        0.0
+    } else {
+        f
+    }
+}
+
+// Parse an ISO 8601 duration made of weeks, days, hours, minutes and seconds,
+// such as `P2W`, `PT1H30M` or `P1DT0.5S`
+fn parse_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.strip_prefix('P')?;
+    let mut time = false;
+    let mut total = None;
+    while !rest.is_empty() {
+        if let Some(after) = rest
+            .strip_prefix('T')
+            .filter(|after| !time && !after.is_empty())
+        {
+            time = true;
+            rest = after;
+            continue;
+        }
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let unit_secs = match (time, rest[end..].chars().next()?) {
+            (false, 'W') => 604_800.0,
+            (false, 'D') => 86_400.0,
+            (true, 'H') => 3_600.0,
+            (true, 'M') => 60.0,
+            (true, 'S') => 1.0,
+            _ => return None,
+        };
+        let number: f64 = rest[..end].parse().ok()?;
+        let part = Duration::try_from_secs_f64(number * unit_secs).ok()?;
+        total = Some(total.unwrap_or(Duration::ZERO) + part);
+        rest = &rest[end + 1..];
+    }
+    total
+}
+
+/// Field modifiers accepted after the field type (`id:int:pk`)
+pub const FIELD_MODIFIERS: &[&str] = &["pk", "unique", "nullable", "required"];
+
+/// Field definition with name, type and optional modifiers
+///
+/// Field literals have the form `name:type[:modifier...]`, where the type may
+/// be generic (`tags:array<str>`) and modifiers are listed in [`FIELD_MODIFIERS`].
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
 pub struct Field {
     pub name: String,
     pub dtype: String,
+    #[serde(default)]
     pub value: Option<DataValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
 }
 
 #[bon]
 impl Field {
-    #[builder]
-    pub fn builder(name: String, dtype: String, value: Option<DataValue>) -> Self {
-        Self { name, dtype, value }
+    #[builder(on(String, into))]
+    pub fn builder(
+        name: String,
+        dtype: String,
+        value: Option<DataValue>,
+        #[builder(default)] modifiers: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            dtype,
+            value,
+            modifiers,
+        }
     }
 
-    pub fn new(name: String, dtype: String, value: Option<DataValue>) -> Self {
-        Self { name, dtype, value }
+    pub fn new(
+        name: impl Into<String>,
+        dtype: impl Into<String>,
+        value: Option<DataValue>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            dtype: dtype.into(),
+            value,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+impl Field {
+    /// Fluent API for adding a modifier
+    pub fn with_modifier(mut self, modifier: impl Into<String>) -> Self {
+        self.modifiers.push(modifier.into());
+        self
+    }
+
+    /// Check if the field is marked as primary key (`pk`)
+    pub fn is_primary_key(&self) -> bool {
+        self.has_modifier("pk")
+    }
+
+    /// Check if the field has the given modifier
+    pub fn has_modifier(&self, modifier: &str) -> bool {
+        self.modifiers.iter().any(|m| m == modifier)
     }
 }
 
@@ -120,27 +482,146 @@ impl FromStr for Field {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let parts: Vec<&str> = s.split(':').collect();
-        if parts.len() != 2 {
-            return Err(Error::InvalidFieldFormat(s.to_string()));
+        let invalid = |detail: String| Error::InvalidFieldFormat(format!("`{}`: {}", s, detail));
+
+        let (name, rest) = match split_quoted(s) {
+            // Quoted names may contain any character
+            Some((name, rest)) => {
+                let rest = rest
+                    .strip_prefix(':')
+                    .ok_or_else(|| invalid("expected `:` after quoted field name".to_string()))?;
+                (name, rest)
+            }
+            None => {
+                let (name, rest) = s
+                    .split_once(':')
+                    .ok_or_else(|| invalid("expected `name:type`".to_string()))?;
+                if let Some(c) = name.chars().find(|c| NAME_DELIMITERS.contains(c)) {
+                    return Err(invalid(format!(
+                        "unexpected `{}` in field name `{}`, quote the name to allow it",
+                        c, name
+                    )));
+                }
+                (Cow::Borrowed(name), rest)
+            }
+        };
+        if name.is_empty() {
+            return Err(invalid("empty field name".to_string()));
+        }
+
+        let mut parts = rest.split(':');
+        let dtype = parts.next().unwrap_or_default();
+        validate_type(dtype).map_err(invalid)?;
+
+        let mut modifiers = Vec::new();
+        for modifier in parts {
+            if !FIELD_MODIFIERS.contains(&modifier) {
+                return Err(invalid(format!("unknown modifier `{}`", modifier)));
+            }
+            modifiers.push(modifier.to_string());
         }
 
         Ok(Field {
-            name: parts[0].to_string(),
-            dtype: parts[1].to_string(),
+            name: name.into_owned(),
+            dtype: dtype.to_string(),
             value: None,
+            modifiers,
         })
     }
 }
 
+// Characters that need quoting in a field name
+const NAME_DELIMITERS: &[char] = &[':', ',', ';', '=', '<', '>', '"'];
+
+// Characters that need quoting in an endpoint path, which is split from the
+// method on its last `:`
+const PATH_DELIMITERS: &[char] = &[',', ';', '"'];
+
 impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}", self.name, self.dtype)
+        write!(
+            f,
+            "{}:{}",
+            quote_with(&self.name, NAME_DELIMITERS),
+            self.dtype
+        )?;
+        for modifier in &self.modifiers {
+            write!(f, ":{}", modifier)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate a type token such as `int` or `array<int>`, describing the offending part
+///
+/// Only the `<...>` parameter syntax is checked; any other token, such as
+/// `varchar(255)` or `x.geo-point`, is accepted and read as
+/// [`DataType::Custom`]. Unknown types are rejected by
+/// [`Parser::strict_types`](crate::Parser::strict_types) instead.
+fn validate_type(dtype: &str) -> std::result::Result<(), String> {
+    if dtype.is_empty() {
+        return Err("empty type".to_string());
+    }
+
+    let (base, params) = match dtype.find('<') {
+        Some(open) => {
+            let inner = dtype[open + 1..]
+                .strip_suffix('>')
+                .ok_or_else(|| format!("unclosed `<` in type `{}`", dtype))?;
+            (&dtype[..open], Some(inner))
+        }
+        None => (dtype, None),
+    };
+
+    if base.is_empty() {
+        return Err(format!("missing type name before `<` in `{}`", dtype));
+    }
+
+    if let Some(params) = params {
+        if params.is_empty() {
+            return Err(format!("missing type parameter in `{}`", dtype));
+        }
+        for param in split_list(params) {
+            validate_type(param)?;
+        }
     }
+    Ok(())
+}
+
+/// Split a comma-separated list, ignoring commas nested inside `<...>` or
+/// quoted tokens
+pub(crate) fn split_list(s: &str) -> Vec<&str> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if quoted => {}
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&s[start..]);
+    items
 }
 
 /// Endpoint definition with path and method
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-schemars", derive(schemars::JsonSchema))]
 pub struct Endpoint {
     pub path: String,
     pub method: String,
@@ -148,34 +629,52 @@ pub struct Endpoint {
 
 #[bon]
 impl Endpoint {
-    #[builder]
+    #[builder(on(String, into))]
     pub fn builder(path: String, method: String) -> Self {
         Self { path, method }
     }
 
-    pub fn new(path: String, method: String) -> Self {
-        Self { path, method }
+    pub fn new(path: impl Into<String>, method: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            method: method.into(),
+        }
     }
 }
 
 impl FromStr for Endpoint {
     type Err = Error;
 
+    /// Parse `path:method`, splitting on the last `:` so paths such as
+    /// `/v1/things:batchGet` keep their colons. The path may be quoted to
+    /// contain `,`.
     fn from_str(s: &str) -> Result<Self> {
-        let parts: Vec<&str> = s.split(':').collect();
-        if parts.len() != 2 {
-            return Err(Error::InvalidEndpointFormat(s.to_string()));
+        let invalid = || Error::InvalidEndpointFormat(s.to_string());
+        let (path, method) = match split_quoted(s) {
+            Some((path, rest)) => (path, rest.strip_prefix(':').ok_or_else(invalid)?),
+            None => {
+                let (path, method) = s.rsplit_once(':').ok_or_else(invalid)?;
+                (Cow::Borrowed(path), method)
+            }
+        };
+        if path.is_empty() || method.is_empty() || method.contains(':') {
+            return Err(invalid());
         }
 
         Ok(Endpoint {
-            path: parts[0].to_string(),
-            method: parts[1].to_string(),
+            path: path.into_owned(),
+            method: method.to_string(),
         })
     }
 }
 
 impl fmt::Display for Endpoint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}", self.path, self.method)
+        write!(
+            f,
+            "{}:{}",
+            quote_with(&self.path, PATH_DELIMITERS),
+            self.method
+        )
     }
 }
@@ -0,0 +1,461 @@
+//! Validation rules for UCDF descriptors
+//!
+//! Parsing only guarantees that a descriptor is well-formed. The [`Validator`]
+//! runs a set of [`Rule`]s over a parsed [`UCDF`] and collects the problems it
+//! finds into a [`ValidationReport`].
+//!
+//! # Examples
+//!
+//! ```
+//! use ucdf::{parse, validate::Validator};
+//!
+//! let ucdf = parse("t=db.postgresql;c.host=localhost;c.port=99999").unwrap();
+//! let report = Validator::with_defaults().validate(&ucdf);
+//! assert!(!report.is_valid());
+//! ```
+
+use std::fmt;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::sections::UCDF;
+
+/// Severity of a validation issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found by a validation rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    /// Name of the rule that reported the issue
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Key path the issue refers to, e.g. `c.port`
+    pub key: Option<String>,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(
+                f,
+                "{} [{}] {}: {}",
+                self.severity, self.rule, key, self.message
+            ),
+            None => write!(f, "{} [{}] {}", self.severity, self.rule, self.message),
+        }
+    }
+}
+
+/// Issues collected while validating a descriptor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        ValidationReport::default()
+    }
+
+    /// Record an issue
+    pub fn push(
+        &mut self,
+        rule: &str,
+        severity: Severity,
+        key: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(Issue {
+            rule: rule.to_string(),
+            severity,
+            message: message.into(),
+            key: key.map(str::to_string),
+        });
+    }
+
+    /// Check if no error-level issues were found
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Iterate over error-level issues
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.with_severity(Severity::Error)
+    }
+
+    /// Iterate over warning-level issues
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.with_severity(Severity::Warning)
+    }
+
+    /// Iterate over issues with the given severity
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+
+    /// Get the highest severity in the report
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.issues.iter().map(|issue| issue.severity).max()
+    }
+}
+
+/// A validation rule applied to a descriptor
+pub trait Rule {
+    /// Name of the rule, used to identify reported issues
+    fn name(&self) -> &str;
+
+    /// Check the descriptor, recording any issues in the report
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport);
+}
+
+/// Requires the given key paths (`a`, `c.host`, `s.fields`, ...) to be present
+#[derive(Debug, Clone)]
+pub struct RequiredKeys {
+    keys: Vec<String>,
+    severity: Severity,
+}
+
+impl RequiredKeys {
+    /// Create a rule requiring the given key paths
+    pub fn new<S: Into<String>>(keys: impl IntoIterator<Item = S>) -> Self {
+        RequiredKeys {
+            keys: keys.into_iter().map(Into::into).collect(),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Set the severity of reported issues
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+impl Rule for RequiredKeys {
+    fn name(&self) -> &str {
+        "required-keys"
+    }
+
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport) {
+        for key in &self.keys {
+            if ucdf.get_path(key).is_none() {
+                report.push(
+                    self.name(),
+                    self.severity,
+                    Some(key),
+                    "required key is missing",
+                );
+            }
+        }
+    }
+}
+
+/// Requires keys depending on the source type, e.g. `c.host` for `db.*`
+#[derive(Debug, Clone)]
+pub struct TypeKeys {
+    requirements: Vec<(String, Vec<String>)>,
+}
+
+impl TypeKeys {
+    /// Create an empty rule without requirements
+    pub fn new() -> Self {
+        TypeKeys {
+            requirements: Vec::new(),
+        }
+    }
+
+    /// Require keys for source types matching a glob pattern such as `db.*`
+    pub fn require<S: Into<String>>(
+        mut self,
+        pattern: impl Into<String>,
+        keys: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.requirements
+            .push((pattern.into(), keys.into_iter().map(Into::into).collect()));
+        self
+    }
+}
+
+impl Default for TypeKeys {
+    /// Requirements for the standard source types
+    fn default() -> Self {
+        TypeKeys::new()
+            .require("db.sqlite", ["c.path"])
+            .require("db.mongodb", ["c.uri"])
+            .require("db.*", ["c.host"])
+            .require("file.s3", ["c.bucket"])
+            .require("file.gcs", ["c.bucket"])
+            .require("file.azblob", ["c.container"])
+            .require("file.fs", ["c.root"])
+            .require("file.*", ["c.path"])
+            .require("api.*", ["c.url"])
+            .require("stream.kafka", ["c.brokers", "c.topic"])
+            .require("ref", ["c.target"])
+    }
+}
+
+impl Rule for TypeKeys {
+    fn name(&self) -> &str {
+        "type-keys"
+    }
+
+    /// Only the first requirement matching the source type is applied
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport) {
+        let requirement = self
+            .requirements
+            .iter()
+            .find(|(pattern, _)| ucdf.source_type.matches(pattern));
+        if let Some((_, keys)) = requirement {
+            for key in keys {
+                if ucdf.get_path(key).is_none() {
+                    report.push(
+                        self.name(),
+                        Severity::Error,
+                        Some(key),
+                        format!("required for source type {}", ucdf.source_type),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Requires the value of a key to match a regular expression
+#[derive(Debug, Clone)]
+pub struct ValuePattern {
+    key: String,
+    pattern: Regex,
+    severity: Severity,
+}
+
+impl ValuePattern {
+    /// Create a rule checking `key` against `pattern`
+    pub fn new(key: impl Into<String>, pattern: Regex) -> Self {
+        ValuePattern {
+            key: key.into(),
+            pattern,
+            severity: Severity::Error,
+        }
+    }
+
+    /// Set the severity of reported issues
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+impl Rule for ValuePattern {
+    fn name(&self) -> &str {
+        "value-pattern"
+    }
+
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport) {
+        if let Some(value) = ucdf.get_path(&self.key) {
+            if !self.pattern.is_match(&value) {
+                report.push(
+                    self.name(),
+                    self.severity,
+                    Some(&self.key),
+                    format!("value does not match `{}`", self.pattern),
+                );
+            }
+        }
+    }
+}
+
+/// Requires connection keys named `port` (or ending in `.port`) to be valid TCP ports
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PortRange;
+
+impl Rule for PortRange {
+    fn name(&self) -> &str {
+        "port-range"
+    }
+
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport) {
+        for (key, value) in ucdf.connection.iter() {
+            if key != "port" && !key.ends_with(".port") {
+                continue;
+            }
+            match value.parse::<u16>() {
+                Ok(port) if port > 0 => {}
+                _ => report.push(
+                    self.name(),
+                    Severity::Error,
+                    Some(&format!("c.{}", key)),
+                    format!("`{}` is not a port in range 1-65535", value),
+                ),
+            }
+        }
+    }
+}
+
+/// Requires connection keys named `url`/`uri` (or ending in `.url`/`.uri`) to be well-formed
+#[derive(Debug, Clone)]
+pub struct UrlFormat {
+    pattern: Regex,
+}
+
+impl Default for UrlFormat {
+    fn default() -> Self {
+        UrlFormat {
+            pattern: Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*://[^\s/?#]+[^\s]*$")
+                .expect("valid URL pattern"),
+        }
+    }
+}
+
+impl Rule for UrlFormat {
+    fn name(&self) -> &str {
+        "url-format"
+    }
+
+    fn check(&self, ucdf: &UCDF, report: &mut ValidationReport) {
+        for (key, value) in ucdf.connection.iter() {
+            let is_url = ["url", "uri"]
+                .iter()
+                .any(|name| key == name || key.ends_with(&format!(".{}", name)));
+            if is_url && !self.pattern.is_match(value) {
+                report.push(
+                    self.name(),
+                    Severity::Error,
+                    Some(&format!("c.{}", key)),
+                    "value is not a well-formed URL",
+                );
+            }
+        }
+    }
+}
+
+/// Runs a set of rules over descriptors
+#[derive(Default)]
+pub struct Validator {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Validator {
+    /// Create a validator without rules
+    pub fn new() -> Self {
+        Validator::default()
+    }
+
+    /// Create a validator with the built-in rules for the standard source types
+    pub fn with_defaults() -> Self {
+        Validator::new()
+            .rule(TypeKeys::default())
+            .rule(PortRange)
+            .rule(UrlFormat::default())
+            .rule(RequiredKeys::new(["a"]).severity(Severity::Warning))
+    }
+
+    /// Add a rule
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Validate a descriptor against all rules
+    pub fn validate(&self, ucdf: &UCDF) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        for rule in &self.rules {
+            rule.check(ucdf, &mut report);
+        }
+        report
+    }
+}
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validator")
+            .field(
+                "rules",
+                &self
+                    .rules
+                    .iter()
+                    .map(|rule| rule.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_default_rules() {
+        let validator = Validator::with_defaults();
+
+        let ucdf = parse("t=db.postgresql;c.host=localhost;c.port=5432;a=r").unwrap();
+        assert!(validator.validate(&ucdf).issues.is_empty());
+
+        let ucdf = parse("t=db.postgresql;c.port=70000").unwrap();
+        let report = validator.validate(&ucdf);
+        assert!(!report.is_valid());
+        let rules: Vec<&str> = report.issues.iter().map(|i| i.rule.as_str()).collect();
+        assert_eq!(rules, vec!["type-keys", "port-range", "required-keys"]);
+        assert_eq!(report.warnings().count(), 1);
+        assert_eq!(report.max_severity(), Some(Severity::Error));
+
+        let ucdf = parse("t=api.rest;c.url=\"not a url?token=abc\";a=r").unwrap();
+        let report = validator.validate(&ucdf);
+        let issue = report.errors().next().unwrap();
+        assert_eq!(issue.key.as_deref(), Some("c.url"));
+        // Messages leave out the value, which may hold credentials
+        assert_eq!(issue.message, "value is not a well-formed URL");
+
+        // Object stores name their location in other keys than `c.path`
+        let ucdf = parse("t=file.s3;c.bucket=lake;a=r").unwrap();
+        assert!(validator.validate(&ucdf).issues.is_empty());
+        let ucdf = parse("t=file.azblob;c.path=/raw;a=r").unwrap();
+        let report = validator.validate(&ucdf);
+        let keys: Vec<_> = report.errors().map(|i| i.key.as_deref()).collect();
+        assert_eq!(keys, [Some("c.container")]);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let validator =
+            Validator::new()
+                .rule(RequiredKeys::new(["m.owner"]))
+                .rule(ValuePattern::new(
+                    "m.env",
+                    Regex::new("^(dev|staging|prod)$").unwrap(),
+                ));
+
+        let ucdf = parse("t=file.csv;m.owner=data;m.env=prod").unwrap();
+        assert!(validator.validate(&ucdf).is_valid());
+
+        let ucdf = parse("t=file.csv;m.env=production").unwrap();
+        let report = validator.validate(&ucdf);
+        assert_eq!(report.errors().count(), 2);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| !i.message.contains("production")));
+    }
+}
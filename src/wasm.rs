@@ -0,0 +1,79 @@
+//! WebAssembly bindings
+//!
+//! Exposes the parser to JavaScript through `wasm-bindgen`, so browser tools
+//! validate and format descriptors with the same code as the backend. Every
+//! function takes a UCDF string and throws an `Error` with the message of the
+//! underlying [`Error`] when it cannot be parsed.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::parser;
+use crate::secrets::SecretResolver;
+use crate::validate::Validator;
+
+/// Parse a descriptor and return its canonical form
+#[wasm_bindgen]
+pub fn parse(input: &str) -> std::result::Result<String, JsError> {
+    canonical(input).map_err(js_error)
+}
+
+/// Validate a descriptor with the default rules, returning the issues as a JSON array
+#[wasm_bindgen]
+pub fn validate(input: &str) -> std::result::Result<String, JsError> {
+    issues_json(input).map_err(js_error)
+}
+
+/// Convert a descriptor to pretty-printed JSON
+#[wasm_bindgen(js_name = toJson)]
+pub fn to_json(input: &str) -> std::result::Result<String, JsError> {
+    json(input).map_err(js_error)
+}
+
+/// Replace plaintext secrets in a descriptor with `***`, returning its canonical form
+#[wasm_bindgen]
+pub fn redact(input: &str) -> std::result::Result<String, JsError> {
+    redacted(input).map_err(js_error)
+}
+
+fn js_error(e: Error) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+fn canonical(input: &str) -> Result<String> {
+    Ok(parser::parse(input)?.to_canonical_string())
+}
+
+fn issues_json(input: &str) -> Result<String> {
+    let report = Validator::with_defaults().validate(&parser::parse(input)?);
+    serde_json::to_string(&report.issues).map_err(|e| Error::ParseError(e.to_string()))
+}
+
+fn json(input: &str) -> Result<String> {
+    parser::parse(input)?.to_json()
+}
+
+fn redacted(input: &str) -> Result<String> {
+    Ok(SecretResolver::redact(&parser::parse(input)?).to_canonical_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings() {
+        let input = "t=db.postgresql;c.port=70000;c.password=secret";
+        assert_eq!(
+            redacted(input).unwrap(),
+            "t=db.postgresql;c.password=***;c.port=70000"
+        );
+
+        let issues: serde_json::Value = serde_json::from_str(&issues_json(input).unwrap()).unwrap();
+        assert_eq!(issues[0]["rule"], "type-keys");
+        assert_eq!(issues[0]["severity"], "error");
+
+        assert!(json(input).unwrap().contains("\"password\": \"secret\""));
+        assert!(canonical("c.host=db1").is_err());
+    }
+}